@@ -1,67 +1,415 @@
-use common::bigdecimal::BigDecimal;
+use crate::asset::Asset;
+use common::amount::Amount;
 use common::biginteger::BigInt;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Account {
     pub wallet: String,
-    pub balance: BigDecimal,
+    pub balance: Amount,
     pub nonce: u64,
     pub stake: BigInt,
+    #[serde(default)]
+    pub slashed: Vec<u64>,
+    #[serde(default)]
+    pub delegations: BTreeMap<String, BigInt>,
+    #[serde(default)]
+    pub delegators: BTreeMap<String, BigInt>,
+    /// Former delegators whose undelegated tokens unbond until the height.
+    #[serde(default)]
+    pub undelegating: BTreeMap<String, u64>,
+    #[serde(default)]
+    pub commission: Amount,
+    #[serde(default)]
+    pub unbonding: Vec<Unbonding>,
+    #[serde(default)]
+    pub htlcs: BTreeMap<String, Htlc>,
+    #[serde(default)]
+    pub vesting: Vec<Vesting>,
+    #[serde(default)]
+    pub issued: BTreeMap<String, Asset>,
+    #[serde(default)]
+    pub assets: BTreeMap<String, Amount>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Unbonding {
+    pub amount: BigInt,
+    pub release_at: u64,
+    /// The validator an undelegated amount was bonded to, `None` for own stake.
+    #[serde(default)]
+    pub validator: Option<String>,
+}
+
+/// Tokens granted with a cliff and linear release between the `start` and
+/// `end` block heights. Only the `released` part is in the balance, so the
+/// rest cannot be spent until it vests.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Vesting {
+    pub amount: Amount,
+    pub released: Amount,
+    pub start: u64,
+    pub cliff: u64,
+    pub end: u64,
+}
+
+impl Vesting {
+    pub fn new(amount: Amount, start: u64, cliff: u64, end: u64) -> Result<Self, String> {
+        if amount == Amount::zero() {
+            return Err(String::from("Vesting amount must be positive"));
+        }
+        if start > cliff || cliff > end {
+            return Err(String::from("Vesting must satisfy start <= cliff <= end"));
+        }
+        Ok(Self {
+            amount,
+            released: Amount::zero(),
+            start,
+            cliff,
+            end,
+        })
+    }
+
+    pub fn vested(&self, height: u64) -> Amount {
+        if height < self.cliff {
+            return Amount::zero();
+        }
+        if height >= self.end {
+            return self.amount;
+        }
+        self.amount
+            .mul_div(
+                (height - self.start) as u128,
+                (self.end - self.start) as u128,
+            )
+            .unwrap_or(self.amount)
+    }
+
+    pub fn locked(&self, height: u64) -> Amount {
+        self.amount
+            .checked_sub(self.vested(height))
+            .unwrap_or_default()
+    }
+
+    pub fn unreleased(&self) -> Amount {
+        self.amount.checked_sub(self.released).unwrap_or_default()
+    }
+}
+
+/// Funds locked by the account until `to` reveals the preimage of `hashlock`,
+/// a hex encoded SHA-256 hash, or the sender takes them back at `timeout`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Htlc {
+    pub to: String,
+    pub amount: Amount,
+    pub hashlock: String,
+    pub timeout: u64,
 }
 
 impl Account {
     pub fn new(wallet: String) -> Self {
         Self {
             wallet,
-            balance: BigDecimal::zero(),
+            balance: Amount::zero(),
             nonce: 0,
             stake: BigInt::zero(),
+            slashed: Vec::new(),
+            delegations: BTreeMap::new(),
+            delegators: BTreeMap::new(),
+            undelegating: BTreeMap::new(),
+            commission: Amount::zero(),
+            unbonding: Vec::new(),
+            htlcs: BTreeMap::new(),
+            vesting: Vec::new(),
+            issued: BTreeMap::new(),
+            assets: BTreeMap::new(),
         }
     }
 
-    pub fn credit(&mut self, amount: BigDecimal) -> Result<(), String> {
-        if amount > self.balance {
+    pub fn credit(&mut self, amount: Amount) -> Result<(), String> {
+        let Some(balance) = self.balance.checked_sub(amount) else {
             return Err(String::from("Not enough balance"));
-        }
-        self.balance -= amount;
+        };
+        self.balance = balance;
         Ok(())
     }
 
-    pub fn debit(&mut self, amount: BigDecimal) -> Result<(), String> {
-        self.balance += amount;
+    pub fn debit(&mut self, amount: Amount) -> Result<(), String> {
+        let Some(balance) = self.balance.checked_add(amount) else {
+            return Err(String::from("Balance overflow"));
+        };
+        self.balance = balance;
         Ok(())
     }
 
-    pub fn stake_amount(&mut self, amount: BigDecimal, fee: BigDecimal) -> Result<(), String> {
-        if self.balance < (fee.clone() + amount.clone()) {
-            return Err(String::from("Not enough balance"));
+    pub fn asset_balance(&self, asset: &str) -> Amount {
+        self.assets.get(asset).copied().unwrap_or_default()
+    }
+
+    pub fn credit_asset(&mut self, asset: &str, amount: Amount) -> Result<(), String> {
+        let Some(balance) = self.asset_balance(asset).checked_sub(amount) else {
+            return Err(format!("Not enough {} balance", asset));
+        };
+        if balance == Amount::zero() {
+            self.assets.remove(asset);
+        } else {
+            self.assets.insert(asset.to_string(), balance);
         }
-        let Some(stake) = amount.to_bigint() else {
+        Ok(())
+    }
+
+    pub fn debit_asset(&mut self, asset: &str, amount: Amount) -> Result<(), String> {
+        let Some(balance) = self.asset_balance(asset).checked_add(amount) else {
+            return Err(String::from("Balance overflow"));
+        };
+        self.assets.insert(asset.to_string(), balance);
+        Ok(())
+    }
+
+    pub fn stake_amount(&mut self, amount: Amount, fee: Amount) -> Result<(), String> {
+        let Some(stake) = amount.whole().map(BigInt::from_u128) else {
             return Err(String::from("Stake must be int"));
         };
-        self.balance -= amount;
-        self.balance -= fee;
+        self.credit(Self::with_fee(amount, fee)?)?;
         self.stake += stake;
         Ok(())
     }
 
-    pub fn unstake_amount(&mut self, amount: BigDecimal, fee: BigDecimal) -> Result<(), String> {
-        if self.balance < fee.clone() {
-            return Err(String::from("Not enough balance for fee"));
-        }
-        let Some(stake) = amount.to_bigint() else {
+    pub fn unstake_amount(
+        &mut self,
+        amount: Amount,
+        fee: Amount,
+        release_at: u64,
+    ) -> Result<(), String> {
+        let Some(stake) = amount.whole().map(BigInt::from_u128) else {
             return Err(String::from("Stake must be int"));
         };
         if self.stake < stake.clone() {
             return Err(String::from("Not enough stake"));
         }
-        self.balance += amount;
-        self.balance -= fee;
-        self.stake -= stake;
+        if self.balance < fee {
+            return Err(String::from("Not enough balance for fee"));
+        }
+        self.credit(fee)?;
+        self.stake -= stake.clone();
+        self.unbonding.push(Unbonding {
+            amount: stake,
+            release_at,
+            validator: None,
+        });
+        Ok(())
+    }
+
+    pub fn release_unbonded(&mut self, height: u64) -> Result<(), String> {
+        let (released, pending): (Vec<Unbonding>, Vec<Unbonding>) =
+            std::mem::take(&mut self.unbonding)
+                .into_iter()
+                .partition(|entry| entry.release_at <= height);
+        self.unbonding = pending;
+        for entry in released {
+            self.debit(Self::tokens(&entry.amount)?)?;
+        }
+        Ok(())
+    }
+
+    pub fn add_vesting(&mut self, vesting: Vesting) {
+        self.vesting.push(vesting);
+    }
+
+    /// Moves the newly vested part of every grant into the balance.
+    pub fn release_vested(&mut self, height: u64) -> Result<(), String> {
+        let mut released = Amount::zero();
+        for entry in &mut self.vesting {
+            let vested = entry.vested(height);
+            let Some(amount) = vested
+                .checked_sub(entry.released)
+                .and_then(|amount| released.checked_add(amount))
+            else {
+                return Err(String::from("Amount overflow"));
+            };
+            released = amount;
+            entry.released = vested;
+        }
+        self.vesting.retain(|entry| entry.released < entry.amount);
+        self.debit(released)
+    }
+
+    /// Returns the vesting amount not yet vested at `height`.
+    pub fn vesting_locked(&self, height: u64) -> Option<Amount> {
+        self.vesting
+            .iter()
+            .try_fold(Amount::zero(), |total, entry| {
+                total.checked_add(entry.locked(height))
+            })
+    }
+
+    /// Returns the balance spendable at `height`, including vested amounts
+    /// released with the next transaction.
+    pub fn unlocked(&self, height: u64) -> Option<Amount> {
+        self.vesting.iter().try_fold(self.balance, |total, entry| {
+            entry
+                .vested(height)
+                .checked_sub(entry.released)
+                .and_then(|releasable| total.checked_add(releasable))
+        })
+    }
+
+    pub fn unreleased_vesting(&self) -> Option<Amount> {
+        self.vesting
+            .iter()
+            .try_fold(Amount::zero(), |total, entry| {
+                total.checked_add(entry.unreleased())
+            })
+    }
+
+    pub fn slash(&mut self, idx: u64, percent: u64) -> Result<BigInt, String> {
+        if self.slashed.contains(&idx) {
+            return Err(format!("Already slashed for block #{}", idx));
+        }
+        let unbonding = self.unbonding.iter().any(|entry| entry.validator.is_none());
+        if self.stake <= BigInt::zero() && !unbonding {
+            return Err(String::from("Nothing to slash"));
+        }
+        let percent = BigInt::from_u64(percent).unwrap();
+        let hundred = BigInt::from_u64(100).unwrap();
+        let mut slashed = self.stake.clone() * percent.clone() / hundred.clone();
+        self.stake -= slashed.clone();
+        for entry in &mut self.unbonding {
+            if entry.validator.is_some() {
+                continue;
+            }
+            let cut = entry.amount.clone() * percent.clone() / hundred.clone();
+            entry.amount -= cut.clone();
+            slashed += cut;
+        }
+        self.slashed.push(idx);
+        Ok(slashed)
+    }
+
+    pub fn delegate(
+        &mut self,
+        validator: &str,
+        amount: Amount,
+        fee: Amount,
+    ) -> Result<BigInt, String> {
+        let delegated = Self::whole(&amount)?;
+        self.credit(Self::with_fee(amount, fee)?)?;
+        let entry = self
+            .delegations
+            .entry(validator.to_string())
+            .or_insert(BigInt::zero());
+        *entry += delegated.clone();
+        Ok(delegated)
+    }
+
+    /// Moves `amount` delegated to `validator` into unbonding until
+    /// `release_at`, so it stays slashable for the validator's misbehaviour.
+    pub fn undelegate(
+        &mut self,
+        validator: &str,
+        amount: Amount,
+        fee: Amount,
+        release_at: u64,
+    ) -> Result<BigInt, String> {
+        if self.balance < fee {
+            return Err(String::from("Not enough balance for fee"));
+        }
+        let undelegated = Self::whole(&amount)?;
+        let Some(delegated) = self.delegations.get_mut(validator) else {
+            return Err(format!("No delegation to {}", validator));
+        };
+        if *delegated < undelegated {
+            return Err(String::from("Not enough delegated"));
+        }
+        *delegated -= undelegated.clone();
+        if *delegated == BigInt::zero() {
+            self.delegations.remove(validator);
+        }
+        self.credit(fee)?;
+        self.unbonding.push(Unbonding {
+            amount: undelegated.clone(),
+            release_at,
+            validator: Some(validator.to_string()),
+        });
+        Ok(undelegated)
+    }
+
+    /// Cuts `percent` of the tokens delegated or unbonding from `validator`
+    /// and returns the amount cut from the active delegation and in total.
+    pub fn slash_delegation(&mut self, validator: &str, percent: u64) -> (BigInt, BigInt) {
+        let percent = BigInt::from_u64(percent).unwrap();
+        let hundred = BigInt::from_u64(100).unwrap();
+        let mut delegated = BigInt::zero();
+        if let Some(entry) = self.delegations.get_mut(validator) {
+            delegated = entry.clone() * percent.clone() / hundred.clone();
+            *entry -= delegated.clone();
+            if *entry == BigInt::zero() {
+                self.delegations.remove(validator);
+            }
+        }
+        let mut slashed = delegated.clone();
+        for entry in &mut self.unbonding {
+            if entry.validator.as_deref() == Some(validator) {
+                let cut = entry.amount.clone() * percent.clone() / hundred.clone();
+                entry.amount -= cut.clone();
+                slashed += cut;
+            }
+        }
+        (delegated, slashed)
+    }
+
+    pub fn add_delegator(&mut self, delegator: &str, amount: BigInt) {
+        let entry = self
+            .delegators
+            .entry(delegator.to_string())
+            .or_insert(BigInt::zero());
+        *entry += amount;
+    }
+
+    pub fn remove_delegator(&mut self, delegator: &str, amount: BigInt) -> Result<(), String> {
+        let Some(delegated) = self.delegators.get_mut(delegator) else {
+            return Err(format!("Unknown delegator {}", delegator));
+        };
+        if *delegated < amount {
+            return Err(String::from("Not enough delegated"));
+        }
+        *delegated -= amount;
+        if *delegated == BigInt::zero() {
+            self.delegators.remove(delegator);
+        }
+        Ok(())
+    }
+
+    pub fn set_commission(&mut self, commission: Amount, fee: Amount) -> Result<(), String> {
+        if commission > Amount::from_whole(1).unwrap() {
+            return Err(String::from("Commission must be between 0 and 1"));
+        }
+        if self.balance < fee {
+            return Err(String::from("Not enough balance for fee"));
+        }
+        self.credit(fee)?;
+        self.commission = commission;
         Ok(())
     }
 
+    fn whole(amount: &Amount) -> Result<BigInt, String> {
+        match amount.whole() {
+            Some(value) if value > 0 => Ok(BigInt::from_u128(value)),
+            _ => Err(String::from("Delegation must be positive int")),
+        }
+    }
+
+    fn tokens(amount: &BigInt) -> Result<Amount, String> {
+        Amount::from_bigint(amount).ok_or_else(|| String::from("Amount overflow"))
+    }
+
+    fn with_fee(amount: Amount, fee: Amount) -> Result<Amount, String> {
+        amount
+            .checked_add(fee)
+            .ok_or_else(|| String::from("Amount overflow"))
+    }
+
     pub fn set_nonce(&mut self, nonce: u64) -> Result<(), String> {
         if self.nonce + 1 != nonce {
             return Err(format!(
@@ -78,8 +426,8 @@ impl Account {
         self.wallet.clone()
     }
 
-    pub fn balance(&self) -> BigDecimal {
-        self.balance.clone()
+    pub fn balance(&self) -> Amount {
+        self.balance
     }
 
     pub fn nonce(&self) -> u64 {
@@ -89,4 +437,37 @@ impl Account {
     pub fn stake(&self) -> BigInt {
         self.stake.clone()
     }
+
+    pub fn unbonding_amount(&self) -> BigInt {
+        self.unbonding
+            .iter()
+            .fold(BigInt::zero(), |total, entry| total + entry.amount.clone())
+    }
+
+    pub fn locked_amount(&self) -> Option<Amount> {
+        self.htlcs
+            .values()
+            .try_fold(Amount::zero(), |total, htlc| total.checked_add(htlc.amount))
+    }
+
+    pub fn delegated(&self) -> BigInt {
+        self.delegators
+            .values()
+            .fold(BigInt::zero(), |total, amount| total + amount.clone())
+    }
+
+    pub fn bonded(&self) -> BigInt {
+        self.delegations
+            .values()
+            .fold(BigInt::zero(), |total, amount| total + amount.clone())
+    }
+
+    /// Returns the validator weight: own stake plus delegations, counted only
+    /// while the validator keeps a stake of its own.
+    pub fn weight(&self) -> BigInt {
+        if self.stake <= BigInt::zero() {
+            return BigInt::zero();
+        }
+        self.stake() + self.delegated()
+    }
 }
@@ -1,4 +1,6 @@
 use crate::account::Account;
+use common::codec::{decode, encode};
+use db::batch::Batch;
 use rocksdb::{DBWithThreadMode, MultiThreaded};
 use std::collections::{BTreeMap, HashSet};
 use std::error::Error;
@@ -16,22 +18,41 @@ impl AccountStorage {
     }
 
     pub fn save(&self, account: &Account) -> Result<(), Box<dyn Error>> {
-        let mut wallets = self.all_balances_wallets()?;
-        wallets.insert(account.wallet());
-        let json = serde_json::to_vec(&account)?;
-        let key = Self::build_key(&account.wallet());
-        self.save_all_balances_wallets(&wallets)?;
-        self.db.put(key, &json)?;
-        Ok(())
+        self.save_all(&vec![account.clone()])
     }
 
     pub fn save_all(&self, balances: &Vec<Account>) -> Result<(), Box<dyn Error>> {
-        let mut wallets = self.all_balances_wallets()?;
+        let mut batch = Batch::new(&self.db);
+        self.stage_all(&mut batch, balances)?;
+        batch.commit()?;
+        Ok(())
+    }
+
+    pub fn stage_all(
+        &self,
+        batch: &mut Batch,
+        balances: &Vec<Account>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut wallets = Self::staged_balances_wallets(batch)?;
         for balance in balances {
-            wallets.insert(balance.wallet.clone());
-            self.save(balance)?;
+            wallets.insert(balance.wallet());
+            batch.put(Self::build_key(&balance.wallet()), encode(balance)?);
+        }
+        batch.put(ALL_ACCOUNT_WALLET_KEY, encode(&wallets)?);
+        Ok(())
+    }
+
+    pub fn stage_remove(
+        &self,
+        batch: &mut Batch,
+        wallets: &Vec<String>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut all_wallets = Self::staged_balances_wallets(batch)?;
+        for wallet in wallets {
+            all_wallets.remove(wallet);
+            batch.delete(Self::build_key(wallet));
         }
-        self.save_all_balances_wallets(&wallets)?;
+        batch.put(ALL_ACCOUNT_WALLET_KEY, encode(&all_wallets)?);
         Ok(())
     }
 
@@ -41,9 +62,17 @@ impl AccountStorage {
     }
 
     pub fn find(&self, wallet: String) -> Result<Option<Account>, Box<dyn Error>> {
-        let key = Self::build_key(&wallet);
-        if let Some(json) = self.db.get(key)? {
-            let value: Account = serde_json::from_slice(&json)?;
+        self.find_staged(&Batch::new(&self.db), wallet)
+    }
+
+    /// Finds the account of `wallet`, seeing accounts staged in `batch`.
+    pub fn find_staged(
+        &self,
+        batch: &Batch,
+        wallet: String,
+    ) -> Result<Option<Account>, Box<dyn Error>> {
+        if let Some(data) = batch.get(Self::build_key(&wallet))? {
+            let value: Account = decode(&data)?;
             return Ok(Some(value));
         }
         Ok(None)
@@ -52,10 +81,18 @@ impl AccountStorage {
     pub fn find_all(
         &self,
         wallets: &HashSet<String>,
+    ) -> Result<BTreeMap<String, Account>, Box<dyn Error>> {
+        self.find_all_staged(&Batch::new(&self.db), wallets)
+    }
+
+    pub fn find_all_staged(
+        &self,
+        batch: &Batch,
+        wallets: &HashSet<String>,
     ) -> Result<BTreeMap<String, Account>, Box<dyn Error>> {
         let mut balances = BTreeMap::new();
         for wallet in wallets {
-            if let Some(balance) = self.find(wallet.clone())? {
+            if let Some(balance) = self.find_staged(batch, wallet.clone())? {
                 balances.insert(wallet.clone(), balance);
             }
         }
@@ -64,16 +101,18 @@ impl AccountStorage {
 
     fn all_balances_wallets(&self) -> Result<HashSet<String>, Box<dyn Error>> {
         if let Some(wallets) = self.db.get(String::from(ALL_ACCOUNT_WALLET_KEY))? {
-            Ok(serde_json::from_slice(&wallets)?)
+            Ok(decode(&wallets)?)
         } else {
             Ok(HashSet::new())
         }
     }
 
-    fn save_all_balances_wallets(&self, wallets: &HashSet<String>) -> Result<(), Box<dyn Error>> {
-        let json = serde_json::to_vec(&wallets)?;
-        self.db.put(String::from(ALL_ACCOUNT_WALLET_KEY), json)?;
-        Ok(())
+    fn staged_balances_wallets(batch: &Batch) -> Result<HashSet<String>, Box<dyn Error>> {
+        if let Some(wallets) = batch.get(ALL_ACCOUNT_WALLET_KEY)? {
+            Ok(decode(&wallets)?)
+        } else {
+            Ok(HashSet::new())
+        }
     }
 
     fn build_key(key: &str) -> String {
@@ -0,0 +1,92 @@
+use common::amount::{Amount, DECIMALS};
+use serde::{Deserialize, Serialize};
+
+pub const MAX_SYMBOL_LEN: usize = 12;
+
+/// Token issued on the chain besides the native coin. Definitions are kept in
+/// the issuer's account under their symbol, and holders' balances under the
+/// asset id, `<issuer>:<symbol>`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Asset {
+    pub issuer: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub max_supply: Amount,
+    pub mint_authority: String,
+    pub supply: Amount,
+}
+
+impl Asset {
+    pub fn new(
+        issuer: String,
+        symbol: String,
+        decimals: u8,
+        max_supply: Amount,
+        mint_authority: String,
+    ) -> Result<Self, String> {
+        if symbol.is_empty()
+            || symbol.len() > MAX_SYMBOL_LEN
+            || !symbol
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        {
+            return Err(format!(
+                "Asset symbol must be 1 to {} uppercase letters or digits",
+                MAX_SYMBOL_LEN
+            ));
+        }
+        if decimals as usize > DECIMALS {
+            return Err(format!("Asset decimals cannot exceed {}", DECIMALS));
+        }
+        let asset = Self {
+            issuer,
+            symbol,
+            decimals,
+            max_supply,
+            mint_authority,
+            supply: Amount::zero(),
+        };
+        if max_supply == Amount::zero() || !asset.valid_amount(max_supply) {
+            return Err(String::from("Invalid asset max supply"));
+        }
+        Ok(asset)
+    }
+
+    pub fn id(&self) -> String {
+        Self::build_id(&self.issuer, &self.symbol)
+    }
+
+    pub fn build_id(issuer: &str, symbol: &str) -> String {
+        format!("{}:{}", issuer, symbol)
+    }
+
+    /// Splits an asset id into its issuer and symbol.
+    pub fn parse_id(id: &str) -> Option<(&str, &str)> {
+        id.split_once(':')
+    }
+
+    /// Returns true for positive amounts without more fractional digits than
+    /// the asset has decimals.
+    pub fn valid_amount(&self, amount: Amount) -> bool {
+        let step = 10u128.pow((DECIMALS - self.decimals as usize) as u32);
+        amount != Amount::zero() && amount.units().is_multiple_of(step)
+    }
+
+    pub fn mint(&mut self, amount: Amount) -> Result<(), String> {
+        match self.supply.checked_add(amount) {
+            Some(supply) if supply <= self.max_supply => {
+                self.supply = supply;
+                Ok(())
+            }
+            _ => Err(format!("{} max supply exceeded", self.symbol)),
+        }
+    }
+
+    pub fn burn(&mut self, amount: Amount) -> Result<(), String> {
+        let Some(supply) = self.supply.checked_sub(amount) else {
+            return Err(format!("Not enough {} supply", self.symbol));
+        };
+        self.supply = supply;
+        Ok(())
+    }
+}
@@ -1,2 +1,3 @@
 pub mod account;
 pub mod account_storage;
+pub mod asset;
@@ -1,15 +1,15 @@
 use account::account::Account;
-use common::bigdecimal::BigDecimal;
+use common::amount::Amount;
 
 #[test]
 fn account_debit_credit() {
     let mut account = Account::new(String::from("wallet"));
 
-    account.debit(BigDecimal::from_str("1").unwrap()).unwrap();
+    account.debit(Amount::from_str("1").unwrap()).unwrap();
 
-    assert!(account.credit(BigDecimal::from_str("1").unwrap()).is_ok());
+    assert!(account.credit(Amount::from_str("1").unwrap()).is_ok());
 
-    match account.credit(BigDecimal::from_str("2").unwrap()) {
+    match account.credit(Amount::from_str("2").unwrap()) {
         Ok(_) => assert!(false, "Credit operation not validated"),
         Err(e) => assert_eq!(e, "Not enough balance"),
     }
@@ -18,11 +18,11 @@ fn account_debit_credit() {
 #[test]
 fn account_stake() {
     let mut account = Account::new(String::from("wallet"));
-    account.debit(BigDecimal::from_str("2.01").unwrap()).unwrap();
+    account.debit(Amount::from_str("2.01").unwrap()).unwrap();
 
-    assert!(account.stake_amount(BigDecimal::from_str("1").unwrap(), BigDecimal::from_str("0.1").unwrap()).is_ok());
+    assert!(account.stake_amount(Amount::from_str("1").unwrap(), Amount::from_str("0.1").unwrap()).is_ok());
 
-    match account.stake_amount(BigDecimal::from_str("1").unwrap(), BigDecimal::from_str("1").unwrap()) {
+    match account.stake_amount(Amount::from_str("1").unwrap(), Amount::from_str("1").unwrap()) {
         Ok(_) => assert!(false, "Credit operation not validated"),
         Err(e) => assert_eq!(e, "Not enough balance"),
     }
@@ -31,13 +31,13 @@ fn account_stake() {
 #[test]
 fn account_unstake() {
     let mut account = Account::new(String::from("wallet"));
-    account.debit(BigDecimal::from_str("2.01").unwrap()).unwrap();
+    account.debit(Amount::from_str("2.01").unwrap()).unwrap();
 
-    assert!(account.stake_amount(BigDecimal::from_str("1").unwrap(), BigDecimal::from_str("0.1").unwrap()).is_ok());
+    assert!(account.stake_amount(Amount::from_str("1").unwrap(), Amount::from_str("0.1").unwrap()).is_ok());
 
-    account.unstake_amount(BigDecimal::from_str("1").unwrap(), BigDecimal::from_str("0.1").unwrap()).unwrap();
+    account.unstake_amount(Amount::from_str("1").unwrap(), Amount::from_str("0.1").unwrap(), 10).unwrap();
 
-    match account.unstake_amount(BigDecimal::from_str("1").unwrap(), BigDecimal::from_str("1").unwrap()) {
+    match account.unstake_amount(Amount::from_str("1").unwrap(), Amount::from_str("1").unwrap(), 10) {
         Ok(_) => assert!(false, "Credit operation not validated"),
         Err(e) => assert_eq!(e, "Not enough stake"),
     }
@@ -46,11 +46,11 @@ fn account_unstake() {
 #[test]
 fn account_unstake_invalid_fee() {
     let mut account = Account::new(String::from("wallet"));
-    account.debit(BigDecimal::from_str("2").unwrap()).unwrap();
+    account.debit(Amount::from_str("2").unwrap()).unwrap();
 
-    assert!(account.stake_amount(BigDecimal::from_str("1").unwrap(), BigDecimal::from_str("0.1").unwrap()).is_ok());
+    assert!(account.stake_amount(Amount::from_str("1").unwrap(), Amount::from_str("0.1").unwrap()).is_ok());
 
-    match account.unstake_amount(BigDecimal::from_str("1").unwrap(), BigDecimal::from_str("1").unwrap()) {
+    match account.unstake_amount(Amount::from_str("1").unwrap(), Amount::from_str("1").unwrap(), 10) {
         Ok(_) => assert!(false, "Credit operation not validated"),
         Err(e) => assert_eq!(e, "Not enough balance for fee"),
     }
@@ -1,15 +1,25 @@
 use crypto::crypto::verify_signature;
+use crypto::vrf::verify_proof;
 use serde::{Deserialize, Serialize};
-use sha2::Digest;
+use tx::evidence::SignedHeader;
 use tx::tx::Tx;
 use wallet::wallet::Wallet;
 
+pub const SLOT_DURATION: u64 = 12;
+/// Most transactions a block can carry besides its coinbase.
+pub const MAX_BLOCK_TXS: usize = 1000;
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Block {
+    #[serde(default)]
+    pub chain_id: String,
     pub idx: u64,
+    pub slot: u64,
+    pub timestamp: u64,
     pub validator: String,
     pub parent_hash: String,
     pub merkle_root: String,
+    pub vrf_proof: String,
     pub txs: Option<Vec<Tx>>,
     pub signature: String,
 }
@@ -17,16 +27,24 @@ pub struct Block {
 impl Block {
     pub fn new(
         wallet: &Wallet,
+        chain_id: String,
         idx: u64,
         parent_hash: String,
+        timestamp: u64,
         txs: Vec<Tx>,
     ) -> Result<Self, std::io::Error> {
         let merkle_root = Self::merkle_root(&txs);
+        let slot = Self::slot_at(timestamp);
+        let (vrf_proof, _) = wallet.vrf_prove(&Self::vrf_input(&parent_hash, idx, slot))?;
         let mut block = Self {
+            chain_id,
             idx,
+            slot,
+            timestamp,
             validator: wallet.address_str(),
             parent_hash,
             merkle_root: bs58::encode(merkle_root).into_string(),
+            vrf_proof,
             txs: Some(txs),
             signature: String::from(""),
         };
@@ -34,15 +52,19 @@ impl Block {
         Ok(block)
     }
 
-    pub fn genesis(txs: Vec<Tx>) -> Self {
+    pub fn genesis(chain_id: String, txs: Vec<Tx>) -> Self {
         let merkle_root = Block::merkle_root(&txs);
         let validator = [0u8; 33];
         let parent_hash = [0u8; 32];
         Block {
+            chain_id,
             idx: 0,
+            slot: 0,
+            timestamp: 0,
             validator: bs58::encode(validator).into_string(),
             parent_hash: bs58::encode(parent_hash).into_string(),
             merkle_root: bs58::encode(merkle_root).into_string(),
+            vrf_proof: String::default(),
             txs: Some(txs),
             signature: String::from("GENESIS"),
         }
@@ -53,14 +75,47 @@ impl Block {
     }
 
     pub fn hash(&self) -> [u8; 32] {
-        let mut hasher = sha2::Sha256::new();
-        hasher.update(self.idx.to_be_bytes());
-        hasher.update(self.validator.as_bytes());
-        hasher.update(self.parent_hash.as_bytes());
-        hasher.update(self.merkle_root.as_bytes());
-        hasher.finalize().into()
+        self.header().hash()
+    }
+
+    pub fn header(&self) -> SignedHeader {
+        SignedHeader {
+            chain_id: self.chain_id.clone(),
+            idx: self.idx,
+            slot: self.slot,
+            timestamp: self.timestamp,
+            validator: self.validator.clone(),
+            parent_hash: self.parent_hash.clone(),
+            merkle_root: self.merkle_root.clone(),
+            vrf_proof: self.vrf_proof.clone(),
+            signature: self.signature.clone(),
+        }
+    }
+
+    pub fn slot_at(timestamp: u64) -> u64 {
+        timestamp / SLOT_DURATION
+    }
+
+    pub fn vrf_input(parent_hash: &str, idx: u64, slot: u64) -> Vec<u8> {
+        let mut input = parent_hash.as_bytes().to_vec();
+        input.extend_from_slice(&idx.to_be_bytes());
+        input.extend_from_slice(&slot.to_be_bytes());
+        input
     }
-    
+
+    pub fn vrf_output(&self) -> Option<[u8; 32]> {
+        let public_key = bs58::decode(&self.validator)
+            .into_vec()
+            .ok()?
+            .try_into()
+            .ok()?;
+        verify_proof(
+            public_key,
+            &self.vrf_proof,
+            &Self::vrf_input(&self.parent_hash, self.idx, self.slot),
+        )
+    }
+
     pub fn validator(&self) -> String {
         self.validator.clone()
     }
@@ -83,12 +138,19 @@ impl Block {
         }
     }
 
+    /// Blocks without their txs, as stored, cannot be checked and are invalid.
     pub fn valid(&self) -> bool {
-        let merkle_root = Block::merkle_root(self.txs.as_ref().unwrap());
+        if self.slot != Self::slot_at(self.timestamp) || self.vrf_output().is_none() {
+            return false;
+        }
+        let Some(txs) = &self.txs else {
+            return false;
+        };
+        let merkle_root = Block::merkle_root(txs);
         if self.merkle_root != bs58::encode(merkle_root).into_string() {
             return false;
         }
-        for tx in self.txs.as_ref().unwrap() {
+        for tx in txs {
             if !tx.valid() {
                 return false;
             }
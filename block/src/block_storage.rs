@@ -1,8 +1,12 @@
 use crate::block::Block;
+use common::codec::{decode, encode};
+use db::batch::Batch;
 use rocksdb::{DBWithThreadMode, MultiThreaded};
 use std::str::FromStr;
 use std::sync::Arc;
 
+const LATEST_IDX_KEY: &str = "block.latest";
+
 pub struct BlockStorage {
     db: Arc<DBWithThreadMode<MultiThreaded>>,
 }
@@ -13,31 +17,43 @@ impl BlockStorage {
     }
 
     pub fn save(&self, block: &Block) -> Result<(), std::io::Error> {
+        let mut batch = Batch::new(&self.db);
+        self.stage(&mut batch, block)?;
+        batch.commit()
+    }
+
+    pub fn stage(&self, batch: &mut Batch, block: &Block) -> Result<(), std::io::Error> {
         let mut block = block.clone();
         block.txs = None;
-        let json = serde_json::to_vec(&block)?;
-        let key = self.build_key(&block.idx.to_string());
-        self.db
-            .put(key, json)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
-        let key = self.build_key(&block.hash_str());
-        self.db
-            .put(key, block.idx.to_string())
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
-        self.db
-            .put("block.latest", block.idx.to_string())
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        let data = encode(&block)?;
+        batch.put(self.build_key(&block.idx.to_string()), data);
+        batch.put(self.build_key(&block.hash_str()), block.idx.to_string());
+        batch.put(LATEST_IDX_KEY, block.idx.to_string());
         Ok(())
     }
 
+    pub fn stage_remove(&self, batch: &mut Batch, block: &Block) {
+        batch.delete(self.build_key(&block.idx.to_string()));
+        batch.delete(self.build_key(&block.hash_str()));
+        if block.idx == 0 {
+            batch.delete(LATEST_IDX_KEY);
+        } else {
+            batch.put(LATEST_IDX_KEY, (block.idx - 1).to_string());
+        }
+    }
+
     pub fn find_by_idx(&self, idx: u64) -> Result<Option<Block>, std::io::Error> {
-        let key = self.build_key(&idx.to_string());
-        if let Some(json) = self
-            .db
-            .get(key)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?
-        {
-            let block: Block = serde_json::from_slice(&json)?;
+        self.find_staged_by_idx(&Batch::new(&self.db), idx)
+    }
+
+    /// Finds the block at `idx`, seeing blocks staged in `batch`.
+    pub fn find_staged_by_idx(
+        &self,
+        batch: &Batch,
+        idx: u64,
+    ) -> Result<Option<Block>, std::io::Error> {
+        if let Some(data) = batch.get(self.build_key(&idx.to_string()))? {
+            let block: Block = decode(&data)?;
             Ok(Some(block))
         } else {
             Ok(None)
@@ -58,13 +74,15 @@ impl BlockStorage {
     }
 
     pub fn find_latest(&self) -> Result<Option<Block>, std::io::Error> {
-        if let Some(idx) = self
-            .db
-            .get("block.latest")
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?
-        {
-            let block =
-                self.find_by_idx(u64::from_str(&String::from_utf8(idx).unwrap()).unwrap())?;
+        self.find_staged_latest(&Batch::new(&self.db))
+    }
+
+    pub fn find_staged_latest(&self, batch: &Batch) -> Result<Option<Block>, std::io::Error> {
+        if let Some(idx) = batch.get(LATEST_IDX_KEY)? {
+            let block = self.find_staged_by_idx(
+                batch,
+                u64::from_str(&String::from_utf8(idx).unwrap()).unwrap(),
+            )?;
             Ok(block)
         } else {
             Ok(None)
@@ -1,2 +1,3 @@
 pub mod block;
-pub mod block_storage;
\ No newline at end of file
+pub mod block_storage;
+pub mod vote;
\ No newline at end of file
@@ -0,0 +1,45 @@
+use crate::block::Block;
+use common::codec::encode;
+use crypto::crypto::verify_signature;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use wallet::wallet::Wallet;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Vote {
+    pub idx: u64,
+    pub block_hash: String,
+    pub validator: String,
+    pub signature: String,
+}
+
+impl Vote {
+    pub fn new(wallet: &Wallet, block: &Block) -> Result<Self, std::io::Error> {
+        let mut vote = Self {
+            idx: block.idx,
+            block_hash: block.hash_str(),
+            validator: wallet.address_str(),
+            signature: String::default(),
+        };
+        vote.signature = wallet.sign(&vote.hash())?;
+        Ok(vote)
+    }
+
+    pub fn hash(&self) -> [u8; 32] {
+        let data = encode(&(self.idx, &self.block_hash, &self.validator)).unwrap();
+        sha2::Sha256::digest(data).into()
+    }
+
+    pub fn valid(&self) -> bool {
+        match bs58::decode(&self.validator).into_vec() {
+            Ok(public_key) => {
+                if let Ok(public_key) = public_key.try_into() {
+                    verify_signature(public_key, &self.signature, &self.hash())
+                } else {
+                    false
+                }
+            }
+            Err(_) => false,
+        }
+    }
+}
@@ -11,6 +11,7 @@ fn test_block_save() {
     let wallet = Wallet::new();
     let tx_data = TxData::new(
         &wallet,
+        String::default(),
         wallet.address_str(),
         String::from("0.001"),
         String::from("0"),
@@ -19,7 +20,7 @@ fn test_block_save() {
     .unwrap();
     let tx = Tx::from_tx(tx_data, String::default(), 0);
     let txs = vec![tx];
-    let block = Block::genesis(txs);
+    let block = Block::genesis(String::default(), txs);
 
     let db = db::open(temp_dir.path()).unwrap();
     let block_storage = BlockStorage::new(&db);
@@ -1,4 +1,5 @@
-use block::block::Block;
+use block::block::{Block, SLOT_DURATION};
+use common::codec::{decode, encode};
 use tx::tx::Tx;
 use tx::tx_data::TxData;
 use wallet::wallet::Wallet;
@@ -8,6 +9,7 @@ fn test_genesis_block_creation() -> Result<(), std::io::Error> {
     let wallet = Wallet::new();
     let tx_data = TxData::new(
         &wallet,
+        String::default(),
         wallet.address_str(),
         String::from("1"),
         String::from("0"),
@@ -16,10 +18,10 @@ fn test_genesis_block_creation() -> Result<(), std::io::Error> {
     let tx = Tx::from_tx(tx_data, String::default(), 0);
     let txs = vec![tx];
 
-    let block1 = Block::genesis(txs.clone());
+    let block1 = Block::genesis(String::default(), txs.clone());
     assert_eq!(block1.idx, 0);
 
-    let block2 = Block::genesis(txs);
+    let block2 = Block::genesis(String::default(), txs);
     assert_eq!(block2.idx, 0);
 
     assert_eq!(block1.hash(), block2.hash());
@@ -32,6 +34,7 @@ fn test_new_block_creation() -> Result<(), std::io::Error> {
     let wallet = Wallet::new();
     let tx_data = TxData::new(
         &wallet,
+        String::default(),
         wallet.address_str(),
         String::from("1"),
         String::from("0"),
@@ -40,7 +43,115 @@ fn test_new_block_creation() -> Result<(), std::io::Error> {
     let tx = Tx::from_tx(tx_data, String::default(), 0);
     let txs = vec![tx];
 
-    let genesis = Block::genesis(txs.clone());
-    Block::new(&wallet, 1, genesis.hash_str(), txs)?;
+    let genesis = Block::genesis(String::default(), txs.clone());
+    let block = Block::new(&wallet, String::default(), 1, genesis.hash_str(), 25, txs)?;
+    assert_eq!(block.slot, 25 / SLOT_DURATION);
+    assert!(block.valid());
+    Ok(())
+}
+
+#[test]
+fn test_block_vrf_proof() -> Result<(), std::io::Error> {
+    let wallet = Wallet::new();
+    let genesis = Block::genesis(String::default(), vec![]);
+    let timestamp = 3 * SLOT_DURATION;
+    let block = Block::new(
+        &wallet,
+        String::default(),
+        1,
+        genesis.hash_str(),
+        timestamp,
+        vec![],
+    )?;
+    let (_, output) = wallet.vrf_prove(&Block::vrf_input(&genesis.hash_str(), 1, 3))?;
+    assert_eq!(block.vrf_output(), Some(output));
+
+    let mut replayed = block.clone();
+    replayed.slot = 4;
+    replayed.timestamp = 4 * SLOT_DURATION;
+    assert_eq!(replayed.vrf_output(), None);
+    assert!(!replayed.valid());
+
+    let mut shifted = block.clone();
+    shifted.timestamp = 4 * SLOT_DURATION;
+    assert!(!shifted.valid());
+
+    let other = Block::new(
+        &Wallet::new(),
+        String::default(),
+        1,
+        genesis.hash_str(),
+        timestamp,
+        vec![],
+    )?;
+    let mut stolen = block.clone();
+    stolen.vrf_proof = other.vrf_proof;
+    assert_eq!(stolen.vrf_output(), None);
+    Ok(())
+}
+
+#[test]
+fn header_hash_matches_block_hash() -> Result<(), std::io::Error> {
+    let wallet = Wallet::new();
+    let block = Block::new(
+        &wallet,
+        String::default(),
+        1,
+        String::from("parent"),
+        SLOT_DURATION,
+        vec![],
+    )?;
+    let header = block.header();
+    assert_eq!(header.hash(), block.hash());
+    assert!(header.valid());
+    Ok(())
+}
+
+#[test]
+fn encoding_round_trips() -> Result<(), std::io::Error> {
+    let wallet = Wallet::new();
+    let tx_data = TxData::new(
+        &wallet,
+        String::from("xchg-testnet"),
+        Wallet::new().address_str(),
+        String::from("10"),
+        String::from("0"),
+        1,
+    )?;
+    let tx = Tx::from_tx(tx_data, String::default(), 1);
+    let block = Block::new(
+        &wallet,
+        String::from("xchg-testnet"),
+        1,
+        String::from("parent"),
+        SLOT_DURATION,
+        vec![tx],
+    )?;
+    let restored: Block = decode(&encode(&block)?)?;
+    assert_eq!(restored, block);
+    assert_eq!(restored.hash(), block.hash());
+    assert!(restored.valid());
+
+    let mut data = encode(&block)?;
+    data.push(0);
+    assert!(decode::<Block>(&data).is_err());
+    Ok(())
+}
+
+#[test]
+fn block_without_txs_is_invalid() -> Result<(), std::io::Error> {
+    let wallet = Wallet::new();
+    let genesis = Block::genesis(String::default(), vec![]);
+    let mut block = Block::new(
+        &wallet,
+        String::default(),
+        1,
+        genesis.hash_str(),
+        3 * SLOT_DURATION,
+        vec![],
+    )?;
+    block.txs = None;
+    let decoded: Block = decode(&encode(&block)?)?;
+    assert!(!decoded.valid());
     Ok(())
 }
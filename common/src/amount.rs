@@ -0,0 +1,134 @@
+use crate::biginteger::BigInt;
+use bigdecimal::ToPrimitive;
+use bigdecimal::num_bigint::BigUint;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+
+/// Number of decimal places an amount can carry.
+pub const DECIMALS: usize = 18;
+const UNIT: u128 = 10u128.pow(DECIMALS as u32);
+
+/// Token amount stored as an integer count of base units, `10^-DECIMALS` of a
+/// token each, so arithmetic is exact and identical on every node.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(u128);
+
+impl Amount {
+    pub fn zero() -> Self {
+        Self(0)
+    }
+
+    pub fn from_units(units: u128) -> Self {
+        Self(units)
+    }
+
+    pub fn units(&self) -> u128 {
+        self.0
+    }
+
+    pub fn from_whole(value: u128) -> Option<Self> {
+        value.checked_mul(UNIT).map(Self)
+    }
+
+    /// Converts a whole token count, as used for stakes, into an amount.
+    pub fn from_bigint(value: &BigInt) -> Option<Self> {
+        value.to_u128().and_then(Self::from_whole)
+    }
+
+    /// Returns the number of whole tokens if the amount has no fractional part.
+    pub fn whole(&self) -> Option<u128> {
+        if self.0.is_multiple_of(UNIT) {
+            Some(self.0 / UNIT)
+        } else {
+            None
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(value: &str) -> Result<Self, std::io::Error> {
+        let invalid = |reason: &str| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid amount '{}': {}", value, reason),
+            )
+        };
+        let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(invalid("empty"));
+        }
+        if !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid("not a positive decimal"));
+        }
+        if fraction.len() > DECIMALS {
+            return Err(invalid("too many decimals"));
+        }
+        let whole: u128 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| invalid("too large"))?
+        };
+        let fraction: u128 = format!("{:0<width$}", fraction, width = DECIMALS)
+            .parse()
+            .map_err(|_| invalid("not a positive decimal"))?;
+        Self::from_whole(whole)
+            .and_then(|amount| amount.checked_add(Self(fraction)))
+            .ok_or_else(|| invalid("too large"))
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.0.checked_sub(rhs.0).map(Self)
+    }
+
+    /// Multiplies two amounts, truncating the result to `DECIMALS` places.
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        self.mul_div(rhs.0, UNIT)
+    }
+
+    /// Returns `self * numerator / denominator` truncated to base units.
+    pub fn mul_div(self, numerator: u128, denominator: u128) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        let result = BigUint::from(self.0) * BigUint::from(numerator) / BigUint::from(denominator);
+        result.to_u128().map(Self)
+    }
+}
+
+impl Display for Amount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let whole = self.0 / UNIT;
+        let fraction = self.0 % UNIT;
+        if fraction == 0 {
+            return write!(f, "{}", whole);
+        }
+        let fraction = format!("{:0>width$}", fraction, width = DECIMALS);
+        write!(f, "{}.{}", whole, fraction.trim_end_matches('0'))
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'a> Deserialize<'a> for Amount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        let value = String::deserialize(deserializer)?;
+        Self::from_str(&value).map_err(serde::de::Error::custom)
+    }
+}
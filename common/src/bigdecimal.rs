@@ -96,7 +96,7 @@ impl Serialize for BigDecimal {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.0.to_plain_string())
+        serializer.serialize_str(&self.0.normalized().to_plain_string())
     }
 }
 
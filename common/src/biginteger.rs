@@ -1,6 +1,6 @@
-use bigdecimal::{FromPrimitive, Zero};
+use bigdecimal::{FromPrimitive, ToPrimitive, Zero};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::ops::{Add, AddAssign, Deref, Rem, Sub, SubAssign};
+use std::ops::{Add, AddAssign, Deref, Div, Mul, Rem, Shl, Sub, SubAssign};
 use std::str::FromStr;
 
 #[derive(Clone, PartialEq, Eq, Debug, PartialOrd, Ord)]
@@ -25,6 +25,21 @@ impl BigInt {
         }
     }
 
+    pub fn from_u128(value: u128) -> Self {
+        Self(bigdecimal::num_bigint::BigInt::from(value))
+    }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        self.0.to_u128()
+    }
+
+    pub fn from_bytes_be(bytes: &[u8]) -> Self {
+        Self(bigdecimal::num_bigint::BigInt::from_bytes_be(
+            bigdecimal::num_bigint::Sign::Plus,
+            bytes,
+        ))
+    }
+
     pub fn zero() -> Self {
         Self(bigdecimal::num_bigint::BigInt::zero())
     }
@@ -66,6 +81,30 @@ impl Sub for BigInt {
     }
 }
 
+impl Mul for BigInt {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self(self.0 * rhs.0)
+    }
+}
+
+impl Div for BigInt {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Self(self.0 / rhs.0)
+    }
+}
+
+impl Shl<usize> for BigInt {
+    type Output = Self;
+
+    fn shl(self, rhs: usize) -> Self::Output {
+        Self(self.0 << rhs)
+    }
+}
+
 impl Rem for BigInt {
     type Output = Self;
 
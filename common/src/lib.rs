@@ -1,2 +1,4 @@
+pub mod amount;
 pub mod bigdecimal;
-pub mod biginteger;
\ No newline at end of file
+pub mod biginteger;
+pub mod codec;
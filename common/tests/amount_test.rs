@@ -0,0 +1,81 @@
+use common::amount::{Amount, DECIMALS};
+use common::biginteger::BigInt;
+
+#[test]
+fn parse_and_format() {
+    assert_eq!(amount("1").units(), 1_000_000_000_000_000_000);
+    assert_eq!(amount("0.000000000000000001").units(), 1);
+    assert_eq!(amount(".5"), amount("0.5"));
+    assert_eq!(amount("10.0"), amount("10"));
+    assert_eq!(amount("10.0").to_string(), "10");
+    assert_eq!(amount("0.1200").to_string(), "0.12");
+    assert_eq!(
+        amount("123.000000000000000001").to_string(),
+        "123.000000000000000001"
+    );
+    assert_eq!(Amount::zero().to_string(), "0");
+}
+
+#[test]
+fn reject_invalid_amounts() {
+    for value in ["", ".", "-1", "+1", "1e5", "1.2.3", "abc", " 1"] {
+        assert!(Amount::from_str(value).is_err(), "{}", value);
+    }
+    let too_precise = format!("0.{}1", "0".repeat(DECIMALS));
+    assert!(Amount::from_str(&too_precise).is_err());
+    let too_large = format!("{}", u128::MAX);
+    assert!(Amount::from_str(&too_large).is_err());
+}
+
+#[test]
+fn checked_arithmetic() {
+    assert_eq!(amount("1.5").checked_add(amount("2.5")), Some(amount("4")));
+    assert_eq!(amount("1.5").checked_sub(amount("0.5")), Some(amount("1")));
+    assert_eq!(amount("1").checked_sub(amount("1.5")), None);
+    assert_eq!(
+        Amount::from_units(u128::MAX).checked_add(amount("0.000000000000000001")),
+        None
+    );
+
+    assert_eq!(amount("1.5").checked_mul(amount("2")), Some(amount("3")));
+    assert_eq!(
+        amount("0.001").checked_mul(amount("0.001")),
+        Some(amount("0.000001"))
+    );
+    assert_eq!(
+        amount("0.000000000000000001").checked_mul(amount("0.5")),
+        Some(Amount::zero())
+    );
+    assert_eq!(Amount::from_units(u128::MAX).checked_mul(amount("2")), None);
+
+    assert_eq!(
+        amount("10").mul_div(1, 3),
+        Some(amount("3.333333333333333333"))
+    );
+    assert_eq!(amount("10").mul_div(1, 0), None);
+}
+
+#[test]
+fn whole_tokens() {
+    assert_eq!(amount("7").whole(), Some(7));
+    assert_eq!(amount("7.1").whole(), None);
+    assert_eq!(Amount::from_whole(7), Some(amount("7")));
+    assert_eq!(Amount::from_whole(u128::MAX), None);
+    assert_eq!(
+        Amount::from_bigint(&BigInt::from_u64(3).unwrap()),
+        Some(amount("3"))
+    );
+}
+
+#[test]
+fn serialize_as_string() {
+    let json = serde_json::to_string(&amount("0.25")).unwrap();
+    assert_eq!(json, "\"0.25\"");
+    let restored: Amount = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, amount("0.25"));
+    assert!(serde_json::from_str::<Amount>("\"-0.25\"").is_err());
+}
+
+fn amount(value: &str) -> Amount {
+    Amount::from_str(value).unwrap()
+}
@@ -1 +1,2 @@
-pub mod crypto;
\ No newline at end of file
+pub mod crypto;
+pub mod vrf;
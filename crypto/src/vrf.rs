@@ -0,0 +1,132 @@
+use k256::elliptic_curve::PrimeField;
+use k256::elliptic_curve::group::Group;
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use k256::{AffinePoint, EncodedPoint, FieldBytes, ProjectivePoint, Scalar, U256};
+use sha2::{Digest, Sha256};
+
+// ECVRF over secp256k1 with SHA-256 and try-and-increment hashing to the curve,
+// laid out as in RFC 9381: proof = Gamma (33) || c (16) || s (32).
+const SUITE: u8 = 0xfe;
+const POINT_LEN: usize = 33;
+const CHALLENGE_LEN: usize = 16;
+pub const PROOF_LEN: usize = POINT_LEN + CHALLENGE_LEN + 32;
+
+pub fn prove(secret: &[u8; 32], alpha: &[u8]) -> Result<(Vec<u8>, [u8; 32]), std::io::Error> {
+    let x: Option<Scalar> = Scalar::from_repr(FieldBytes::from(*secret)).into();
+    let x = x
+        .filter(|x| !bool::from(x.is_zero()))
+        .ok_or(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Invalid secret key",
+        ))?;
+    let public_key = encode(&(ProjectivePoint::GENERATOR * x));
+    let h = hash_to_curve(&public_key, alpha).ok_or(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        "Cannot hash input to curve",
+    ))?;
+    let gamma = h * x;
+    let mut hasher = Sha256::new();
+    hasher.update(secret);
+    hasher.update(encode(&h));
+    let k = <Scalar as Reduce<U256>>::reduce_bytes(&hasher.finalize());
+    let c = challenge(&[
+        &public_key,
+        &encode(&h),
+        &encode(&gamma),
+        &encode(&(ProjectivePoint::GENERATOR * k)),
+        &encode(&(h * k)),
+    ]);
+    let s = k + to_scalar(&c) * x;
+    let mut proof = encode(&gamma);
+    proof.extend_from_slice(&c);
+    proof.extend_from_slice(&s.to_bytes());
+    Ok((proof, proof_to_hash(&gamma)))
+}
+
+pub fn verify(public_key: &[u8; 33], alpha: &[u8], proof: &[u8]) -> Option<[u8; 32]> {
+    if proof.len() != PROOF_LEN {
+        return None;
+    }
+    let y = decode(public_key)?;
+    let gamma = decode(&proof[..POINT_LEN])?;
+    let c = &proof[POINT_LEN..POINT_LEN + CHALLENGE_LEN];
+    let s: Option<Scalar> = Scalar::from_repr(FieldBytes::clone_from_slice(
+        &proof[POINT_LEN + CHALLENGE_LEN..],
+    ))
+    .into();
+    let s = s?;
+    let h = hash_to_curve(public_key, alpha)?;
+    let u = ProjectivePoint::GENERATOR * s - y * to_scalar(c);
+    let v = h * s - gamma * to_scalar(c);
+    let expected = challenge(&[
+        public_key,
+        &encode(&h),
+        &proof[..POINT_LEN],
+        &encode(&u),
+        &encode(&v),
+    ]);
+    if expected.as_slice() != c {
+        return None;
+    }
+    Some(proof_to_hash(&gamma))
+}
+
+pub fn verify_proof(public_key: [u8; 33], proof: &String, alpha: &[u8]) -> Option<[u8; 32]> {
+    let proof = bs58::decode(proof).into_vec().ok()?;
+    verify(&public_key, alpha, &proof)
+}
+
+fn hash_to_curve(public_key: &[u8], alpha: &[u8]) -> Option<ProjectivePoint> {
+    for ctr in 0..=u8::MAX {
+        let mut hasher = Sha256::new();
+        hasher.update([SUITE, 0x01]);
+        hasher.update(public_key);
+        hasher.update(alpha);
+        hasher.update([ctr, 0x00]);
+        let mut candidate = [0x02; POINT_LEN];
+        candidate[1..].copy_from_slice(&hasher.finalize());
+        if let Some(point) = decode(&candidate) {
+            return Some(point);
+        }
+    }
+    None
+}
+
+fn challenge(points: &[&[u8]]) -> [u8; CHALLENGE_LEN] {
+    let mut hasher = Sha256::new();
+    hasher.update([SUITE, 0x02]);
+    for point in points {
+        hasher.update(point);
+    }
+    hasher.update([0x00]);
+    let mut c = [0u8; CHALLENGE_LEN];
+    c.copy_from_slice(&hasher.finalize()[..CHALLENGE_LEN]);
+    c
+}
+
+fn proof_to_hash(gamma: &ProjectivePoint) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([SUITE, 0x03]);
+    hasher.update(encode(gamma));
+    hasher.update([0x00]);
+    hasher.finalize().into()
+}
+
+fn to_scalar(c: &[u8]) -> Scalar {
+    let mut bytes = FieldBytes::default();
+    bytes[32 - c.len()..].copy_from_slice(c);
+    <Scalar as Reduce<U256>>::reduce_bytes(&bytes)
+}
+
+fn encode(point: &ProjectivePoint) -> Vec<u8> {
+    point.to_affine().to_encoded_point(true).as_bytes().to_vec()
+}
+
+fn decode(bytes: &[u8]) -> Option<ProjectivePoint> {
+    let point = EncodedPoint::from_bytes(bytes).ok()?;
+    let point: Option<AffinePoint> = AffinePoint::from_encoded_point(&point).into();
+    point
+        .map(ProjectivePoint::from)
+        .filter(|point| !bool::from(point.is_identity()))
+}
@@ -0,0 +1,42 @@
+use crypto::vrf::{PROOF_LEN, prove, verify};
+use libp2p::identity::secp256k1;
+
+#[test]
+fn prove_and_verify() {
+    let keypair = secp256k1::Keypair::generate();
+    let secret = keypair.secret().to_bytes();
+    let public_key = keypair.public().to_bytes();
+
+    let (proof, output) = prove(&secret, b"block").unwrap();
+    assert_eq!(proof.len(), PROOF_LEN);
+    assert_eq!(verify(&public_key, b"block", &proof), Some(output));
+
+    let (same_proof, same_output) = prove(&secret, b"block").unwrap();
+    assert_eq!(same_proof, proof);
+    assert_eq!(same_output, output);
+
+    let (_, other_output) = prove(&secret, b"other block").unwrap();
+    assert_ne!(other_output, output);
+}
+
+#[test]
+fn reject_invalid_proofs() {
+    let keypair = secp256k1::Keypair::generate();
+    let other = secp256k1::Keypair::generate();
+    let public_key = keypair.public().to_bytes();
+    let (proof, _) = prove(&keypair.secret().to_bytes(), b"block").unwrap();
+
+    assert_eq!(verify(&public_key, b"other block", &proof), None);
+    assert_eq!(verify(&other.public().to_bytes(), b"block", &proof), None);
+    assert_eq!(verify(&public_key, b"block", &proof[1..]), None);
+    for i in [0, 1, 40, PROOF_LEN - 1] {
+        let mut tampered = proof.clone();
+        tampered[i] ^= 0x01;
+        assert_eq!(
+            verify(&public_key, b"block", &tampered),
+            None,
+            "byte: {}",
+            i
+        );
+    }
+}
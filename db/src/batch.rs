@@ -0,0 +1,45 @@
+use rocksdb::{DBWithThreadMode, MultiThreaded, WriteBatch};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct Batch {
+    db: Arc<DBWithThreadMode<MultiThreaded>>,
+    batch: WriteBatch,
+    staged: HashMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl Batch {
+    pub fn new(db: &Arc<DBWithThreadMode<MultiThreaded>>) -> Self {
+        Self {
+            db: Arc::clone(db),
+            batch: WriteBatch::default(),
+            staged: HashMap::new(),
+        }
+    }
+
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<Vec<u8>>, std::io::Error> {
+        if let Some(value) = self.staged.get(key.as_ref()) {
+            return Ok(value.clone());
+        }
+        self.db
+            .get(key)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn put<K: AsRef<[u8]>, V: AsRef<[u8]>>(&mut self, key: K, value: V) {
+        self.batch.put(key.as_ref(), value.as_ref());
+        self.staged
+            .insert(key.as_ref().to_vec(), Some(value.as_ref().to_vec()));
+    }
+
+    pub fn delete<K: AsRef<[u8]>>(&mut self, key: K) {
+        self.batch.delete(key.as_ref());
+        self.staged.insert(key.as_ref().to_vec(), None);
+    }
+
+    pub fn commit(self) -> Result<(), std::io::Error> {
+        self.db
+            .write(self.batch)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
@@ -1,3 +1,5 @@
+pub mod batch;
+
 use rocksdb::{DBWithThreadMode, MultiThreaded, Options};
 use std::path::Path;
 use std::sync::Arc;
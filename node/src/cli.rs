@@ -1,10 +1,13 @@
 use crate::config::Config;
 use crate::logger::init_logger;
 use crate::node::Node;
-use clap::{Parser, Subcommand};
-use common::bigdecimal::BigDecimal;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use common::amount::Amount;
 use rpc::client::RpcClient;
+use serde::Serialize;
 use std::process::exit;
+use tx::multisig::Multisig;
+use tx::payload::{Output, Payload};
 use tx::tx_data::TxData;
 use wallet::wallet::Wallet;
 
@@ -15,6 +18,48 @@ pub struct NodeCli {
     command: NodeCmd,
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+pub enum TxKind {
+    Transfer,
+    Stake,
+    Unstake,
+    Delegate,
+    Undelegate,
+    Commission,
+    Batch,
+    AssetMint,
+    AssetTransfer,
+    AssetBurn,
+}
+
+#[derive(Args)]
+pub struct TxOptions {
+    #[arg(
+        long,
+        value_name = "BLOCK",
+        help = "Last block the transaction can be included in"
+    )]
+    valid_until: Option<u64>,
+    #[arg(
+        long,
+        value_name = "TEXT",
+        help = "Memo attached to the transaction, e.g. a deposit id"
+    )]
+    memo: Option<String>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Multisig policy file to spend from, signed partially by the wallet"
+    )]
+    multisig: Option<String>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write the signed transaction to a file instead of sending it"
+    )]
+    out: Option<String>,
+}
+
 #[derive(Subcommand)]
 pub enum NodeCmd {
     #[clap(about = "Initialize chain with new genesis block")]
@@ -47,6 +92,11 @@ pub enum NodeCmd {
         #[arg(long, value_name = "IDX", help = "Block height index")]
         idx: u64,
     },
+    #[clap(about = "Show total, circulating and staked supply")]
+    Supply {
+        #[arg(long, value_name = "URL", help = "URL address of known node")]
+        node: String,
+    },
     #[clap(about = "Find block by index")]
     NewTx {
         #[arg(long, value_name = "PATH", help = "Keystore path")]
@@ -55,10 +105,105 @@ pub enum NodeCmd {
         wallet: String,
         #[arg(long, value_name = "URL", help = "URL address of known node")]
         node: String,
+        #[arg(long, value_enum, default_value_t = TxKind::Transfer, help = "Kind of transaction")]
+        kind: TxKind,
         #[arg(long, value_name = "WALLET", help = "Receiver wallet address")]
-        to: String,
-        #[arg(long, value_name = "AMOUNT", help = "Amount of transaction")]
-        amount: String,
+        to: Option<String>,
+        #[arg(
+            long,
+            value_name = "AMOUNT",
+            help = "Amount of transaction or commission rate"
+        )]
+        amount: Option<String>,
+        #[arg(
+            long,
+            value_name = "WALLET",
+            help = "Validator wallet address to delegate or undelegate"
+        )]
+        validator: Option<String>,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "CSV file of 'wallet,amount' lines paid by a batch transaction"
+        )]
+        outputs: Option<String>,
+        #[arg(
+            long,
+            value_name = "ASSET",
+            help = "Asset id '<issuer>:<symbol>' to mint, transfer or burn"
+        )]
+        asset: Option<String>,
+        #[command(flatten)]
+        options: TxOptions,
+    },
+    #[clap(about = "Issue new asset with the wallet as issuer")]
+    CreateAsset {
+        #[arg(long, value_name = "PATH", help = "Keystore path")]
+        keystore: String,
+        #[arg(long, value_name = "WALLET", help = "Wallet address")]
+        wallet: String,
+        #[arg(long, value_name = "URL", help = "URL address of known node")]
+        node: String,
+        #[arg(long, value_name = "SYMBOL", help = "Asset symbol, e.g. 'POINTS'")]
+        symbol: String,
+        #[arg(long, default_value_t = 18, help = "Number of fractional digits")]
+        decimals: u8,
+        #[arg(long, value_name = "AMOUNT", help = "Maximum amount ever minted")]
+        max_supply: String,
+        #[arg(
+            long,
+            value_name = "WALLET",
+            help = "Wallet allowed to mint, the signing wallet by default"
+        )]
+        mint_authority: Option<String>,
+        #[command(flatten)]
+        options: TxOptions,
+    },
+    #[clap(about = "Show asset and its holders")]
+    FindAsset {
+        #[arg(long, value_name = "URL", help = "URL address of known node")]
+        node: String,
+        #[arg(long, value_name = "ASSET", help = "Asset id '<issuer>:<symbol>'")]
+        id: String,
+    },
+    #[clap(about = "Create M-of-N multisig policy and print its address")]
+    CreateMultisig {
+        #[arg(long, value_name = "M", help = "Number of signatures required")]
+        threshold: u8,
+        #[arg(
+            long,
+            value_name = "WALLET",
+            help = "Wallet address of a signer, repeated for each key"
+        )]
+        key: Vec<String>,
+        #[arg(long, value_name = "PATH", help = "Path to write policy file to")]
+        out: String,
+    },
+    #[clap(about = "Add partial signature to multisig transaction file")]
+    SignTx {
+        #[arg(long, value_name = "PATH", help = "Keystore path")]
+        keystore: String,
+        #[arg(long, value_name = "WALLET", help = "Wallet address")]
+        wallet: String,
+        #[arg(long, value_name = "PATH", help = "Transaction file")]
+        tx: String,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Path to write signed transaction to"
+        )]
+        out: String,
+    },
+    #[clap(about = "Combine partial signatures of multisig transaction files and send it")]
+    CombineTx {
+        #[arg(long, value_name = "URL", help = "URL address of known node")]
+        node: String,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Transaction file, repeated for each signer"
+        )]
+        tx: Vec<String>,
     },
 }
 
@@ -103,30 +248,223 @@ async fn find_block(node: String, idx: u64) {
     }
 }
 
-async fn add_tx(keystore: String, wallet: String, node: String, to: String, amount: String) {
+async fn supply(node: String) {
+    let client = RpcClient::new(node);
+    if let Some(supply) = client.get_supply().await {
+        println!("Total: {}", supply.total);
+        println!("Circulating: {}", supply.circulating);
+        println!("Staked: {}", supply.staked);
+        println!("Vesting: {}", supply.vesting);
+    } else {
+        eprintln!("Cannot load supply");
+    }
+}
+
+fn payload(
+    kind: TxKind,
+    to: Option<String>,
+    amount: Option<String>,
+    validator: Option<String>,
+    outputs: Option<String>,
+    asset: Option<String>,
+) -> Result<Payload, String> {
+    let amount = || {
+        let amount = amount.as_deref().ok_or("Missing --amount")?;
+        Amount::from_str(amount).map_err(|err| err.to_string())
+    };
+    let payload = match kind {
+        TxKind::Transfer => Payload::Transfer {
+            to: to.ok_or("Missing --to")?,
+            amount: amount()?,
+        },
+        TxKind::Stake => Payload::Stake { amount: amount()? },
+        TxKind::Unstake => Payload::Unstake { amount: amount()? },
+        TxKind::Delegate => Payload::Delegate {
+            validator: validator.ok_or("Missing --validator")?,
+            amount: amount()?,
+        },
+        TxKind::Undelegate => Payload::Undelegate {
+            validator: validator.ok_or("Missing --validator")?,
+            amount: amount()?,
+        },
+        TxKind::Commission => Payload::Commission { rate: amount()? },
+        TxKind::Batch => Payload::Batch {
+            outputs: read_outputs(&outputs.ok_or("Missing --outputs")?)?,
+        },
+        TxKind::AssetMint => Payload::AssetMint {
+            asset: asset.ok_or("Missing --asset")?,
+            to: to.ok_or("Missing --to")?,
+            amount: amount()?,
+        },
+        TxKind::AssetTransfer => Payload::AssetTransfer {
+            asset: asset.ok_or("Missing --asset")?,
+            to: to.ok_or("Missing --to")?,
+            amount: amount()?,
+        },
+        TxKind::AssetBurn => Payload::AssetBurn {
+            asset: asset.ok_or("Missing --asset")?,
+            amount: amount()?,
+        },
+    };
+    Ok(payload)
+}
+
+async fn find_asset(node: String, id: String) {
+    let client = RpcClient::new(node);
+    let Some(asset) = client.find_asset(&id).await else {
+        eprintln!("Asset not found: {}", id);
+        exit(1);
+    };
+    println!("{}", serde_json::to_string_pretty(&asset).unwrap());
+    if let Some(holders) = client.get_asset_holders(&id).await {
+        println!("Holders:");
+        for (wallet, balance) in holders {
+            println!("{}: {}", wallet, balance);
+        }
+    }
+}
+
+fn read_outputs(path: &str) -> Result<Vec<Output>, String> {
+    let csv = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    let mut outputs = Vec::new();
+    for line in csv.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let Some((to, amount)) = line.split_once(',') else {
+            return Err(format!(
+                "Invalid output '{}', expected 'wallet,amount'",
+                line
+            ));
+        };
+        outputs.push(Output {
+            to: to.trim().to_string(),
+            amount: Amount::from_str(amount.trim()).map_err(|err| err.to_string())?,
+        });
+    }
+    Ok(outputs)
+}
+
+async fn add_tx(
+    keystore: String,
+    wallet: String,
+    node: String,
+    payload: Payload,
+    options: TxOptions,
+) {
     let password = read_password();
     let Ok(wallet) = Wallet::read(&keystore, &wallet, password.as_bytes()) else {
         eprintln!("Wallet not found");
         exit(1);
     };
+    let multisig = options.multisig.map(|path| read_json::<Multisig>(&path));
+    let from = multisig
+        .as_ref()
+        .map_or(wallet.address_str(), Multisig::address);
     let client = RpcClient::new(node);
-    let next_nonce = if let Some(account) = client.get_account(wallet.address_str()).await {
+    let next_nonce = if let Some(account) = client.get_account(from).await {
         account.nonce + 1
     } else {
         1
     };
 
-    let Ok(fee_amount) = client.get_current_fee().await else {
+    let Some(chain) = client.get_chain_config().await else {
+        eprintln!("Can't load chain id");
+        exit(1);
+    };
+    let Ok(fee_rate) = client.get_current_fee().await else {
         eprintln!("Can't load current fee amount");
         exit(1);
     };
-    let fee = fee_amount * BigDecimal::from_str(&amount).unwrap();
-    println!("Fee: {}", fee.to_plain_string());
+    let memo_size = options.memo.as_ref().map_or(0, String::len);
+    let fee = payload
+        .required_fee(fee_rate)
+        .zip(TxData::memo_fee(memo_size, fee_rate))
+        .and_then(|(fee, memo_fee)| fee.checked_add(memo_fee));
+    let Some(fee) = fee else {
+        eprintln!("Fee overflow");
+        exit(1);
+    };
+    println!("Fee: {}", fee);
+
+    let tx = match multisig {
+        Some(multisig) => TxData::with_multisig(
+            &wallet,
+            chain.params.chain_id,
+            multisig,
+            payload,
+            fee.to_string(),
+            next_nonce,
+        ),
+        None => TxData::with_payload(
+            &wallet,
+            chain.params.chain_id,
+            payload,
+            fee.to_string(),
+            next_nonce,
+        ),
+    }
+    .and_then(|tx| tx.valid_between(&wallet, None, options.valid_until))
+    .and_then(|tx| match options.memo {
+        Some(memo) => tx.with_memo(&wallet, memo),
+        None => Ok(tx),
+    });
+    let tx = match tx {
+        Ok(tx) => tx,
+        Err(err) => {
+            eprintln!("Can't create new transaction: {}", err);
+            exit(1);
+        }
+    };
+    if let Some(out) = options.out {
+        write_json(&out, &tx);
+        println!("Transaction written to {}", out);
+    } else {
+        send_tx(&client, tx).await;
+    }
+}
+
+fn create_multisig(threshold: u8, keys: Vec<String>, out: String) {
+    let multisig = match Multisig::new(threshold, keys) {
+        Ok(multisig) => multisig,
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(1);
+        }
+    };
+    write_json(&out, &multisig);
+    println!("Multisig created: {}", multisig.address());
+}
+
+fn sign_tx(keystore: String, wallet: String, tx: String, out: String) {
+    let mut tx = read_json::<TxData>(&tx);
+    let password = read_password();
+    let Ok(wallet) = Wallet::read(&keystore, &wallet, password.as_bytes()) else {
+        eprintln!("Wallet not found");
+        exit(1);
+    };
+    if let Err(err) = tx.sign_partial(&wallet) {
+        eprintln!("{}", err);
+        exit(1);
+    }
+    write_json(&out, &tx);
+    println!("Signatures: {}", tx.signatures.len());
+}
 
-    let Ok(tx) = TxData::new(&wallet, to, amount, fee.to_string(), next_nonce) else {
-        eprintln!("Can't create new transaction");
+async fn combine_tx(node: String, paths: Vec<String>) {
+    let mut txs = paths.iter().map(|path| read_json::<TxData>(path));
+    let Some(first) = txs.next() else {
+        eprintln!("Missing --tx");
         exit(1);
     };
+    let tx = match txs.try_fold(first, TxData::combine) {
+        Ok(tx) => tx,
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(1);
+        }
+    };
+    send_tx(&RpcClient::new(node), tx).await;
+}
+
+async fn send_tx(client: &RpcClient, tx: TxData) {
     if let Some(err) = client.add_tx(tx).await {
         eprintln!("Invalid transaction: {}", err);
     } else {
@@ -134,6 +472,27 @@ async fn add_tx(keystore: String, wallet: String, node: String, to: String, amou
     }
 }
 
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> T {
+    let value = std::fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|err| err.to_string()));
+    match value {
+        Ok(value) => value,
+        Err(err) => {
+            eprintln!("Cannot read {}: {}", path, err);
+            exit(1);
+        }
+    }
+}
+
+fn write_json<T: Serialize>(path: &str, value: &T) {
+    let json = serde_json::to_string_pretty(value).unwrap();
+    if let Err(err) = std::fs::write(path, json) {
+        eprintln!("Cannot write {}: {}", path, err);
+        exit(1);
+    }
+}
+
 pub async fn start_cli() {
     let cli = NodeCli::parse();
     match cli.command {
@@ -144,12 +503,65 @@ pub async fn start_cli() {
             log_secret,
         } => create_wallet(keystore, log_secret),
         NodeCmd::FindBlock { node, idx } => find_block(node, idx).await,
+        NodeCmd::Supply { node } => supply(node).await,
         NodeCmd::NewTx {
             keystore,
             wallet,
             node,
+            kind,
             to,
             amount,
-        } => add_tx(keystore, wallet, node, to, amount).await,
+            validator,
+            outputs,
+            asset,
+            options,
+        } => {
+            let payload = match payload(kind, to, amount, validator, outputs, asset) {
+                Ok(payload) => payload,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    exit(1);
+                }
+            };
+            add_tx(keystore, wallet, node, payload, options).await
+        }
+        NodeCmd::CreateAsset {
+            keystore,
+            wallet,
+            node,
+            symbol,
+            decimals,
+            max_supply,
+            mint_authority,
+            options,
+        } => {
+            let max_supply = match Amount::from_str(&max_supply) {
+                Ok(max_supply) => max_supply,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    exit(1);
+                }
+            };
+            let payload = Payload::AssetCreate {
+                symbol,
+                decimals,
+                max_supply,
+                mint_authority: mint_authority.unwrap_or(wallet.clone()),
+            };
+            add_tx(keystore, wallet, node, payload, options).await
+        }
+        NodeCmd::FindAsset { node, id } => find_asset(node, id).await,
+        NodeCmd::CreateMultisig {
+            threshold,
+            key,
+            out,
+        } => create_multisig(threshold, key, out),
+        NodeCmd::SignTx {
+            keystore,
+            wallet,
+            tx,
+            out,
+        } => sign_tx(keystore, wallet, tx, out),
+        NodeCmd::CombineTx { node, tx } => combine_tx(node, tx).await,
     }
 }
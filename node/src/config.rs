@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use state::pool::PoolLimits;
 use std::env::home_dir;
 use std::fs;
 use std::process::exit;
@@ -44,6 +45,7 @@ pub struct Config {
     secret: String,
     storage_path: Option<String>,
     nodes: Option<Vec<String>>,
+    mempool: Option<PoolLimits>,
 }
 
 impl Config {
@@ -95,6 +97,10 @@ impl Config {
         }
     }
 
+    pub fn mempool(&self) -> PoolLimits {
+        self.mempool.clone().unwrap_or_default()
+    }
+
     pub fn logs(&self) -> Logs {
         if let Some(logs) = self.logs.as_ref() {
             logs.clone()
@@ -39,7 +39,14 @@ impl Node {
         let storage_path = config.storage_path();
         let path = Path::new(&storage_path);
         let storage = Arc::new(Storage::new(path));
-        let state = Arc::new(State::new(wallet.clone()));
+        let params = match storage.chain_config() {
+            Ok(config) => config.params,
+            Err(e) => {
+                eprintln!("Cannot load chain config: {}", e);
+                exit(1);
+            }
+        };
+        let state = Arc::new(State::with_limits(wallet.clone(), params, config.mempool()));
         if let Some(latest_block) = storage.find_latest_block() {
             state
                 .update(
@@ -50,6 +57,7 @@ impl Node {
                 )
                 .await;
         }
+        Self::replay_pending_txs(&storage, &state).await;
         Self {
             http_port: config.http_port(),
             address: config.address(),
@@ -60,6 +68,31 @@ impl Node {
         }
     }
 
+    /// Readds the journaled mempool transactions and deletes the ones no
+    /// longer valid against the current accounts from the journal.
+    pub async fn replay_pending_txs(storage: &Storage, state: &State) {
+        let pending_txs = match storage.pending_txs() {
+            Ok(pending_txs) => pending_txs,
+            Err(e) => {
+                error!("Cannot load pending transactions: {}", e);
+                return;
+            }
+        };
+        let mut removed = Vec::new();
+        for data in pending_txs {
+            let key = (data.from(), data.nonce);
+            let (result, dropped) = state.add_tx_dropping(data).await;
+            if let Err(e) = result {
+                debug!("Dropping pending tx: {}", e);
+                removed.push(key);
+            }
+            removed.extend(dropped);
+        }
+        if let Err(e) = storage.remove_pending_txs(&removed) {
+            error!("Cannot save pending transactions: {}", e);
+        }
+    }
+
     pub async fn start(&self) {
         let (block_tx, block_rx) = tokio::sync::mpsc::channel::<Block>(100);
         if let Err(_) = self.start_validator(block_tx).await {
@@ -84,34 +117,51 @@ impl Node {
             client.dial(peer_id.clone(), address).await.unwrap();
             self.sync_state(&mut client, peer_id).await;
         }
-        rpc::server::run(
-            self.http_port,
-            self.wallet.address_str(),
-            &self.storage,
-            &self.state,
-            client,
-        )
-        .await;
+        rpc::server::run(self.http_port, &self.storage, &self.state, client).await;
     }
 
     async fn start_validator(&self, block_tx: Sender<Block>) -> Result<(), Box<dyn Error>> {
         let storage = Arc::clone(&self.storage);
         let state = Arc::clone(&self.state);
+        let wallet = self.wallet.clone();
         let scheduler = JobScheduler::new().await?;
         scheduler
             .add(Job::new_async("*/12 * * * * *", move |_, _| {
                 let block_tx = block_tx.clone();
                 let storage = Arc::clone(&storage);
                 let state = Arc::clone(&state);
+                let wallet = wallet.clone();
                 Box::pin(async move {
-                    let validator = storage.current_validator().unwrap();
-                    match state.new_block(validator).await {
+                    let Some(parent) = storage.find_latest_block() else {
+                        return;
+                    };
+                    let timestamp = Storage::now();
+                    let slot = Block::slot_at(timestamp);
+                    let input = Block::vrf_input(&parent.hash_str(), parent.idx + 1, slot);
+                    let can_propose = match wallet.vrf_prove(&input) {
+                        Ok((_, output)) => storage
+                            .can_propose(&wallet.address_str(), &parent, slot, output)
+                            .unwrap_or(false),
+                        Err(_) => false,
+                    };
+                    if !can_propose {
+                        debug!("Not a proposer for slot {}", slot);
+                        return;
+                    }
+                    let reward = match storage.block_reward(parent.idx + 1) {
+                        Ok(reward) => reward,
+                        Err(e) => {
+                            error!("Cannot compute block reward: {}", e);
+                            return;
+                        }
+                    };
+                    match state.new_block(timestamp, reward).await {
                         Some(block) => {
                             if let Err(e) = storage.add_block(&block) {
                                 error!("Error adding block: {}", e);
                             } else {
                                 let last_event = storage.find_latest_event_hash();
-                                state
+                                let dropped = state
                                     .update(
                                         block.hash_str(),
                                         block.idx() + 1,
@@ -119,6 +169,9 @@ impl Node {
                                         storage.accounts(),
                                     )
                                     .await;
+                                if let Err(e) = storage.remove_pending_txs(&dropped) {
+                                    error!("Cannot remove dropped pending txs: {}", e);
+                                }
                                 if let Err(e) = block_tx.send(block).await {
                                     error!("Error sending block: {}", e);
                                 }
@@ -146,7 +199,8 @@ impl Node {
                     if let Err(_) = self.storage.add_block(&block) {
                         break;
                     } else {
-                        self.state
+                        let dropped = self
+                            .state
                             .update(
                                 block.hash_str(),
                                 block.idx() + 1,
@@ -154,6 +208,9 @@ impl Node {
                                 self.storage.accounts(),
                             )
                             .await;
+                        if let Err(e) = self.storage.remove_pending_txs(&dropped) {
+                            error!("Cannot remove dropped pending txs: {}", e);
+                        }
                     }
                 }
                 None => synced = true,
@@ -1,8 +1,8 @@
+mod support;
+
 use state::state::State;
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::path::Path;
 use storage::storage::Storage;
+use support::wallet_with_balance;
 use tempfile::{tempdir, NamedTempFile};
 use tx::tx_data::TxData;
 use wallet::wallet::Wallet;
@@ -19,7 +19,7 @@ async fn add_valid_block() {
 
     storage.load_genesis_from_file(genesis_json.path()).unwrap();
 
-    let state = State::new(wallet.clone());
+    let state = State::new(wallet.clone(), storage.chain_config().unwrap().params);
     let block = storage.find_latest_block().expect("Can't find block");
     let latest_event_hash = storage.find_latest_event_hash();
     state
@@ -31,8 +31,10 @@ async fn add_valid_block() {
         )
         .await;
 
+    let chain_id = storage.chain_config().unwrap().params.chain_id;
     let tx1 = TxData::new(
         &wallet,
+        chain_id.clone(),
         wallet.address_str(),
         String::from("10"),
         String::from("1"),
@@ -45,6 +47,7 @@ async fn add_valid_block() {
 
     let tx2 = TxData::new(
         &wallet,
+        chain_id.clone(),
         wallet.address_str(),
         String::from("10"),
         String::from("1"),
@@ -57,6 +60,7 @@ async fn add_valid_block() {
 
     let invalid_tx = TxData::new(
         &wallet,
+        chain_id,
         wallet.address_str(),
         String::from("100"),
         String::from("1"),
@@ -66,24 +70,9 @@ async fn add_valid_block() {
     let err = state.add_tx(invalid_tx).await.is_err();
     assert!(err);
 
-    let block = state.new_block(wallet.address_str()).await;
+    let reward = storage.block_reward(1).unwrap();
+    let block = state.new_block(Storage::now(), reward).await;
     assert!(block.clone().unwrap().valid());
 
     assert!(storage.add_block(&block.unwrap()).is_ok())
 }
-
-fn wallet_with_balance(wallet: &Wallet, path: &Path) -> Result<(), std::io::Error> {
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(path)?;
-    let json = format!(
-        "[{{\"from\": \"GENESIS\",\"to\": \"{}\",\"amount\": \"100\",\"fee\": \"0\",\"nonce\": 1,\"signature\": \"GENESIS\"}},\
-        {{\"from\": \"{}\",\"to\": \"STAKE\",\"amount\": \"50\",\"fee\": \"0\",\"nonce\": 1,\"signature\": \"GENESIS\"}}]",
-        wallet.address_str(),
-        wallet.address_str()
-    );
-    file.write(json.as_bytes())?;
-    Ok(())
-}
@@ -0,0 +1,128 @@
+mod support;
+
+use state::pool::PoolLimits;
+use state::state::State;
+use storage::storage::Storage;
+use support::wallet_with_balance;
+use tempfile::{NamedTempFile, tempdir};
+use tx::tx_data::TxData;
+use wallet::wallet::Wallet;
+use xchg::node::Node;
+
+fn transfer(storage: &Storage, wallet: &Wallet, amount: &str, nonce: u64) -> TxData {
+    TxData::new(
+        wallet,
+        storage.chain_config().unwrap().params.chain_id,
+        wallet.address_str(),
+        String::from(amount),
+        String::from("1"),
+        nonce,
+    )
+    .unwrap()
+}
+
+async fn started_state(wallet: &Wallet, storage: &Storage) -> State {
+    let state = State::new(wallet.clone(), storage.chain_config().unwrap().params);
+    let block = storage.find_latest_block().unwrap();
+    state
+        .update(
+            block.hash_str(),
+            block.idx() + 1,
+            block.last_event(),
+            storage.accounts(),
+        )
+        .await;
+    state
+}
+
+#[tokio::test]
+async fn pending_txs_survive_restart() {
+    let storage_dir = tempdir().unwrap();
+    let storage = Storage::new(storage_dir.path());
+    let genesis_json = NamedTempFile::new().unwrap();
+    let wallet = Wallet::new();
+    wallet_with_balance(&wallet, genesis_json.path()).unwrap();
+    storage.load_genesis_from_file(genesis_json.path()).unwrap();
+
+    let state = started_state(&wallet, &storage).await;
+    for nonce in [2, 3] {
+        let data = transfer(&storage, &wallet, "10", nonce);
+        let (result, dropped) = state.add_tx_dropping(data.clone()).await;
+        result.unwrap();
+        storage.save_pending_tx(&data, &dropped).unwrap();
+    }
+    let reward = storage.block_reward(1).unwrap();
+    let block = state.new_block(Storage::now(), reward).await.unwrap();
+    storage.add_block(&block).unwrap();
+    assert!(storage.pending_txs().unwrap().is_empty());
+
+    let pending = transfer(&storage, &wallet, "10", 4);
+    let future = transfer(&storage, &wallet, "10", 6);
+    let stale = transfer(&storage, &wallet, "10", 3);
+    let overdrawn = transfer(&storage, &wallet, "1000", 5);
+    for data in [&pending, &future, &stale, &overdrawn] {
+        storage.save_pending_tx(data, &[]).unwrap();
+    }
+
+    let restarted = started_state(&wallet, &storage).await;
+    Node::replay_pending_txs(&storage, &restarted).await;
+    assert_eq!(
+        restarted.pending_txs().await,
+        vec![pending.clone(), future.clone()]
+    );
+    assert_eq!(storage.pending_txs().unwrap(), vec![pending, future]);
+    let account = restarted.get_account(wallet.address_str()).await.unwrap();
+    assert_eq!(account.nonce, 4);
+}
+
+#[tokio::test]
+async fn evicted_txs_are_removed_from_journal() {
+    let storage_dir = tempdir().unwrap();
+    let storage = Storage::new(storage_dir.path());
+    let genesis_json = NamedTempFile::new().unwrap();
+    let wallet = Wallet::new();
+    wallet_with_balance(&wallet, genesis_json.path()).unwrap();
+    storage.load_genesis_from_file(genesis_json.path()).unwrap();
+
+    let limits = PoolLimits {
+        max_txs: 2,
+        ..PoolLimits::default()
+    };
+    let params = storage.chain_config().unwrap().params;
+    let state = State::with_limits(wallet.clone(), params, limits);
+    let block = storage.find_latest_block().unwrap();
+    state
+        .update(
+            block.hash_str(),
+            block.idx() + 1,
+            block.last_event(),
+            storage.accounts(),
+        )
+        .await;
+    for nonce in [2, 3] {
+        let data = transfer(&storage, &wallet, "10", nonce);
+        let (result, dropped) = state.add_tx_dropping(data.clone()).await;
+        result.unwrap();
+        storage.save_pending_tx(&data, &dropped).unwrap();
+    }
+    assert_eq!(storage.pending_txs().unwrap().len(), 2);
+
+    let data = TxData::new(
+        &wallet,
+        storage.chain_config().unwrap().params.chain_id,
+        wallet.address_str(),
+        String::from("10"),
+        String::from("2"),
+        4,
+    )
+    .unwrap();
+    let (result, dropped) = state.add_tx_dropping(data).await;
+    assert_eq!(result.err(), Some(String::from("Mempool is full")));
+    let sender = wallet.address_str();
+    assert_eq!(
+        dropped,
+        vec![(sender.clone(), 2), (sender.clone(), 3), (sender, 4)]
+    );
+    storage.remove_pending_txs(&dropped).unwrap();
+    assert!(storage.pending_txs().unwrap().is_empty());
+}
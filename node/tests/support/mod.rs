@@ -0,0 +1,21 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use wallet::wallet::Wallet;
+
+/// Writes a genesis funding the wallet with 100 and staking 50 of it.
+pub fn wallet_with_balance(wallet: &Wallet, path: &Path) -> Result<(), std::io::Error> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+    let json = format!(
+        "[{{\"version\": 1,\"from\": \"GENESIS\",\"payload\": {{\"genesis\": {{\"to\": \"{}\",\"amount\": \"100\"}}}},\"fee\": \"0\",\"nonce\": 1,\"signature\": \"GENESIS\"}},\
+        {{\"version\": 1,\"from\": \"{}\",\"payload\": {{\"stake\": {{\"amount\": \"50\"}}}},\"fee\": \"0\",\"nonce\": 1,\"signature\": \"GENESIS\"}}]",
+        wallet.address_str(),
+        wallet.address_str()
+    );
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use account::account::Account;
+use account::asset::Asset;
+use common::amount::Amount;
+use std::collections::BTreeMap;
+use tx::tx::Tx;
+
+pub fn create(
+    tx: &Tx,
+    symbol: &str,
+    decimals: u8,
+    max_supply: Amount,
+    mint_authority: &str,
+    accounts: &mut BTreeMap<String, Account>,
+) -> Result<(), String> {
+    let asset = Asset::new(
+        tx.from(),
+        symbol.to_string(),
+        decimals,
+        max_supply,
+        mint_authority.to_string(),
+    )?;
+    let Some(account) = accounts.get_mut(&tx.from()) else {
+        return Err(String::from("Not enough balance"));
+    };
+    if account.issued.contains_key(symbol) {
+        return Err(format!("Asset {} already exists", asset.id()));
+    }
+    account.credit(tx.fee())?;
+    account.issued.insert(symbol.to_string(), asset);
+    Ok(())
+}
+
+pub fn mint(
+    tx: &Tx,
+    id: &str,
+    to: &str,
+    amount: Amount,
+    accounts: &mut BTreeMap<String, Account>,
+) -> Result<(), String> {
+    let mut asset = find(id, accounts)?;
+    if asset.mint_authority != tx.from() {
+        return Err(format!("Only the mint authority can mint {}", id));
+    }
+    if !asset.valid_amount(amount) {
+        return Err(format!("Invalid {} amount", id));
+    }
+    asset.mint(amount)?;
+    let Some(account) = accounts.get_mut(&tx.from()) else {
+        return Err(String::from("Not enough balance"));
+    };
+    account.credit(tx.fee())?;
+    save(asset, accounts)?;
+    let account = accounts
+        .entry(to.to_string())
+        .or_insert(Account::new(to.to_string()));
+    account.debit_asset(id, amount)
+}
+
+pub fn transfer(
+    tx: &Tx,
+    id: &str,
+    to: &str,
+    amount: Amount,
+    accounts: &mut BTreeMap<String, Account>,
+) -> Result<(), String> {
+    let asset = find(id, accounts)?;
+    if !asset.valid_amount(amount) {
+        return Err(format!("Invalid {} amount", id));
+    }
+    let Some(mut sender) = accounts.get(&tx.from()).cloned() else {
+        return Err(String::from("Not enough balance"));
+    };
+    sender.credit_asset(id, amount)?;
+    sender.credit(tx.fee())?;
+    accounts.insert(tx.from(), sender);
+    let account = accounts
+        .entry(to.to_string())
+        .or_insert(Account::new(to.to_string()));
+    account.debit_asset(id, amount)
+}
+
+pub fn burn(
+    tx: &Tx,
+    id: &str,
+    amount: Amount,
+    accounts: &mut BTreeMap<String, Account>,
+) -> Result<(), String> {
+    let mut asset = find(id, accounts)?;
+    if !asset.valid_amount(amount) {
+        return Err(format!("Invalid {} amount", id));
+    }
+    asset.burn(amount)?;
+    let Some(mut sender) = accounts.get(&tx.from()).cloned() else {
+        return Err(String::from("Not enough balance"));
+    };
+    sender.credit_asset(id, amount)?;
+    sender.credit(tx.fee())?;
+    accounts.insert(tx.from(), sender);
+    save(asset, accounts)
+}
+
+fn find(id: &str, accounts: &BTreeMap<String, Account>) -> Result<Asset, String> {
+    Asset::parse_id(id)
+        .and_then(|(issuer, symbol)| accounts.get(issuer)?.issued.get(symbol))
+        .cloned()
+        .ok_or_else(|| format!("Unknown asset {}", id))
+}
+
+fn save(asset: Asset, accounts: &mut BTreeMap<String, Account>) -> Result<(), String> {
+    let Some(issuer) = accounts.get_mut(&asset.issuer) else {
+        return Err(format!("Unknown asset {}", asset.id()));
+    };
+    issuer.issued.insert(asset.symbol.clone(), asset);
+    Ok(())
+}
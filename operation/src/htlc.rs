@@ -0,0 +1,113 @@
+use account::account::{Account, Htlc};
+use common::amount::Amount;
+use sha2::Digest;
+use std::collections::BTreeMap;
+use tx::tx::Tx;
+
+/// Contracts are identified by the hash of the signed data creating them, so
+/// the sender knows the id before the transaction is included in a block.
+pub fn htlc_id(tx: &Tx) -> String {
+    tx.data.hash_str()
+}
+
+pub fn create(
+    tx: &Tx,
+    height: u64,
+    to: &String,
+    amount: Amount,
+    hashlock: &str,
+    timeout: u64,
+    accounts: &mut BTreeMap<String, Account>,
+) -> Result<(), String> {
+    if amount == Amount::zero() {
+        return Err(String::from("HTLC amount must be positive"));
+    }
+    if *to == tx.from() {
+        return Err(String::from("Cannot lock funds to self"));
+    }
+    if timeout <= height {
+        return Err(String::from("HTLC timeout must be after current block"));
+    }
+    if !matches!(hex::decode(hashlock), Ok(hash) if hash.len() == 32) {
+        return Err(String::from("Invalid hashlock"));
+    }
+    let Some(amount_with_fee) = tx.amount_with_fee() else {
+        return Err(String::from("Amount overflow"));
+    };
+    let Some(account) = accounts.get_mut(&tx.from()) else {
+        return Err(String::from("Not enough balance"));
+    };
+    account.credit(amount_with_fee)?;
+    account.htlcs.insert(
+        htlc_id(tx),
+        Htlc {
+            to: to.clone(),
+            amount,
+            hashlock: hashlock.to_lowercase(),
+            timeout,
+        },
+    );
+    Ok(())
+}
+
+pub fn claim(
+    tx: &Tx,
+    height: u64,
+    sender: &String,
+    id: &String,
+    preimage: &str,
+    accounts: &mut BTreeMap<String, Account>,
+) -> Result<(), String> {
+    let Some(htlc) = accounts
+        .get(sender)
+        .and_then(|account| account.htlcs.get(id))
+    else {
+        return Err(format!("Unknown HTLC {}", id));
+    };
+    if htlc.to != tx.from() {
+        return Err(String::from("Only the recipient can claim HTLC"));
+    }
+    if height >= htlc.timeout {
+        return Err(String::from("HTLC expired"));
+    }
+    let Ok(preimage) = hex::decode(preimage) else {
+        return Err(String::from("Invalid preimage"));
+    };
+    if hex::encode(sha2::Sha256::digest(preimage)) != htlc.hashlock {
+        return Err(String::from("Invalid preimage"));
+    }
+    let Some(mut recipient) = accounts.get(&tx.from()).cloned() else {
+        return Err(String::from("Not enough balance"));
+    };
+    recipient.debit(htlc.amount)?;
+    recipient.credit(tx.fee())?;
+    if let Some(account) = accounts.get_mut(sender) {
+        account.htlcs.remove(id);
+    }
+    accounts.insert(tx.from(), recipient);
+    Ok(())
+}
+
+pub fn refund(
+    tx: &Tx,
+    height: u64,
+    id: &String,
+    accounts: &mut BTreeMap<String, Account>,
+) -> Result<(), String> {
+    let Some(account) = accounts.get_mut(&tx.from()) else {
+        return Err(String::from("Not enough balance"));
+    };
+    let Some(htlc) = account.htlcs.get(id) else {
+        return Err(format!("Unknown HTLC {}", id));
+    };
+    if height < htlc.timeout {
+        return Err(format!("HTLC locked until block #{}", htlc.timeout));
+    }
+    let mut refunded = account.clone();
+    if let Some(htlc) = refunded.htlcs.remove(id) {
+        refunded.debit(htlc.amount)?;
+    }
+    refunded.credit(tx.fee())?;
+    *account = refunded;
+    Ok(())
+}
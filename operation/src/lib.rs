@@ -1 +1,4 @@
-pub mod tx;
\ No newline at end of file
+pub mod asset;
+pub mod htlc;
+pub mod params;
+pub mod tx;
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_UNBONDING_PERIOD: u64 = 100;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Params {
+    #[serde(default)]
+    pub chain_id: String,
+    #[serde(default = "default_unbonding_period")]
+    pub unbonding_period: u64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            chain_id: String::default(),
+            unbonding_period: DEFAULT_UNBONDING_PERIOD,
+        }
+    }
+}
+
+fn default_unbonding_period() -> u64 {
+    DEFAULT_UNBONDING_PERIOD
+}
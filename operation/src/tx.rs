@@ -1,37 +1,310 @@
-use account::account::Account;
-use std::collections::BTreeMap;
+use crate::asset;
+use crate::htlc;
+use crate::params::Params;
+use account::account::{Account, Vesting};
+use common::amount::Amount;
+use common::biginteger::BigInt;
+use std::collections::{BTreeMap, BTreeSet};
+use tx::evidence::Evidence;
+use tx::payload::{Output, Payload};
 use tx::tx::Tx;
+use tx::tx_data::GENESIS_WALLET;
 
-const GENESIS_WALLET: &str = "GENESIS";
-const STAKE_WALLET: &str = "STAKE";
-const UNSTAKE_WALLET: &str = "UNSTAKE";
+const SLASH_PERCENT: u64 = 50;
+const REPORTER_PERCENT: u64 = 10;
 
+/// Applies the tx as included at block `height`; the height the tx is
+/// stamped with is only trusted once the block checked it.
 pub fn process_tx(
     validator: String,
     tx: &Tx,
+    height: u64,
     accounts: &mut BTreeMap<String, Account>,
+    params: &Params,
 ) -> Result<(), String> {
-    if tx.from() == GENESIS_WALLET && tx.block == 0 {
-        let mut account = Account::new(GENESIS_WALLET.to_string());
-        account.debit(tx.amount_with_fee())?;
-        accounts.insert(tx.from(), account);
+    match tx.payload() {
+        Payload::Genesis { to, amount } => {
+            if height != 0 {
+                return Err(String::from("Genesis tx outside of genesis block"));
+            }
+            let account = accounts
+                .entry(to.clone())
+                .or_insert(Account::new(to.clone()));
+            return account.debit(*amount);
+        }
+        Payload::Vesting {
+            to,
+            amount,
+            start,
+            cliff,
+            end,
+        } if tx.from() == GENESIS_WALLET => {
+            if height != 0 {
+                return Err(String::from("Genesis tx outside of genesis block"));
+            }
+            let account = accounts
+                .entry(to.clone())
+                .or_insert(Account::new(to.clone()));
+            account.add_vesting(Vesting::new(*amount, *start, *cliff, *end)?);
+            return Ok(());
+        }
+        Payload::Coinbase { to, amount } => {
+            if tx.fee() != Amount::zero() {
+                return Err(String::from("Coinbase cannot pay fee"));
+            }
+            return pay_validator(to.clone(), *amount, accounts);
+        }
+        _ => {}
     }
     let Some(account) = accounts.get_mut(&tx.from()) else {
         return Err(String::from("Not enough balance"));
     };
+    account.release_unbonded(height)?;
+    account.release_vested(height)?;
     account.set_nonce(tx.nonce())?;
-    if tx.to() == STAKE_WALLET {
-        account.stake_amount(tx.amount(), tx.fee())?;
-    } else if tx.to() == UNSTAKE_WALLET {
-        account.unstake_amount(tx.amount(), tx.fee())?
-    } else {
-        account.credit(tx.amount_with_fee())?;
-    }
-    let account = accounts.entry(tx.to()).or_insert(Account::new(tx.to()));
-    account.debit(tx.amount())?;
-    let validator = accounts
+    match tx.payload() {
+        Payload::Transfer { to, amount } => {
+            let Some(amount_with_fee) = tx.amount_with_fee() else {
+                return Err(String::from("Amount overflow"));
+            };
+            account.credit(amount_with_fee)?;
+            let account = accounts
+                .entry(to.clone())
+                .or_insert(Account::new(to.clone()));
+            account.debit(*amount)?;
+        }
+        Payload::Stake { amount } => account.stake_amount(*amount, tx.fee())?,
+        Payload::Unstake { amount } => {
+            account.unstake_amount(*amount, tx.fee(), height + params.unbonding_period)?
+        }
+        Payload::Commission { rate } => account.set_commission(*rate, tx.fee())?,
+        Payload::Delegate { validator, amount } => delegate(tx, validator, *amount, accounts)?,
+        Payload::Undelegate { validator, amount } => {
+            undelegate(tx, height, validator, *amount, accounts, params)?
+        }
+        Payload::Evidence { evidence } => slash(tx, height, evidence, accounts, params)?,
+        Payload::Batch { outputs } => batch(tx, outputs, accounts)?,
+        Payload::HtlcCreate {
+            to,
+            amount,
+            hashlock,
+            timeout,
+        } => htlc::create(tx, height, to, *amount, hashlock, *timeout, accounts)?,
+        Payload::HtlcClaim {
+            sender,
+            id,
+            preimage,
+        } => htlc::claim(tx, height, sender, id, preimage, accounts)?,
+        Payload::HtlcRefund { id } => htlc::refund(tx, height, id, accounts)?,
+        Payload::Vesting {
+            to,
+            amount,
+            start,
+            cliff,
+            end,
+        } => {
+            let vesting = Vesting::new(*amount, *start, *cliff, *end)?;
+            let Some(amount_with_fee) = tx.amount_with_fee() else {
+                return Err(String::from("Amount overflow"));
+            };
+            account.credit(amount_with_fee)?;
+            let account = accounts
+                .entry(to.clone())
+                .or_insert(Account::new(to.clone()));
+            account.add_vesting(vesting);
+        }
+        Payload::AssetCreate {
+            symbol,
+            decimals,
+            max_supply,
+            mint_authority,
+        } => asset::create(tx, symbol, *decimals, *max_supply, mint_authority, accounts)?,
+        Payload::AssetMint {
+            asset: id,
+            to,
+            amount,
+        } => asset::mint(tx, id, to, *amount, accounts)?,
+        Payload::AssetTransfer {
+            asset: id,
+            to,
+            amount,
+        } => asset::transfer(tx, id, to, *amount, accounts)?,
+        Payload::AssetBurn { asset: id, amount } => asset::burn(tx, id, *amount, accounts)?,
+        Payload::Genesis { .. } | Payload::Coinbase { .. } => {
+            return Err(String::from("Mint outside of block"));
+        }
+    }
+    pay_validator(validator, tx.fee(), accounts)
+}
+
+/// Pays `amount` to the block validator, sharing the part left after its
+/// commission with delegators in proportion to their share of its weight.
+fn pay_validator(
+    validator: String,
+    amount: Amount,
+    accounts: &mut BTreeMap<String, Account>,
+) -> Result<(), String> {
+    let account = accounts
         .entry(validator.clone())
-        .or_insert(Account::new(validator));
-    validator.debit(tx.fee())?;
+        .or_insert(Account::new(validator.clone()));
+    let weight = account.weight();
+    let mut shares = Vec::new();
+    if weight > BigInt::zero() {
+        let overflow = || String::from("Amount overflow");
+        let weight = weight.to_u128().ok_or_else(overflow)?;
+        let kept = Amount::from_whole(1)
+            .and_then(|one| one.checked_sub(account.commission))
+            .ok_or_else(overflow)?;
+        let distributed = amount.checked_mul(kept).ok_or_else(overflow)?;
+        for (delegator, delegated) in &account.delegators {
+            let delegated = delegated.to_u128().ok_or_else(overflow)?;
+            let share = distributed
+                .mul_div(delegated, weight)
+                .ok_or_else(overflow)?;
+            shares.push((delegator.clone(), share));
+        }
+    }
+    let mut remaining = amount;
+    for (delegator, share) in shares {
+        let Some(rest) = remaining.checked_sub(share) else {
+            return Err(String::from("Amount overflow"));
+        };
+        remaining = rest;
+        let account = accounts
+            .entry(delegator.clone())
+            .or_insert(Account::new(delegator));
+        account.debit(share)?;
+    }
+    let Some(account) = accounts.get_mut(&validator) else {
+        return Err(String::from("Unknown validator"));
+    };
+    account.debit(remaining)
+}
+
+fn delegate(
+    tx: &Tx,
+    validator: &String,
+    amount: Amount,
+    accounts: &mut BTreeMap<String, Account>,
+) -> Result<(), String> {
+    if *validator == tx.from() {
+        return Err(String::from("Cannot delegate to self"));
+    }
+    let Some(account) = accounts.get(validator) else {
+        return Err(String::from("Unknown validator"));
+    };
+    if account.stake() <= BigInt::zero() {
+        return Err(String::from("Validator has no stake"));
+    }
+    let Some(delegator) = accounts.get_mut(&tx.from()) else {
+        return Err(String::from("Not enough balance"));
+    };
+    let amount = delegator.delegate(validator, amount, tx.fee())?;
+    if let Some(account) = accounts.get_mut(validator) {
+        account.add_delegator(&tx.from(), amount);
+    }
     Ok(())
 }
+
+fn undelegate(
+    tx: &Tx,
+    height: u64,
+    validator: &String,
+    amount: Amount,
+    accounts: &mut BTreeMap<String, Account>,
+    params: &Params,
+) -> Result<(), String> {
+    let Some(delegator) = accounts.get_mut(&tx.from()) else {
+        return Err(String::from("Not enough balance"));
+    };
+    let release_at = height + params.unbonding_period;
+    let amount = delegator.undelegate(validator, amount, tx.fee(), release_at)?;
+    let Some(account) = accounts.get_mut(validator) else {
+        return Err(String::from("Unknown validator"));
+    };
+    account.remove_delegator(&tx.from(), amount)?;
+    account.undelegating.insert(tx.from(), release_at);
+    Ok(())
+}
+
+/// Pays every output or none: accounts are updated on copies first and only
+/// written back once all of them succeeded.
+fn batch(
+    tx: &Tx,
+    outputs: &[Output],
+    accounts: &mut BTreeMap<String, Account>,
+) -> Result<(), String> {
+    let Some(sender) = accounts.get(&tx.from()) else {
+        return Err(String::from("Not enough balance"));
+    };
+    let Some(amount_with_fee) = tx.amount_with_fee() else {
+        return Err(String::from("Amount overflow"));
+    };
+    let mut updated = BTreeMap::new();
+    let mut sender = sender.clone();
+    sender.credit(amount_with_fee)?;
+    updated.insert(tx.from(), sender);
+    for output in outputs {
+        let account = updated.entry(output.to.clone()).or_insert_with(|| {
+            accounts
+                .get(&output.to)
+                .cloned()
+                .unwrap_or(Account::new(output.to.clone()))
+        });
+        account.debit(output.amount)?;
+    }
+    accounts.extend(updated);
+    Ok(())
+}
+
+/// Slashes the offender's stake along with the tokens delegated to it,
+/// including delegations still unbonding.
+fn slash(
+    tx: &Tx,
+    height: u64,
+    evidence: &Evidence,
+    accounts: &mut BTreeMap<String, Account>,
+    params: &Params,
+) -> Result<(), String> {
+    if !evidence.valid() {
+        return Err(String::from("Invalid evidence"));
+    }
+    if evidence.first.chain_id != params.chain_id || evidence.second.chain_id != params.chain_id {
+        return Err(String::from("Evidence from other chain"));
+    }
+    let Some(offender) = accounts.get_mut(&evidence.validator()) else {
+        return Err(String::from("Unknown validator"));
+    };
+    let mut slashed = offender.slash(evidence.idx(), SLASH_PERCENT)?;
+    offender
+        .undelegating
+        .retain(|_, release_at| *release_at > height);
+    let delegators: BTreeSet<String> = offender
+        .delegators
+        .keys()
+        .chain(offender.undelegating.keys())
+        .cloned()
+        .collect();
+    for delegator in delegators {
+        let Some(account) = accounts.get_mut(&delegator) else {
+            return Err(format!("Unknown delegator {}", delegator));
+        };
+        let (delegated, cut) = account.slash_delegation(&evidence.validator(), SLASH_PERCENT);
+        slashed += cut;
+        if delegated > BigInt::zero()
+            && let Some(offender) = accounts.get_mut(&evidence.validator())
+        {
+            offender.remove_delegator(&delegator, delegated)?;
+        }
+    }
+    let reward =
+        slashed * BigInt::from_u64(REPORTER_PERCENT).unwrap() / BigInt::from_u64(100).unwrap();
+    let Some(reporter) = accounts.get_mut(&tx.from()) else {
+        return Err(String::from("Not enough balance"));
+    };
+    reporter.credit(tx.fee())?;
+    let Some(reward) = Amount::from_bigint(&reward) else {
+        return Err(String::from("Amount overflow"));
+    };
+    reporter.debit(reward)
+}
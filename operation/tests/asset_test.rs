@@ -0,0 +1,118 @@
+mod support;
+
+use account::asset::Asset;
+use common::amount::Amount;
+use support::{amount, apply, funded, tx};
+use tx::payload::Payload;
+use tx::tx::Tx;
+use wallet::wallet::Wallet;
+
+fn create(issuer: &Wallet, authority: &Wallet, max_supply: &str, nonce: u64) -> Tx {
+    let payload = Payload::AssetCreate {
+        symbol: String::from("PTS"),
+        decimals: 2,
+        max_supply: amount(max_supply),
+        mint_authority: authority.address_str(),
+    };
+    tx(issuer, payload, "1", nonce, 1)
+}
+
+fn mint(authority: &Wallet, id: &str, to: &Wallet, value: &str, nonce: u64) -> Tx {
+    let payload = Payload::AssetMint {
+        asset: id.to_string(),
+        to: to.address_str(),
+        amount: amount(value),
+    };
+    tx(authority, payload, "1", nonce, 1)
+}
+
+#[test]
+fn asset_definition_limits() {
+    let issuer = String::from("issuer");
+    let create = |symbol: &str, decimals, max_supply| {
+        Asset::new(
+            issuer.clone(),
+            symbol.to_string(),
+            decimals,
+            amount(max_supply),
+            issuer.clone(),
+        )
+    };
+    assert!(create("", 2, "100").is_err());
+    assert!(create("pts", 2, "100").is_err());
+    assert!(create("POINTS1234567", 2, "100").is_err());
+    assert!(create("PTS", 19, "100").is_err());
+    assert!(create("PTS", 2, "0").is_err());
+    assert!(create("PTS", 2, "100.001").is_err());
+
+    let asset = create("PTS", 2, "100.01").unwrap();
+    assert_eq!(asset.id(), "issuer:PTS");
+    assert_eq!(Asset::parse_id(&asset.id()), Some(("issuer", "PTS")));
+    assert!(asset.valid_amount(amount("0.01")));
+    assert!(!asset.valid_amount(amount("0.001")));
+    assert!(!asset.valid_amount(Amount::zero()));
+}
+
+#[test]
+fn issue_mint_transfer_and_burn() {
+    let issuer = Wallet::new();
+    let authority = Wallet::new();
+    let holder = Wallet::new();
+    let mut accounts = funded(&[(&issuer, "10"), (&authority, "10"), (&holder, "10")]);
+    let id = Asset::build_id(&issuer.address_str(), "PTS");
+
+    apply(&create(&issuer, &authority, "1000", 1), &mut accounts).unwrap();
+    let result = apply(&create(&issuer, &authority, "1000", 2), &mut accounts);
+    assert_eq!(result, Err(format!("Asset {} already exists", id)));
+
+    let result = apply(&mint(&issuer, &id, &holder, "10", 2), &mut accounts);
+    assert_eq!(
+        result,
+        Err(format!("Only the mint authority can mint {}", id))
+    );
+    let result = apply(&mint(&authority, &id, &holder, "0.001", 1), &mut accounts);
+    assert_eq!(result, Err(format!("Invalid {} amount", id)));
+    let result = apply(&mint(&authority, &id, &holder, "1000.01", 1), &mut accounts);
+    assert_eq!(result, Err(String::from("PTS max supply exceeded")));
+    apply(&mint(&authority, &id, &holder, "600", 1), &mut accounts).unwrap();
+
+    let transfer = |value: &str, nonce| {
+        let payload = Payload::AssetTransfer {
+            asset: id.clone(),
+            to: issuer.address_str(),
+            amount: amount(value),
+        };
+        tx(&holder, payload, "1", nonce, 1)
+    };
+    let result = apply(&transfer("600.01", 1), &mut accounts);
+    assert_eq!(result, Err(format!("Not enough {} balance", id)));
+    apply(&transfer("150", 1), &mut accounts).unwrap();
+
+    let burn = Payload::AssetBurn {
+        asset: id.clone(),
+        amount: amount("50"),
+    };
+    apply(&tx(&holder, burn, "1", 2, 1), &mut accounts).unwrap();
+
+    let asset = &accounts[&issuer.address_str()].issued["PTS"];
+    assert_eq!(asset.supply, amount("550"));
+    assert_eq!(
+        accounts[&holder.address_str()].asset_balance(&id),
+        amount("400")
+    );
+    assert_eq!(
+        accounts[&issuer.address_str()].asset_balance(&id),
+        amount("150")
+    );
+    assert_eq!(accounts[&holder.address_str()].balance, amount("8"));
+    assert_eq!(accounts[&authority.address_str()].balance, amount("9"));
+}
+
+#[test]
+fn unknown_asset_rejected() {
+    let wallet = Wallet::new();
+    let mut accounts = funded(&[(&wallet, "10")]);
+    let id = Asset::build_id(&wallet.address_str(), "PTS");
+    let result = apply(&mint(&wallet, &id, &wallet, "1", 1), &mut accounts);
+    assert_eq!(result, Err(format!("Unknown asset {}", id)));
+}
@@ -0,0 +1,113 @@
+mod support;
+
+use account::account::Account;
+use common::amount::Amount;
+use operation::params::Params;
+use operation::tx::process_tx;
+use std::collections::BTreeMap;
+use support::{amount, funded, tx};
+use tx::payload::{Output, Payload};
+use tx::tx::Tx;
+use wallet::wallet::Wallet;
+
+fn batch(wallet: &Wallet, outputs: &[(&str, &str)], fee: &str) -> Tx {
+    let outputs = outputs
+        .iter()
+        .map(|(to, value)| Output {
+            to: to.to_string(),
+            amount: amount(value),
+        })
+        .collect();
+    tx(wallet, Payload::Batch { outputs }, fee, 1, 1)
+}
+
+fn balance(accounts: &BTreeMap<String, Account>, wallet: &str) -> String {
+    accounts.get(wallet).unwrap().balance.to_string()
+}
+
+#[test]
+fn pays_every_output() {
+    let sender = Wallet::new();
+    let validator = Wallet::new();
+    let mut accounts = funded(&[(&sender, "100")]);
+
+    let tx = batch(
+        &sender,
+        &[("alice", "10"), ("bob", "20.5"), ("alice", "1")],
+        "0.5",
+    );
+    assert!(tx.data.valid());
+    process_tx(
+        validator.address_str(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    )
+    .unwrap();
+
+    assert_eq!(balance(&accounts, &sender.address_str()), "68");
+    assert_eq!(balance(&accounts, "alice"), "11");
+    assert_eq!(balance(&accounts, "bob"), "20.5");
+    assert_eq!(balance(&accounts, &validator.address_str()), "0.5");
+    assert_eq!(accounts.get(&sender.address_str()).unwrap().nonce, 1);
+}
+
+#[test]
+fn pays_no_output_without_balance() {
+    let sender = Wallet::new();
+    let mut accounts = funded(&[(&sender, "30")]);
+    accounts.insert(String::from("alice"), Account::new(String::from("alice")));
+
+    let tx = batch(&sender, &[("alice", "10"), ("bob", "20.5")], "0");
+    let result = process_tx(
+        String::default(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    );
+    assert_eq!(result, Err(String::from("Not enough balance")));
+    assert_eq!(balance(&accounts, &sender.address_str()), "30");
+    assert_eq!(balance(&accounts, "alice"), "0");
+    assert!(!accounts.contains_key("bob"));
+}
+
+#[test]
+fn pays_no_output_on_overflow() {
+    let sender = Wallet::new();
+    let mut accounts = funded(&[(&sender, "30")]);
+    let mut rich = Account::new(String::from("rich"));
+    rich.debit(Amount::from_units(u128::MAX)).unwrap();
+    accounts.insert(rich.wallet(), rich);
+
+    let tx = batch(&sender, &[("alice", "10"), ("rich", "1")], "0");
+    let result = process_tx(
+        String::default(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    );
+    assert_eq!(result, Err(String::from("Balance overflow")));
+    assert_eq!(balance(&accounts, &sender.address_str()), "30");
+    assert!(!accounts.contains_key("alice"));
+}
+
+#[test]
+fn sender_can_be_output() {
+    let sender = Wallet::new();
+    let mut accounts = funded(&[(&sender, "30")]);
+
+    let tx = batch(&sender, &[(&sender.address_str(), "10"), ("bob", "5")], "0");
+    process_tx(
+        String::default(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    )
+    .unwrap();
+    assert_eq!(balance(&accounts, &sender.address_str()), "25");
+    assert_eq!(balance(&accounts, "bob"), "5");
+}
@@ -0,0 +1,187 @@
+mod support;
+
+use account::account::Unbonding;
+use common::amount::Amount;
+use common::biginteger::BigInt;
+use operation::params::Params;
+use operation::tx::process_tx;
+use support::{amount, staked, tx};
+use tx::payload::Payload;
+use tx::tx::Tx;
+use tx::tx_data::TxData;
+use wallet::wallet::Wallet;
+
+fn delegation(wallet: &Wallet, delegate: bool, validator: &Wallet, value: &str, nonce: u64) -> Tx {
+    let validator = validator.address_str();
+    let amount = amount(value);
+    let payload = if delegate {
+        Payload::Delegate { validator, amount }
+    } else {
+        Payload::Undelegate { validator, amount }
+    };
+    tx(wallet, payload, "0", nonce, 1)
+}
+
+#[test]
+fn delegate_and_undelegate() {
+    let validator = Wallet::new();
+    let delegator = Wallet::new();
+    let mut accounts = staked(&[(&validator, "0", "60"), (&delegator, "100", "0")]);
+
+    let tx = delegation(&delegator, true, &validator, "40", 1);
+    process_tx(
+        String::default(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    )
+    .unwrap();
+
+    let account = accounts.get(&delegator.address_str()).unwrap();
+    assert_eq!(account.balance.to_string(), "60");
+    assert_eq!(account.bonded(), BigInt::from_u64(40).unwrap());
+    let account = accounts.get(&validator.address_str()).unwrap();
+    assert_eq!(account.weight(), BigInt::from_u64(100).unwrap());
+
+    let tx = delegation(&delegator, false, &validator, "50", 2);
+    let result = process_tx(
+        String::default(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    );
+    assert_eq!(result, Err(String::from("Not enough delegated")));
+
+    let tx = delegation(&delegator, false, &validator, "40", 3);
+    process_tx(
+        String::default(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    )
+    .unwrap();
+    let release_at = 1 + Params::default().unbonding_period;
+    let account = accounts.get(&delegator.address_str()).unwrap();
+    assert_eq!(account.balance.to_string(), "60");
+    assert!(account.delegations.is_empty());
+    assert_eq!(
+        account.unbonding,
+        vec![Unbonding {
+            amount: BigInt::from_u64(40).unwrap(),
+            release_at,
+            validator: Some(validator.address_str()),
+        }]
+    );
+    let account = accounts.get(&validator.address_str()).unwrap();
+    assert_eq!(account.weight(), BigInt::from_u64(60).unwrap());
+    assert_eq!(account.undelegating[&delegator.address_str()], release_at);
+
+    let account = accounts.get_mut(&delegator.address_str()).unwrap();
+    account.release_unbonded(release_at).unwrap();
+    assert_eq!(account.balance.to_string(), "100");
+    assert!(account.unbonding.is_empty());
+}
+
+#[test]
+fn reject_invalid_delegation() {
+    let validator = Wallet::new();
+    let delegator = Wallet::new();
+    let mut accounts = staked(&[(&validator, "0", "0"), (&delegator, "100", "0")]);
+
+    let tx = delegation(&delegator, true, &validator, "10", 1);
+    let result = process_tx(
+        String::default(),
+        &tx,
+        tx.block,
+        &mut accounts.clone(),
+        &Params::default(),
+    );
+    assert_eq!(result, Err(String::from("Validator has no stake")));
+
+    accounts.get_mut(&validator.address_str()).unwrap().stake = BigInt::from_u64(1).unwrap();
+    let tx = delegation(&delegator, true, &validator, "1.5", 1);
+    let result = process_tx(
+        String::default(),
+        &tx,
+        tx.block,
+        &mut accounts.clone(),
+        &Params::default(),
+    );
+    assert_eq!(result, Err(String::from("Delegation must be positive int")));
+
+    let tx = delegation(&delegator, true, &delegator, "10", 1);
+    let result = process_tx(
+        String::default(),
+        &tx,
+        tx.block,
+        &mut accounts.clone(),
+        &Params::default(),
+    );
+    assert_eq!(result, Err(String::from("Cannot delegate to self")));
+}
+
+#[test]
+fn fee_split_by_commission() {
+    let validator = Wallet::new();
+    let delegator = Wallet::new();
+    let sender = Wallet::new();
+    let mut accounts = staked(&[
+        (&validator, "0", "60"),
+        (&delegator, "40", "0"),
+        (&sender, "10", "0"),
+    ]);
+
+    let tx = delegation(&delegator, true, &validator, "40", 1);
+    process_tx(
+        String::default(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    )
+    .unwrap();
+    let tx = support::tx(
+        &validator,
+        Payload::Commission {
+            rate: amount("0.1"),
+        },
+        "0",
+        1,
+        1,
+    );
+    process_tx(
+        String::default(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    )
+    .unwrap();
+
+    let data = TxData::new(
+        &sender,
+        String::default(),
+        Wallet::new().address_str(),
+        String::from("1"),
+        String::from("1"),
+        1,
+    )
+    .unwrap();
+    let tx = Tx::from_tx(data, String::default(), 1);
+    process_tx(
+        validator.address_str(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    )
+    .unwrap();
+
+    let account = accounts.get(&validator.address_str()).unwrap();
+    assert_eq!(account.balance, Amount::from_str("0.64").unwrap());
+    let account = accounts.get(&delegator.address_str()).unwrap();
+    assert_eq!(account.balance, Amount::from_str("0.36").unwrap());
+}
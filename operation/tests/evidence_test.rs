@@ -0,0 +1,203 @@
+mod support;
+
+use account::account::{Account, Unbonding};
+use common::biginteger::BigInt;
+use operation::params::Params;
+use operation::tx::process_tx;
+use std::collections::BTreeMap;
+use support::{equivocation, funded, staked};
+use tx::evidence::Evidence;
+use tx::tx::Tx;
+use tx::tx_data::TxData;
+use wallet::wallet::Wallet;
+
+fn accounts(offender: &Wallet, reporter: &Wallet) -> BTreeMap<String, Account> {
+    let mut accounts = staked(&[(offender, "0", "100")]);
+    accounts.extend(funded(&[(reporter, "1")]));
+    accounts
+}
+
+#[test]
+fn valid_evidence_slashes_validator() {
+    let offender = Wallet::new();
+    let reporter = Wallet::new();
+    let validator = Wallet::new();
+    let mut accounts = accounts(&offender, &reporter);
+
+    let evidence = equivocation(&offender, 1);
+    let data = TxData::new_evidence(
+        &reporter,
+        String::default(),
+        evidence,
+        String::from("0.01"),
+        1,
+    )
+    .unwrap();
+    let tx = Tx::from_tx(data, String::default(), 1);
+    process_tx(
+        validator.address_str(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    )
+    .unwrap();
+
+    let offender = accounts.get(&offender.address_str()).unwrap();
+    let reporter = accounts.get(&reporter.address_str()).unwrap();
+    let validator = accounts.get(&validator.address_str()).unwrap();
+    assert_eq!(offender.stake(), BigInt::from_u64(50).unwrap());
+    assert_eq!(reporter.balance.to_string(), "5.99");
+    assert_eq!(validator.balance.to_string(), "0.01");
+}
+
+#[test]
+fn slash_delegations_to_offender() {
+    let offender = Wallet::new();
+    let reporter = Wallet::new();
+    let delegator = Wallet::new();
+    let undelegated = Wallet::new();
+    let mut accounts = accounts(&offender, &reporter);
+    let forty = BigInt::from_u64(40).unwrap();
+    let account = accounts.get_mut(&offender.address_str()).unwrap();
+    account.add_delegator(&delegator.address_str(), forty.clone());
+    account.undelegating.insert(undelegated.address_str(), 20);
+    let mut account = Account::new(delegator.address_str());
+    account.delegations.insert(offender.address_str(), forty);
+    accounts.insert(delegator.address_str(), account);
+    let mut account = Account::new(undelegated.address_str());
+    account.unbonding.push(Unbonding {
+        amount: BigInt::from_u64(20).unwrap(),
+        release_at: 20,
+        validator: Some(offender.address_str()),
+    });
+    accounts.insert(undelegated.address_str(), account);
+
+    let evidence = equivocation(&offender, 1);
+    let data =
+        TxData::new_evidence(&reporter, String::default(), evidence, String::from("0"), 1).unwrap();
+    let tx = Tx::from_tx(data, String::default(), 1);
+    process_tx(
+        String::default(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    )
+    .unwrap();
+
+    let twenty = BigInt::from_u64(20).unwrap();
+    let account = accounts.get(&offender.address_str()).unwrap();
+    assert_eq!(account.stake(), BigInt::from_u64(50).unwrap());
+    assert_eq!(account.delegators[&delegator.address_str()], twenty);
+    let account = accounts.get(&delegator.address_str()).unwrap();
+    assert_eq!(account.delegations[&offender.address_str()], twenty);
+    let account = accounts.get(&undelegated.address_str()).unwrap();
+    assert_eq!(account.unbonding[0].amount, BigInt::from_u64(10).unwrap());
+    let account = accounts.get(&reporter.address_str()).unwrap();
+    assert_eq!(account.balance.to_string(), "9");
+}
+
+#[test]
+fn reject_duplicate_evidence() {
+    let offender = Wallet::new();
+    let reporter = Wallet::new();
+    let mut accounts = accounts(&offender, &reporter);
+
+    for nonce in 1..=2 {
+        let evidence = equivocation(&offender, 1);
+        let data = TxData::new_evidence(
+            &reporter,
+            String::default(),
+            evidence,
+            String::from("0"),
+            nonce,
+        )
+        .unwrap();
+        let tx = Tx::from_tx(data, String::default(), 1);
+        let result = process_tx(
+            String::default(),
+            &tx,
+            tx.block,
+            &mut accounts,
+            &Params::default(),
+        );
+        assert_eq!(result.is_ok(), nonce == 1);
+    }
+    let offender = accounts.get(&offender.address_str()).unwrap();
+    assert_eq!(offender.stake(), BigInt::from_u64(50).unwrap());
+}
+
+#[test]
+fn reject_invalid_evidence() {
+    let offender = Wallet::new();
+    let reporter = Wallet::new();
+    let accounts = accounts(&offender, &reporter);
+
+    let header = equivocation(&offender, 1).first;
+    let same = Evidence::new(header.clone(), header.clone());
+    let mut forged = equivocation(&offender, 1);
+    forged.second.merkle_root = String::from("forged");
+    let other = Wallet::new();
+    let mixed = Evidence::new(header, equivocation(&other, 1).second);
+
+    for evidence in [same, forged, mixed] {
+        let data =
+            TxData::new_evidence(&reporter, String::default(), evidence, String::from("0"), 1)
+                .unwrap();
+        let tx = Tx::from_tx(data, String::default(), 1);
+        let result = process_tx(
+            String::default(),
+            &tx.clone(),
+            tx.block,
+            &mut accounts.clone(),
+            &Params::default(),
+        );
+        assert_eq!(result, Err(String::from("Invalid evidence")));
+    }
+}
+
+#[test]
+fn reject_evidence_from_other_chain() {
+    let offender = Wallet::new();
+    let reporter = Wallet::new();
+    let mut accounts = accounts(&offender, &reporter);
+    let params = Params {
+        chain_id: String::from("xchg-testnet"),
+        ..Params::default()
+    };
+
+    let evidence = equivocation(&offender, 1);
+    let data = TxData::new_evidence(
+        &reporter,
+        params.chain_id.clone(),
+        evidence,
+        String::from("0"),
+        1,
+    )
+    .unwrap();
+    let tx = Tx::from_tx(data, String::default(), 1);
+    let result = process_tx(String::default(), &tx, tx.block, &mut accounts, &params);
+    assert_eq!(result, Err(String::from("Evidence from other chain")));
+}
+
+#[test]
+fn reject_evidence_for_unstaked_validator() {
+    let offender = Wallet::new();
+    let reporter = Wallet::new();
+    let mut accounts = accounts(&offender, &reporter);
+    accounts.insert(offender.address_str(), Account::new(offender.address_str()));
+
+    let evidence = equivocation(&offender, 1);
+    let data =
+        TxData::new_evidence(&reporter, String::default(), evidence, String::from("0"), 1).unwrap();
+    let tx = Tx::from_tx(data, String::default(), 1);
+    let result = process_tx(
+        String::default(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    );
+    assert_eq!(result, Err(String::from("Nothing to slash")));
+}
@@ -0,0 +1,230 @@
+mod support;
+
+use account::account::{Account, Htlc};
+use common::amount::Amount;
+use operation::htlc::htlc_id;
+use sha2::Digest;
+use std::collections::BTreeMap;
+use support::{apply, funded, tx};
+use tx::payload::Payload;
+use tx::tx::Tx;
+use wallet::wallet::Wallet;
+
+const PREIMAGE: &str = "73776170207365637265";
+
+fn hashlock() -> String {
+    hex::encode(sha2::Sha256::digest(hex::decode(PREIMAGE).unwrap()))
+}
+
+fn create(sender: &Wallet, recipient: &Wallet, timeout: u64) -> Tx {
+    let payload = Payload::HtlcCreate {
+        to: recipient.address_str(),
+        amount: Amount::from_str("40").unwrap(),
+        hashlock: hashlock(),
+        timeout,
+    };
+    tx(sender, payload, "1", 1, 5)
+}
+
+fn claim(recipient: &Wallet, sender: &Wallet, id: &str, preimage: &str, block: u64) -> Tx {
+    claim_with_nonce(recipient, sender, id, preimage, 1, block)
+}
+
+fn claim_with_nonce(
+    recipient: &Wallet,
+    sender: &Wallet,
+    id: &str,
+    preimage: &str,
+    nonce: u64,
+    block: u64,
+) -> Tx {
+    let payload = Payload::HtlcClaim {
+        sender: sender.address_str(),
+        id: id.to_string(),
+        preimage: preimage.to_string(),
+    };
+    tx(recipient, payload, "0.5", nonce, block)
+}
+
+fn refund(sender: &Wallet, id: &str, block: u64) -> Tx {
+    let payload = Payload::HtlcRefund { id: id.to_string() };
+    tx(sender, payload, "0.5", 2, block)
+}
+
+fn balance(accounts: &BTreeMap<String, Account>, wallet: &Wallet) -> String {
+    accounts
+        .get(&wallet.address_str())
+        .unwrap()
+        .balance
+        .to_string()
+}
+
+#[test]
+fn create_locks_funds_outside_balance() {
+    let sender = Wallet::new();
+    let recipient = Wallet::new();
+    let mut accounts = funded(&[(&sender, "100")]);
+    let tx = create(&sender, &recipient, 20);
+    apply(&tx, &mut accounts).unwrap();
+
+    let account = accounts.get(&sender.address_str()).unwrap();
+    assert_eq!(account.balance.to_string(), "59");
+    assert_eq!(account.locked_amount(), Amount::from_str("40").ok());
+    assert_eq!(
+        account.htlcs.get(&htlc_id(&tx)),
+        Some(&Htlc {
+            to: recipient.address_str(),
+            amount: Amount::from_str("40").unwrap(),
+            hashlock: hashlock(),
+            timeout: 20,
+        })
+    );
+    assert!(!accounts.contains_key(&recipient.address_str()));
+}
+
+#[test]
+fn reject_invalid_create() {
+    let sender = Wallet::new();
+    let recipient = Wallet::new();
+    let mut accounts = funded(&[(&sender, "100")]);
+    let invalid = |to: &Wallet, amount: &str, hashlock: String, timeout: u64| {
+        let payload = Payload::HtlcCreate {
+            to: to.address_str(),
+            amount: Amount::from_str(amount).unwrap(),
+            hashlock,
+            timeout,
+        };
+        tx(&sender, payload, "1", 1, 5)
+    };
+
+    let result = apply(&invalid(&recipient, "40", hashlock(), 5), &mut accounts);
+    assert_eq!(
+        result.unwrap_err(),
+        "HTLC timeout must be after current block"
+    );
+    let result = apply(
+        &invalid(&recipient, "40", String::from("abcd"), 20),
+        &mut accounts,
+    );
+    assert_eq!(result.unwrap_err(), "Invalid hashlock");
+    let result = apply(&invalid(&recipient, "0", hashlock(), 20), &mut accounts);
+    assert_eq!(result.unwrap_err(), "HTLC amount must be positive");
+    let result = apply(&invalid(&sender, "40", hashlock(), 20), &mut accounts);
+    assert_eq!(result.unwrap_err(), "Cannot lock funds to self");
+    let result = apply(&invalid(&recipient, "100", hashlock(), 20), &mut accounts);
+    assert_eq!(result.unwrap_err(), "Not enough balance");
+}
+
+#[test]
+fn recipient_claims_with_preimage_before_timeout() {
+    let sender = Wallet::new();
+    let recipient = Wallet::new();
+    let mut accounts = funded(&[(&sender, "100"), (&recipient, "0")]);
+    let tx = create(&sender, &recipient, 20);
+    apply(&tx, &mut accounts).unwrap();
+    let id = htlc_id(&tx);
+
+    apply(
+        &claim(&recipient, &sender, &id, PREIMAGE, 19),
+        &mut accounts,
+    )
+    .unwrap();
+    assert_eq!(balance(&accounts, &recipient), "39.5");
+    assert_eq!(balance(&accounts, &sender), "59");
+    let account = accounts.get(&sender.address_str()).unwrap();
+    assert!(account.htlcs.is_empty());
+
+    let tx = claim_with_nonce(&recipient, &sender, &id, PREIMAGE, 2, 19);
+    assert_eq!(
+        apply(&tx, &mut accounts).unwrap_err(),
+        format!("Unknown HTLC {}", id)
+    );
+}
+
+#[test]
+fn reject_claim_with_wrong_preimage() {
+    let sender = Wallet::new();
+    let recipient = Wallet::new();
+    let mut accounts = funded(&[(&sender, "100"), (&recipient, "1")]);
+    let tx = create(&sender, &recipient, 20);
+    apply(&tx, &mut accounts).unwrap();
+
+    let result = apply(
+        &claim(&recipient, &sender, &htlc_id(&tx), "00", 10),
+        &mut accounts,
+    );
+    assert_eq!(result.unwrap_err(), "Invalid preimage");
+    assert_eq!(balance(&accounts, &recipient), "1");
+    let account = accounts.get(&sender.address_str()).unwrap();
+    assert_eq!(account.htlcs.len(), 1);
+}
+
+#[test]
+fn reject_claim_by_other_wallet() {
+    let sender = Wallet::new();
+    let recipient = Wallet::new();
+    let other = Wallet::new();
+    let mut accounts = funded(&[(&sender, "100"), (&other, "1")]);
+    let tx = create(&sender, &recipient, 20);
+    apply(&tx, &mut accounts).unwrap();
+
+    let result = apply(
+        &claim(&other, &sender, &htlc_id(&tx), PREIMAGE, 10),
+        &mut accounts,
+    );
+    assert_eq!(result.unwrap_err(), "Only the recipient can claim HTLC");
+}
+
+#[test]
+fn reject_claim_after_timeout() {
+    let sender = Wallet::new();
+    let recipient = Wallet::new();
+    let mut accounts = funded(&[(&sender, "100"), (&recipient, "1")]);
+    let tx = create(&sender, &recipient, 20);
+    apply(&tx, &mut accounts).unwrap();
+
+    let result = apply(
+        &claim(&recipient, &sender, &htlc_id(&tx), PREIMAGE, 20),
+        &mut accounts,
+    );
+    assert_eq!(result.unwrap_err(), "HTLC expired");
+    assert_eq!(balance(&accounts, &recipient), "1");
+}
+
+#[test]
+fn sender_refunds_after_timeout() {
+    let sender = Wallet::new();
+    let recipient = Wallet::new();
+    let mut accounts = funded(&[(&sender, "100")]);
+    let tx = create(&sender, &recipient, 20);
+    apply(&tx, &mut accounts).unwrap();
+    let id = htlc_id(&tx);
+
+    let result = apply(&refund(&sender, &id, 19), &mut accounts);
+    assert_eq!(result.unwrap_err(), "HTLC locked until block #20");
+
+    apply(&refund(&sender, &id, 20), &mut accounts).unwrap();
+    assert_eq!(balance(&accounts, &sender), "98.5");
+    let account = accounts.get(&sender.address_str()).unwrap();
+    assert!(account.htlcs.is_empty());
+}
+
+#[test]
+fn reject_refund_by_recipient() {
+    let sender = Wallet::new();
+    let recipient = Wallet::new();
+    let mut accounts = funded(&[(&sender, "100"), (&recipient, "1")]);
+    let tx = create(&sender, &recipient, 20);
+    apply(&tx, &mut accounts).unwrap();
+    let id = htlc_id(&tx);
+
+    let payload = Payload::HtlcRefund { id: id.clone() };
+    let result = apply(&tx_for(&recipient, payload), &mut accounts);
+    assert_eq!(result.unwrap_err(), format!("Unknown HTLC {}", id));
+    let account = accounts.get(&sender.address_str()).unwrap();
+    assert_eq!(account.htlcs.len(), 1);
+}
+
+fn tx_for(wallet: &Wallet, payload: Payload) -> Tx {
+    tx(wallet, payload, "0", 1, 30)
+}
@@ -0,0 +1,87 @@
+#![allow(dead_code)]
+
+use account::account::Account;
+use block::block::{Block, SLOT_DURATION};
+use common::amount::Amount;
+use common::biginteger::BigInt;
+use operation::params::Params;
+use operation::tx::process_tx;
+use std::collections::BTreeMap;
+use tx::evidence::Evidence;
+use tx::payload::Payload;
+use tx::tx::Tx;
+use tx::tx_data::TxData;
+use wallet::wallet::Wallet;
+
+pub fn amount(value: &str) -> Amount {
+    Amount::from_str(value).unwrap()
+}
+
+pub fn funded(wallets: &[(&Wallet, &str)]) -> BTreeMap<String, Account> {
+    let mut accounts = BTreeMap::new();
+    for (wallet, balance) in wallets {
+        let mut account = Account::new(wallet.address_str());
+        account.debit(amount(balance)).unwrap();
+        accounts.insert(wallet.address_str(), account);
+    }
+    accounts
+}
+
+/// Funds each wallet with the first amount and stakes the second.
+pub fn staked(wallets: &[(&Wallet, &str, &str)]) -> BTreeMap<String, Account> {
+    let mut accounts = BTreeMap::new();
+    for (wallet, balance, stake) in wallets {
+        let mut account = Account::new(wallet.address_str());
+        account.debit(amount(balance)).unwrap();
+        account.stake = BigInt::from_str(stake).unwrap();
+        accounts.insert(wallet.address_str(), account);
+    }
+    accounts
+}
+
+pub fn tx(wallet: &Wallet, payload: Payload, fee: &str, nonce: u64, block: u64) -> Tx {
+    let data =
+        TxData::with_payload(wallet, String::default(), payload, String::from(fee), nonce).unwrap();
+    Tx::from_tx(data, String::default(), block)
+}
+
+/// Applies the tx at the block it is stamped with.
+pub fn apply(tx: &Tx, accounts: &mut BTreeMap<String, Account>) -> Result<(), String> {
+    apply_at(tx, tx.block, accounts)
+}
+
+/// Applies the tx to a copy of the accounts, kept only on success like blocks.
+pub fn apply_at(
+    tx: &Tx,
+    height: u64,
+    accounts: &mut BTreeMap<String, Account>,
+) -> Result<(), String> {
+    let mut updated = accounts.clone();
+    process_tx(
+        String::from("validator"),
+        tx,
+        height,
+        &mut updated,
+        &Params::default(),
+    )?;
+    *accounts = updated;
+    Ok(())
+}
+
+/// Two different headers signed by `offender` for block `idx`.
+pub fn equivocation(offender: &Wallet, idx: u64) -> Evidence {
+    let timestamp = 10 * SLOT_DURATION;
+    let header = |parent_hash: &str| {
+        Block::new(
+            offender,
+            String::default(),
+            idx,
+            String::from(parent_hash),
+            timestamp,
+            vec![],
+        )
+        .unwrap()
+        .header()
+    };
+    Evidence::new(header("first"), header("second"))
+}
@@ -1,9 +1,11 @@
 use account::account::Account;
-use common::bigdecimal::BigDecimal;
+use common::amount::Amount;
 use common::biginteger::BigInt;
+use operation::params::Params;
 use operation::tx::process_tx;
 use std::collections::BTreeMap;
 use std::process::exit;
+use tx::payload::Payload;
 use tx::tx::Tx;
 use tx::tx_data::TxData;
 use wallet::wallet::Wallet;
@@ -17,6 +19,7 @@ fn not_enough_balance_tx() {
     let to = Wallet::new();
     let tx_data = TxData::new(
         &from,
+        String::default(),
         to.address_str(),
         String::from("0.001"),
         String::from("1"),
@@ -24,7 +27,13 @@ fn not_enough_balance_tx() {
     )
     .unwrap();
     let tx = Tx::from_tx(tx_data, String::default(), 1);
-    if let Err(err) = process_tx(DEFAULT_VALIDATOR.to_string(), &tx, &mut accounts) {
+    if let Err(err) = process_tx(
+        DEFAULT_VALIDATOR.to_string(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    ) {
         assert_eq!(err, "Not enough balance");
     } else {
         assert!(false);
@@ -35,16 +44,24 @@ fn not_enough_balance_tx() {
 fn not_enough_balance_unstake() {
     let mut accounts = BTreeMap::new();
     let from = Wallet::new();
-    let tx_data = TxData::new(
+    let tx_data = TxData::with_payload(
         &from,
-        String::from("UNSTAKE"),
-        String::from("0.001"),
+        String::default(),
+        Payload::Unstake {
+            amount: Amount::from_str("0.001").unwrap(),
+        },
         String::from("1"),
         1,
     )
     .unwrap();
     let tx = Tx::from_tx(tx_data, String::default(), 1);
-    if let Err(err) = process_tx(DEFAULT_VALIDATOR.to_string(), &tx, &mut accounts) {
+    if let Err(err) = process_tx(
+        DEFAULT_VALIDATOR.to_string(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    ) {
         assert_eq!(err, "Not enough balance");
     } else {
         assert!(false);
@@ -56,28 +73,34 @@ fn valid_unstake() {
     let mut accounts = BTreeMap::new();
     let from = Wallet::new();
     let mut account = Account::new(from.address_str());
-    account
-        .debit(BigDecimal::from_str("10.02").unwrap())
-        .unwrap();
+    account.debit(Amount::from_str("10.02").unwrap()).unwrap();
     account
         .stake_amount(
-            BigDecimal::from_str("10").unwrap(),
-            BigDecimal::from_str("0.01").unwrap(),
+            Amount::from_str("10").unwrap(),
+            Amount::from_str("0.01").unwrap(),
         )
         .unwrap();
 
     accounts.insert(from.address_str(), account);
 
-    let tx_data = TxData::new(
+    let tx_data = TxData::with_payload(
         &from,
-        String::from("UNSTAKE"),
-        String::from("1"),
+        String::default(),
+        Payload::Unstake {
+            amount: Amount::from_str("1").unwrap(),
+        },
         String::from("0.01"),
         1,
     )
     .unwrap();
     let tx = Tx::from_tx(tx_data, String::default(), 1);
-    if let Err(err) = process_tx(DEFAULT_VALIDATOR.to_string(), &tx, &mut accounts) {
+    if let Err(err) = process_tx(
+        DEFAULT_VALIDATOR.to_string(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    ) {
         assert!(false, "{}", err);
     }
     assert_eq!(
@@ -91,19 +114,27 @@ fn not_enough_for_fee_unstake() {
     let mut accounts = BTreeMap::new();
     let from = Wallet::new();
     let mut account = Account::new(from.address_str());
-    account.debit(BigDecimal::from_str("10").unwrap()).unwrap();
+    account.debit(Amount::from_str("10").unwrap()).unwrap();
 
     accounts.insert(from.address_str(), account);
-    let tx_data = TxData::new(
+    let tx_data = TxData::with_payload(
         &from,
-        String::from("UNSTAKE"),
-        String::from("1"),
+        String::default(),
+        Payload::Unstake {
+            amount: Amount::from_str("1").unwrap(),
+        },
         String::from("0.01"),
         1,
     )
     .unwrap();
     let tx = Tx::from_tx(tx_data, String::default(), 1);
-    if let Err(err) = process_tx(DEFAULT_VALIDATOR.to_string(), &tx, &mut accounts) {
+    if let Err(err) = process_tx(
+        DEFAULT_VALIDATOR.to_string(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    ) {
         println!("{}", err);
         assert!(true);
     } else {
@@ -118,19 +149,27 @@ fn not_enough_for_fee_stake() {
     let from = Wallet::new();
 
     let mut account = Account::new(from.address_str());
-    account.debit(BigDecimal::from_str("1").unwrap()).unwrap();
+    account.debit(Amount::from_str("1").unwrap()).unwrap();
 
     balances.insert(from.address_str(), account);
-    let tx_data = TxData::new(
+    let tx_data = TxData::with_payload(
         &from,
-        String::from("STAKE"),
-        String::from("1"),
+        String::default(),
+        Payload::Stake {
+            amount: Amount::from_str("1").unwrap(),
+        },
         String::from("0.01"),
         1,
     )
     .unwrap();
     let tx = Tx::from_tx(tx_data, String::default(), 1);
-    if let Err(err) = process_tx(DEFAULT_VALIDATOR.to_string(), &tx, &mut balances) {
+    if let Err(err) = process_tx(
+        DEFAULT_VALIDATOR.to_string(),
+        &tx,
+        tx.block,
+        &mut balances,
+        &Params::default(),
+    ) {
         println!("{}", err);
         assert!(true);
     } else {
@@ -143,12 +182,13 @@ fn valid_tx() {
     let mut balances = BTreeMap::new();
     let from = Wallet::new();
     let mut account = Account::new(from.address_str());
-    account.debit(BigDecimal::from_str("1").unwrap()).unwrap();
+    account.debit(Amount::from_str("1").unwrap()).unwrap();
 
     balances.insert(from.address_str(), account);
     let to = Wallet::new();
     let tx_data = TxData::new(
         &from,
+        String::default(),
         to.address_str(),
         String::from("0.001"),
         String::from("0"),
@@ -156,7 +196,13 @@ fn valid_tx() {
     )
     .unwrap();
     let tx = Tx::from_tx(tx_data, String::default(), 1);
-    if let Err(err) = process_tx(DEFAULT_VALIDATOR.to_string(), &tx, &mut balances) {
+    if let Err(err) = process_tx(
+        DEFAULT_VALIDATOR.to_string(),
+        &tx,
+        tx.block,
+        &mut balances,
+        &Params::default(),
+    ) {
         assert!(false, "{}", err);
     }
 }
@@ -167,19 +213,27 @@ fn valid_stake() {
 
     let from = Wallet::new();
     let mut account = Account::new(from.address_str());
-    account.debit(BigDecimal::from_str("10").unwrap()).unwrap();
+    account.debit(Amount::from_str("10").unwrap()).unwrap();
 
     accounts.insert(from.address_str(), account);
-    let tx_data = TxData::new(
+    let tx_data = TxData::with_payload(
         &from,
-        String::from("STAKE"),
-        String::from("1"),
+        String::default(),
+        Payload::Stake {
+            amount: Amount::from_str("1").unwrap(),
+        },
         String::from("1"),
         1,
     )
     .unwrap();
     let tx = Tx::from_tx(tx_data, String::default(), 1);
-    if let Err(err) = process_tx(DEFAULT_VALIDATOR.to_string(), &tx, &mut accounts) {
+    if let Err(err) = process_tx(
+        DEFAULT_VALIDATOR.to_string(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    ) {
         assert!(false, "{}", err);
     }
     assert_eq!(
@@ -194,13 +248,14 @@ fn wrong_nonce_value() {
 
     let from = Wallet::new();
     let mut account = Account::new(from.address_str());
-    account.debit(BigDecimal::from_str("1").unwrap()).unwrap();
+    account.debit(Amount::from_str("1").unwrap()).unwrap();
 
     accounts.insert(from.address_str(), account);
 
     let to = Wallet::new();
     let tx_data = TxData::new(
         &from,
+        String::default(),
         to.address_str(),
         String::from("0.001"),
         String::from("0"),
@@ -208,7 +263,13 @@ fn wrong_nonce_value() {
     )
     .unwrap();
     let tx = Tx::from_tx(tx_data, String::default(), 1);
-    let Err(err) = process_tx(DEFAULT_VALIDATOR.to_string(), &tx, &mut accounts) else {
+    let Err(err) = process_tx(
+        DEFAULT_VALIDATOR.to_string(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    ) else {
         assert!(false, "Expect nonce validation");
         exit(-1);
     };
@@ -224,13 +285,14 @@ fn tx_fee() {
 
     let from = Wallet::new();
     let mut account = Account::new(from.address_str());
-    account.debit(BigDecimal::from_str("100").unwrap()).unwrap();
+    account.debit(Amount::from_str("100").unwrap()).unwrap();
     account.set_nonce(1).unwrap();
 
     let to = Wallet::new();
     accounts.insert(from.address_str(), account);
     let tx_data = TxData::new(
         &from,
+        String::default(),
         to.address_str(),
         start_balance.clone(),
         start_fee.clone(),
@@ -239,13 +301,20 @@ fn tx_fee() {
     .unwrap();
     let validator = Wallet::new();
     let tx = Tx::from_tx(tx_data, String::default(), 1);
-    process_tx(validator.address_str(), &tx, &mut accounts).unwrap();
+    process_tx(
+        validator.address_str(),
+        &tx,
+        tx.block,
+        &mut accounts,
+        &Params::default(),
+    )
+    .unwrap();
 
     let validator_balance = accounts.get(&validator.address_str()).unwrap();
     let from_balance = accounts.get(&from.address_str()).unwrap();
     let to_balance = accounts.get(&to.address_str()).unwrap();
 
-    assert_eq!(validator_balance.balance.to_plain_string(), start_fee);
-    assert_eq!(from_balance.balance.to_plain_string(), "99.899");
-    assert_eq!(to_balance.balance.to_plain_string(), "0.1");
+    assert_eq!(validator_balance.balance.to_string(), start_fee);
+    assert_eq!(from_balance.balance.to_string(), "99.899");
+    assert_eq!(to_balance.balance.to_string(), "0.1");
 }
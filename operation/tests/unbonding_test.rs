@@ -0,0 +1,127 @@
+mod support;
+
+use account::account::Unbonding;
+use common::biginteger::BigInt;
+use operation::params::Params;
+use operation::tx::process_tx;
+use support::{amount, equivocation, funded, staked, tx};
+use tx::payload::Payload;
+use tx::tx::Tx;
+use tx::tx_data::TxData;
+use wallet::wallet::Wallet;
+
+const PARAMS: Params = Params {
+    chain_id: String::new(),
+    unbonding_period: 10,
+};
+
+fn unstake(wallet: &Wallet, value: &str, block: u64) -> Tx {
+    let payload = Payload::Unstake {
+        amount: amount(value),
+    };
+    tx(wallet, payload, "0", 1, block)
+}
+
+#[test]
+fn unstaked_funds_locked_until_maturity() {
+    let wallet = Wallet::new();
+    let mut accounts = staked(&[(&wallet, "1", "10")]);
+    process_tx(
+        String::default(),
+        &unstake(&wallet, "4", 5),
+        5,
+        &mut accounts,
+        &PARAMS,
+    )
+    .unwrap();
+
+    let account = accounts.get(&wallet.address_str()).unwrap();
+    assert_eq!(account.stake(), BigInt::from_u64(6).unwrap());
+    assert_eq!(account.balance.to_string(), "1");
+    assert_eq!(
+        account.unbonding,
+        vec![Unbonding {
+            amount: BigInt::from_u64(4).unwrap(),
+            release_at: 15,
+            validator: None,
+        }]
+    );
+
+    let transfer = |block| {
+        let data = TxData::new(
+            &wallet,
+            String::default(),
+            Wallet::new().address_str(),
+            String::from("5"),
+            String::from("0"),
+            2,
+        )
+        .unwrap();
+        Tx::from_tx(data, String::default(), block)
+    };
+    let result = process_tx(String::default(), &transfer(14), 14, &mut accounts, &PARAMS);
+    assert_eq!(result, Err(String::from("Not enough balance")));
+
+    accounts.get_mut(&wallet.address_str()).unwrap().nonce = 1;
+    process_tx(String::default(), &transfer(15), 15, &mut accounts, &PARAMS).unwrap();
+    let account = accounts.get(&wallet.address_str()).unwrap();
+    assert!(account.unbonding.is_empty());
+    assert_eq!(account.balance.to_string(), "0");
+}
+
+#[test]
+fn lockup_ignores_block_stamped_on_tx() {
+    let wallet = Wallet::new();
+    let mut accounts = staked(&[(&wallet, "1", "10")]);
+    process_tx(
+        String::default(),
+        &unstake(&wallet, "4", 1000),
+        5,
+        &mut accounts,
+        &PARAMS,
+    )
+    .unwrap();
+    let account = accounts.get(&wallet.address_str()).unwrap();
+    assert_eq!(account.unbonding[0].release_at, 15);
+
+    let data = TxData::new(
+        &wallet,
+        String::default(),
+        Wallet::new().address_str(),
+        String::from("5"),
+        String::from("0"),
+        2,
+    )
+    .unwrap();
+    let tx = Tx::from_tx(data, String::default(), 1000);
+    let result = process_tx(String::default(), &tx, 14, &mut accounts, &PARAMS);
+    assert_eq!(result, Err(String::from("Not enough balance")));
+}
+
+#[test]
+fn unbonding_stake_is_slashable() {
+    let offender = Wallet::new();
+    let reporter = Wallet::new();
+    let mut accounts = staked(&[(&offender, "0", "100")]);
+    accounts.extend(funded(&[(&reporter, "1")]));
+    process_tx(
+        String::default(),
+        &unstake(&offender, "100", 1),
+        1,
+        &mut accounts,
+        &PARAMS,
+    )
+    .unwrap();
+
+    let evidence = equivocation(&offender, 1);
+    let data =
+        TxData::new_evidence(&reporter, String::default(), evidence, String::from("0"), 1).unwrap();
+    let tx = Tx::from_tx(data, String::default(), 2);
+    process_tx(String::default(), &tx, tx.block, &mut accounts, &PARAMS).unwrap();
+
+    let account = accounts.get(&offender.address_str()).unwrap();
+    assert_eq!(account.stake(), BigInt::zero());
+    assert_eq!(account.unbonding_amount(), BigInt::from_u64(50).unwrap());
+    let account = accounts.get(&reporter.address_str()).unwrap();
+    assert_eq!(account.balance.to_string(), "6");
+}
@@ -0,0 +1,183 @@
+mod support;
+
+use account::account::{Account, Vesting};
+use common::amount::Amount;
+use std::collections::BTreeMap;
+use support::{amount, apply, apply_at};
+use tx::payload::Payload;
+use tx::tx::Tx;
+use tx::tx_data::{GENESIS_WALLET, TX_VERSION, TxData};
+use wallet::wallet::Wallet;
+
+fn vesting(to: &Wallet, value: &str, start: u64, cliff: u64, end: u64) -> Payload {
+    Payload::Vesting {
+        to: to.address_str(),
+        amount: amount(value),
+        start,
+        cliff,
+        end,
+    }
+}
+
+fn genesis(payload: Payload, block: u64) -> Tx {
+    let data = TxData {
+        version: TX_VERSION,
+        chain_id: String::default(),
+        from: String::from(GENESIS_WALLET),
+        payload,
+        fee: Amount::zero(),
+        nonce: 1,
+        valid_from_block: None,
+        valid_until_block: None,
+        memo: None,
+        multisig: None,
+        signature: String::from(GENESIS_WALLET),
+        signatures: BTreeMap::new(),
+    };
+    Tx::from_tx(data, String::default(), block)
+}
+
+fn transfer(wallet: &Wallet, value: &str, nonce: u64, block: u64) -> Tx {
+    let data = TxData::new(
+        wallet,
+        String::default(),
+        Wallet::new().address_str(),
+        String::from(value),
+        String::from("0"),
+        nonce,
+    )
+    .unwrap();
+    Tx::from_tx(data, String::default(), block)
+}
+
+#[test]
+fn vesting_schedule() {
+    let vesting = Vesting::new(amount("100"), 10, 20, 60).unwrap();
+    assert_eq!(vesting.vested(19), Amount::zero());
+    assert_eq!(vesting.vested(20), amount("20"));
+    assert_eq!(vesting.vested(35), amount("50"));
+    assert_eq!(vesting.vested(60), amount("100"));
+    assert_eq!(vesting.locked(35), amount("50"));
+
+    assert!(Vesting::new(Amount::zero(), 0, 0, 10).is_err());
+    assert!(Vesting::new(amount("1"), 5, 4, 10).is_err());
+    assert!(Vesting::new(amount("1"), 0, 11, 10).is_err());
+}
+
+#[test]
+fn genesis_vesting_only_in_genesis_block() {
+    let wallet = Wallet::new();
+    let mut accounts = BTreeMap::new();
+    let result = apply(
+        &genesis(vesting(&wallet, "100", 0, 10, 50), 1),
+        &mut accounts,
+    );
+    assert_eq!(
+        result,
+        Err(String::from("Genesis tx outside of genesis block"))
+    );
+    let result = apply_at(
+        &genesis(vesting(&wallet, "100", 0, 10, 50), 0),
+        1,
+        &mut accounts,
+    );
+    assert_eq!(
+        result,
+        Err(String::from("Genesis tx outside of genesis block"))
+    );
+
+    apply(
+        &genesis(vesting(&wallet, "100", 0, 10, 50), 0),
+        &mut accounts,
+    )
+    .unwrap();
+    let account = accounts.get(&wallet.address_str()).unwrap();
+    assert_eq!(account.balance, Amount::zero());
+    assert_eq!(account.vesting_locked(0), Some(amount("100")));
+    assert_eq!(account.unreleased_vesting(), Some(amount("100")));
+}
+
+#[test]
+fn spend_only_vested_amount() {
+    let wallet = Wallet::new();
+    let mut accounts = BTreeMap::new();
+    apply(
+        &genesis(vesting(&wallet, "100", 0, 10, 50), 0),
+        &mut accounts,
+    )
+    .unwrap();
+
+    let result = apply(&transfer(&wallet, "1", 1, 9), &mut accounts);
+    assert_eq!(result, Err(String::from("Not enough balance")));
+
+    let account = accounts.get(&wallet.address_str()).unwrap();
+    assert_eq!(account.unlocked(25), Some(amount("50")));
+    assert_eq!(account.vesting_locked(25), Some(amount("50")));
+    let result = apply(&transfer(&wallet, "51", 1, 25), &mut accounts);
+    assert_eq!(result, Err(String::from("Not enough balance")));
+    apply(&transfer(&wallet, "30", 1, 25), &mut accounts).unwrap();
+    let account = accounts.get(&wallet.address_str()).unwrap();
+    assert_eq!(account.balance, amount("20"));
+    assert_eq!(account.unreleased_vesting(), Some(amount("50")));
+
+    apply(&transfer(&wallet, "70", 2, 50), &mut accounts).unwrap();
+    let account = accounts.get(&wallet.address_str()).unwrap();
+    assert_eq!(account.balance, Amount::zero());
+    assert!(account.vesting.is_empty());
+}
+
+#[test]
+fn vesting_released_at_inclusion_height() {
+    let wallet = Wallet::new();
+    let mut accounts = BTreeMap::new();
+    apply(
+        &genesis(vesting(&wallet, "100", 0, 10, 50), 0),
+        &mut accounts,
+    )
+    .unwrap();
+
+    let result = apply_at(&transfer(&wallet, "100", 1, 50), 9, &mut accounts);
+    assert_eq!(result, Err(String::from("Not enough balance")));
+    let account = accounts.get(&wallet.address_str()).unwrap();
+    assert_eq!(account.unreleased_vesting(), Some(amount("100")));
+}
+
+#[test]
+fn vesting_tx_locks_sender_funds() {
+    let sender = Wallet::new();
+    let recipient = Wallet::new();
+    let mut accounts = BTreeMap::new();
+    let mut account = Account::new(sender.address_str());
+    account.debit(amount("100")).unwrap();
+    accounts.insert(sender.address_str(), account);
+
+    let tx = |payload, fee: &str| {
+        let data =
+            TxData::with_payload(&sender, String::default(), payload, fee.to_string(), 1).unwrap();
+        Tx::from_tx(data, String::default(), 5)
+    };
+    let result = apply(&tx(vesting(&recipient, "10", 5, 4, 20), "1"), &mut accounts);
+    assert_eq!(
+        result,
+        Err(String::from("Vesting must satisfy start <= cliff <= end"))
+    );
+    let result = apply(
+        &tx(vesting(&recipient, "100", 5, 10, 20), "1"),
+        &mut accounts,
+    );
+    assert_eq!(result, Err(String::from("Not enough balance")));
+
+    apply(
+        &tx(vesting(&recipient, "60", 5, 10, 20), "1"),
+        &mut accounts,
+    )
+    .unwrap();
+    assert_eq!(
+        accounts.get(&sender.address_str()).unwrap().balance,
+        amount("39")
+    );
+    let account = accounts.get(&recipient.address_str()).unwrap();
+    assert_eq!(account.balance, Amount::zero());
+    assert_eq!(account.unlocked(9), Some(Amount::zero()));
+    assert_eq!(account.unlocked(20), Some(amount("60")));
+}
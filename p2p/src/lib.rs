@@ -1,2 +1,3 @@
 pub mod network;
-pub mod address;
\ No newline at end of file
+pub mod address;
+pub mod codec;
@@ -1,6 +1,9 @@
+use crate::codec;
 use account::account::Account;
 use block::block::Block;
-use common::bigdecimal::BigDecimal;
+use block::vote::Vote;
+use common::amount::Amount;
+use common::codec::{decode, encode};
 use futures::{
     StreamExt,
     channel::{mpsc, oneshot},
@@ -24,17 +27,22 @@ use std::sync::Arc;
 use std::time::Duration;
 use storage::storage::Storage;
 use tokio::sync::mpsc::Receiver;
+use tokio::task::spawn;
+use tx::evidence::Evidence;
 use tx::tx::Tx;
 use tx::tx_data::TxData;
+use wallet::wallet::Wallet;
+
+const MAX_REORG_DEPTH: u64 = 100;
 
 #[derive(NetworkBehaviour)]
 pub struct P2pBehaviour {
     kademlia: kad::Behaviour<MemoryStore>,
     gossipsub: gossipsub::Behaviour,
-    get_nonce: request_response::json::Behaviour<NonceRequest, AccountResponse>,
-    find_block: request_response::json::Behaviour<BlockRequest, BlockResponse>,
-    add_tx: request_response::json::Behaviour<TxData, TxResponse>,
-    get_fee: request_response::json::Behaviour<FeeRequest, FeeResponse>,
+    get_nonce: codec::Behaviour<NonceRequest, AccountResponse>,
+    find_block: codec::Behaviour<BlockRequest, BlockResponse>,
+    add_tx: codec::Behaviour<TxData, TxResponse>,
+    get_fee: codec::Behaviour<FeeRequest, FeeResponse>,
 }
 
 #[derive(Debug)]
@@ -49,6 +57,9 @@ enum Command {
         sender: oneshot::Sender<Result<(), Box<dyn Error + Send>>>,
     },
     Subscribe,
+    PublishTx {
+        data: TxData,
+    },
     StartProviding {
         wallet: String,
         sender: oneshot::Sender<()>,
@@ -84,6 +95,7 @@ pub async fn new(
     state: &Arc<State>,
     block_rx: Receiver<Block>,
 ) -> Result<(Client, EventLoop), Box<dyn Error>> {
+    let wallet = Wallet::from_secret(secret)?;
     let secret = libp2p::identity::secp256k1::SecretKey::try_from_bytes(secret).unwrap();
     let keypair = libp2p::identity::secp256k1::Keypair::from(secret);
     let keypair = libp2p::identity::Keypair::from(keypair);
@@ -110,27 +122,27 @@ pub async fn new(
             .expect("Cannot build gossipsub");
             P2pBehaviour {
                 kademlia: kad::Behaviour::new(peer_id, MemoryStore::new(key.public().to_peer_id())),
-                get_nonce: request_response::json::Behaviour::new(
+                get_nonce: codec::Behaviour::new(
                     [(
-                        StreamProtocol::new("/get-nonce/0.0.1"),
+                        StreamProtocol::new("/get-nonce/0.0.2"),
                         ProtocolSupport::Full,
                     )],
                     request_response::Config::default(),
                 ),
-                find_block: request_response::json::Behaviour::new(
+                find_block: codec::Behaviour::new(
                     [(
-                        StreamProtocol::new("/find-block/0.0.1"),
+                        StreamProtocol::new("/find-block/0.0.2"),
                         ProtocolSupport::Full,
                     )],
                     request_response::Config::default(),
                 ),
                 gossipsub,
-                add_tx: request_response::json::Behaviour::new(
-                    [(StreamProtocol::new("/add-tx/0.0.1"), ProtocolSupport::Full)],
+                add_tx: codec::Behaviour::new(
+                    [(StreamProtocol::new("/add-tx/0.0.2"), ProtocolSupport::Full)],
                     request_response::Config::default(),
                 ),
-                get_fee: request_response::json::Behaviour::new(
-                    [(StreamProtocol::new("/get-fee/0.0.1"), ProtocolSupport::Full)],
+                get_fee: codec::Behaviour::new(
+                    [(StreamProtocol::new("/get-fee/0.0.2"), ProtocolSupport::Full)],
                     request_response::Config::default(),
                 ),
             }
@@ -144,12 +156,21 @@ pub async fn new(
         .set_mode(Some(kad::Mode::Server));
 
     let (command_sender, command_receiver) = mpsc::channel(0);
+    let client = Client {
+        sender: command_sender,
+    };
 
     Ok((
-        Client {
-            sender: command_sender,
-        },
-        EventLoop::new(swarm, command_receiver, storage, state, block_rx),
+        client.clone(),
+        EventLoop::new(
+            swarm,
+            command_receiver,
+            client,
+            wallet,
+            storage,
+            state,
+            block_rx,
+        ),
     ))
 }
 
@@ -164,6 +185,10 @@ pub struct EventLoop {
     pending_get_fee: HashMap<OutboundRequestId, oneshot::Sender<FeeResponse>>,
     pending_find_block: HashMap<OutboundRequestId, oneshot::Sender<Option<Block>>>,
     new_block_topic: IdentTopic,
+    new_tx_topic: IdentTopic,
+    new_vote_topic: IdentTopic,
+    client: Client,
+    wallet: Wallet,
     storage: Arc<Storage>,
     state: Arc<State>,
     block_rx: Receiver<Block>,
@@ -173,6 +198,8 @@ impl EventLoop {
     fn new(
         swarm: Swarm<P2pBehaviour>,
         command_receiver: mpsc::Receiver<Command>,
+        client: Client,
+        wallet: Wallet,
         storage: &Arc<Storage>,
         state: &Arc<State>,
         block_rx: Receiver<Block>,
@@ -187,9 +214,13 @@ impl EventLoop {
             pending_add_tx: HashMap::new(),
             pending_find_block: HashMap::new(),
             pending_get_fee: HashMap::new(),
+            client,
+            wallet,
             storage: Arc::clone(storage),
             state: Arc::clone(state),
             new_block_topic: IdentTopic::new("new_block"),
+            new_tx_topic: IdentTopic::new("new_tx"),
+            new_vote_topic: IdentTopic::new("new_vote"),
             block_rx,
         }
     }
@@ -212,15 +243,76 @@ impl EventLoop {
 
     async fn handle_block(&mut self, block: Option<Block>) {
         if let Some(block) = block {
-            let block = serde_json::to_vec(&block).unwrap();
+            let data = encode(&block).unwrap();
             if let Err(e) = self
                 .swarm
                 .behaviour_mut()
                 .gossipsub
-                .publish(self.new_block_topic.clone(), block)
+                .publish(self.new_block_topic.clone(), data)
             {
                 error!("Failed to publish block: {}", e);
             }
+            self.vote(&block);
+        }
+    }
+
+    async fn report(&mut self, evidence: Evidence) {
+        let nonce = match self.state.get_account(self.wallet.address_str()).await {
+            Some(account) => account.nonce() + 1,
+            None => return,
+        };
+        let validator = evidence.validator();
+        let chain_id = self.state.chain_id().await;
+        let Ok(data) =
+            TxData::new_evidence(&self.wallet, chain_id, evidence, String::from("0"), nonce)
+        else {
+            return;
+        };
+        if let Err(e) = self.add_tx(data.clone()).await {
+            debug!("Cannot report equivocation of {}: {}", validator, e);
+            return;
+        }
+        let data = encode(&data).unwrap();
+        if let Err(e) = self
+            .swarm
+            .behaviour_mut()
+            .gossipsub
+            .publish(self.new_tx_topic.clone(), data)
+        {
+            debug!("Failed to publish evidence: {}", e);
+        }
+    }
+
+    /// Adds the tx to the mempool and journals it so it survives a restart,
+    /// deleting the journaled txs it dropped from the mempool.
+    async fn add_tx(&mut self, data: TxData) -> Result<Tx, String> {
+        let (result, dropped) = self.state.add_tx_dropping(data.clone()).await;
+        let saved = match &result {
+            Ok(_) => self.storage.save_pending_tx(&data, &dropped),
+            Err(_) => self.storage.remove_pending_txs(&dropped),
+        };
+        let tx = result?;
+        saved.map_err(|e| e.to_string())?;
+        Ok(tx)
+    }
+
+    fn vote(&mut self, block: &Block) {
+        let Ok(vote) = Vote::new(&self.wallet, block) else {
+            error!("Cannot sign vote for block #{}", block.idx);
+            return;
+        };
+        if let Err(e) = self.storage.add_vote(&vote) {
+            debug!("Not voting for block #{}: {}", block.idx, e);
+            return;
+        }
+        let data = encode(&vote).unwrap();
+        if let Err(e) = self
+            .swarm
+            .behaviour_mut()
+            .gossipsub
+            .publish(self.new_vote_topic.clone(), data)
+        {
+            debug!("Failed to publish vote: {}", e);
         }
     }
 
@@ -243,33 +335,33 @@ impl EventLoop {
                     self.swarm.local_peer_id()
                 );
             }
-            SwarmEvent::Behaviour(P2pBehaviourEvent::Kademlia(event)) => match event {
-                kad::Event::OutboundQueryProgressed { id, result, .. } => match result {
-                    kad::QueryResult::StartProviding(_) => {
-                        let sender: oneshot::Sender<()> = self
-                            .pending_start_providing
-                            .remove(&id)
-                            .expect("Completed query to be previously pending");
-                        let _ = sender.send(());
+            SwarmEvent::Behaviour(P2pBehaviourEvent::Kademlia(
+                kad::Event::OutboundQueryProgressed { id, result, .. },
+            )) => match result {
+                kad::QueryResult::StartProviding(_) => {
+                    let sender: oneshot::Sender<()> = self
+                        .pending_start_providing
+                        .remove(&id)
+                        .expect("Completed query to be previously pending");
+                    let _ = sender.send(());
+                }
+                kad::QueryResult::GetProviders(Ok(GetProvidersOk::FoundProviders {
+                    providers,
+                    ..
+                })) => {
+                    if let Some(sender) = self.pending_get_providers.remove(&id) {
+                        sender.send(providers).expect("Receiver not to be dropped");
+                        self.swarm
+                            .behaviour_mut()
+                            .kademlia
+                            .query_mut(&id)
+                            .unwrap()
+                            .finish();
                     }
-                    kad::QueryResult::GetProviders(Ok(result)) => match result {
-                        GetProvidersOk::FoundProviders { providers, .. } => {
-                            if let Some(sender) = self.pending_get_providers.remove(&id) {
-                                sender.send(providers).expect("Receiver not to be dropped");
-                                self.swarm
-                                    .behaviour_mut()
-                                    .kademlia
-                                    .query_mut(&id)
-                                    .unwrap()
-                                    .finish();
-                            }
-                        }
-                        _ => {}
-                    },
-                    _ => {}
-                },
+                }
                 _ => {}
             },
+            SwarmEvent::Behaviour(P2pBehaviourEvent::Kademlia(_)) => {}
             SwarmEvent::Behaviour(P2pBehaviourEvent::GetNonce(
                 request_response::Event::Message { message, .. },
             )) => match message {
@@ -335,7 +427,7 @@ impl EventLoop {
                 request_response::Message::Request {
                     request, channel, ..
                 } => {
-                    let response = match self.state.add_tx(request).await {
+                    let response = match self.add_tx(request).await {
                         Ok(tx) => TxResponse {
                             data: Some(tx),
                             error: None,
@@ -399,11 +491,67 @@ impl EventLoop {
                 }
             },
             SwarmEvent::Behaviour(P2pBehaviourEvent::Gossipsub(event)) => match event {
-                gossipsub::Event::Message { message, .. } => {
+                gossipsub::Event::Message { message, .. }
+                    if message.topic == self.new_tx_topic.hash() =>
+                {
+                    debug!("New tx {:?}", message);
+                    if let Ok(data) = decode::<TxData>(&message.data) {
+                        if let Err(e) = self.add_tx(data).await {
+                            debug!("Skipping gossiped tx: {}", e);
+                        }
+                    } else {
+                        error!("Got invalid tx!");
+                    }
+                }
+                gossipsub::Event::Message { message, .. }
+                    if message.topic == self.new_vote_topic.hash() =>
+                {
+                    if let Ok(vote) = decode::<Vote>(&message.data) {
+                        if let Err(e) = self.storage.add_vote(&vote) {
+                            debug!("Skipping gossiped vote: {}", e);
+                        }
+                    } else {
+                        error!("Got invalid vote!");
+                    }
+                }
+                gossipsub::Event::Message {
+                    propagation_source,
+                    message,
+                    ..
+                } => {
                     debug!("New block {:?}", message);
-                    if let Ok(block) = serde_json::from_slice::<Block>(&message.data) {
-                        if let Err(e) = self.storage.add_block(&block) {
+                    if let Ok(block) = decode::<Block>(&message.data) {
+                        let added = self.storage.add_block(&block).map_err(|e| e.to_string());
+                        if let Err(e) = added {
                             error!("Failed to add block: {}", e);
+                            let evidence = self.storage.find_equivocation(&block).unwrap_or(None);
+                            if let Some(evidence) = evidence {
+                                self.report(evidence).await;
+                            }
+                            if self.storage.needs_sync(&block).unwrap_or(false) {
+                                let peer = message.source.unwrap_or(propagation_source);
+                                spawn(adopt_chain(
+                                    self.client.clone(),
+                                    Arc::clone(&self.storage),
+                                    Arc::clone(&self.state),
+                                    peer,
+                                    block,
+                                ));
+                            }
+                        } else {
+                            let dropped = self
+                                .state
+                                .update(
+                                    block.hash_str(),
+                                    block.idx() + 1,
+                                    self.storage.find_latest_event_hash(),
+                                    self.storage.accounts(),
+                                )
+                                .await;
+                            if let Err(e) = self.storage.remove_pending_txs(&dropped) {
+                                error!("Cannot remove dropped pending txs: {}", e);
+                            }
+                            self.vote(&block);
                         }
                     } else {
                         error!("Got invalid block!");
@@ -417,10 +565,10 @@ impl EventLoop {
             SwarmEvent::ConnectionEstablished {
                 peer_id, endpoint, ..
             } => {
-                if endpoint.is_dialer() {
-                    if let Some(sender) = self.pending_dial.remove(&peer_id) {
-                        let _ = sender.send(Ok(()));
-                    }
+                if endpoint.is_dialer()
+                    && let Some(sender) = self.pending_dial.remove(&peer_id)
+                {
+                    let _ = sender.send(Ok(()));
                 }
             }
             SwarmEvent::Dialing {
@@ -431,10 +579,10 @@ impl EventLoop {
             }
             SwarmEvent::OutgoingConnectionError { peer_id, error, .. } => {
                 error!("Dialing error");
-                if let Some(peer_id) = peer_id {
-                    if let Some(sender) = self.pending_dial.remove(&peer_id) {
-                        let _ = sender.send(Err(Box::new(error)));
-                    }
+                if let Some(peer_id) = peer_id
+                    && let Some(sender) = self.pending_dial.remove(&peer_id)
+                {
+                    let _ = sender.send(Err(Box::new(error)));
                 }
             }
             e => {
@@ -524,6 +672,33 @@ impl EventLoop {
                 {
                     error!("Failed to subscribe to new blocks: {:?}", e);
                 }
+                if let Err(e) = self
+                    .swarm
+                    .behaviour_mut()
+                    .gossipsub
+                    .subscribe(&self.new_tx_topic)
+                {
+                    error!("Failed to subscribe to new txs: {:?}", e);
+                }
+                if let Err(e) = self
+                    .swarm
+                    .behaviour_mut()
+                    .gossipsub
+                    .subscribe(&self.new_vote_topic)
+                {
+                    error!("Failed to subscribe to new votes: {:?}", e);
+                }
+            }
+            Command::PublishTx { data } => {
+                let data = encode(&data).unwrap();
+                if let Err(e) = self
+                    .swarm
+                    .behaviour_mut()
+                    .gossipsub
+                    .publish(self.new_tx_topic.clone(), data)
+                {
+                    debug!("Failed to publish tx: {}", e);
+                }
             }
             Command::GetFee { peer, sender } => {
                 let request_id = self
@@ -537,6 +712,66 @@ impl EventLoop {
     }
 }
 
+async fn adopt_chain(
+    mut client: Client,
+    storage: Arc<Storage>,
+    state: Arc<State>,
+    peer: PeerId,
+    head: Block,
+) {
+    let Some(latest) = storage.find_latest_block() else {
+        return;
+    };
+    if head.idx <= latest.idx {
+        debug!(
+            "Competing block #{} is not longer than local chain",
+            head.idx
+        );
+        return;
+    }
+    let mut blocks = vec![head];
+    loop {
+        let first = &blocks[0];
+        if first.idx == 0 || latest.idx.saturating_sub(first.idx) >= MAX_REORG_DEPTH {
+            error!("Cannot find common ancestor with peer {}", peer);
+            return;
+        }
+        let parent_idx = first.idx - 1;
+        let known = match storage.find_block_by_idx(parent_idx) {
+            Ok(Some(block)) => block.hash_str() == first.parent_hash,
+            _ => false,
+        };
+        if known {
+            break;
+        }
+        match client.find_block(parent_idx, peer).await {
+            Some(block) => blocks.insert(0, block),
+            None => {
+                error!("Peer {} doesn't have block #{}", peer, parent_idx);
+                return;
+            }
+        }
+    }
+    let ancestor = blocks[0].idx - 1;
+    if let Err(e) = storage.switch_chain(ancestor, &blocks) {
+        error!("Failed to switch chain: {}", e);
+        return;
+    }
+    if let Some(latest) = storage.find_latest_block() {
+        let dropped = state
+            .update(
+                latest.hash_str(),
+                latest.idx() + 1,
+                storage.find_latest_event_hash(),
+                storage.accounts(),
+            )
+            .await;
+        if let Err(e) = storage.remove_pending_txs(&dropped) {
+            error!("Cannot remove dropped pending txs: {}", e);
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Client {
     sender: mpsc::Sender<Command>,
@@ -625,6 +860,13 @@ impl Client {
             .expect("Command receiver not to be dropped.");
     }
 
+    pub async fn publish_tx(&mut self, data: TxData) {
+        self.sender
+            .send(Command::PublishTx { data })
+            .await
+            .expect("Command receiver not to be dropped.");
+    }
+
     pub async fn get_fee(&mut self, peer: PeerId) -> FeeResponse {
         let (sender, receiver) = oneshot::channel();
         self.sender
@@ -666,5 +908,5 @@ pub struct FeeRequest {}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FeeResponse {
-    pub fee: BigDecimal,
+    pub fee: Amount,
 }
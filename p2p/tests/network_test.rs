@@ -1,5 +1,6 @@
 use block::block::Block;
 use libp2p::{Multiaddr, PeerId};
+use operation::params::Params;
 use p2p::network;
 use state::state::State;
 use std::sync::Arc;
@@ -14,11 +15,11 @@ pub async fn p2p_test() {
 
     let temp_storage_dir1 = tempdir().unwrap();
     let storage1 = Storage::new(temp_storage_dir1.path());
-    let state1 = State::new(wallet.clone());
+    let state1 = State::new(wallet.clone(), Params::default());
     let storage1 = Arc::new(storage1);
     let state1 = Arc::new(state1);
 
-    let genesis = Block::new(&wallet, 0, String::from("0"), vec![]).unwrap();
+    let genesis = Block::new(&wallet, String::default(), 0, String::from("0"), 0, vec![]).unwrap();
     storage1.add_block(&genesis).unwrap();
     let (_, rx) = tokio::sync::mpsc::channel(10);
 
@@ -36,7 +37,7 @@ pub async fn p2p_test() {
 
     let temp_storage_dir2 = tempdir().unwrap();
     let storage2 = Storage::new(temp_storage_dir2.path());
-    let state2 = State::new(wallet.clone());
+    let state2 = State::new(wallet.clone(), Params::default());
     let storage2 = Arc::new(storage2);
     let state2 = Arc::new(state2);
 
@@ -79,7 +80,7 @@ async fn then_dial_error() {
 
     let temp_storage_dir = tempdir().unwrap();
     let storage = Storage::new(temp_storage_dir.path());
-    let state = State::new(wallet.clone());
+    let state = State::new(wallet.clone(), Params::default());
     let storage = Arc::new(storage);
     let state = Arc::new(state);
 
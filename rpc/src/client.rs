@@ -1,9 +1,12 @@
-use crate::schema::ErrorResponse;
+use crate::schema::{BlockInfo, ErrorResponse};
 use account::account::Account;
-use block::block::Block;
-use common::bigdecimal::BigDecimal;
+use account::asset::Asset;
+use common::amount::Amount;
 use httpclient::{Client, InMemoryBody, ResponseExt};
 use p2p::network::FeeResponse;
+use std::collections::BTreeMap;
+use storage::config_storage::ChainConfig;
+use storage::inflation::Supply;
 use tx::tx_data::TxData;
 
 pub struct RpcClient {
@@ -16,7 +19,7 @@ impl RpcClient {
         Self { client }
     }
 
-    pub async fn find_block(&self, idx: u64) -> Option<Block> {
+    pub async fn find_block(&self, idx: u64) -> Option<BlockInfo> {
         if let Ok(response) = self.client.get(format!("/api/blocks/{}", idx)).send().await {
             if response.status().as_u16() == 200 {
                 let body = response.text().await;
@@ -49,7 +52,7 @@ impl RpcClient {
         }
     }
 
-    pub async fn get_current_fee(&self) -> Result<BigDecimal, String> {
+    pub async fn get_current_fee(&self) -> Result<Amount, String> {
         if let Ok(response) = self.client.get("/api/fee").send().await {
             if response.status().as_u16() == 200 {
                 let body = response.text().await;
@@ -60,6 +63,54 @@ impl RpcClient {
         Err(String::from("Failed to get current fee with RPC"))
     }
 
+    pub async fn get_supply(&self) -> Option<Supply> {
+        if let Ok(response) = self.client.get("/api/supply").send().await
+            && response.status().as_u16() == 200
+        {
+            let body = response.text().await;
+            let body: Supply = serde_json::from_str(&body.unwrap()).unwrap();
+            return Some(body);
+        }
+        None
+    }
+
+    pub async fn get_chain_config(&self) -> Option<ChainConfig> {
+        if let Ok(response) = self.client.get("/api/chain").send().await
+            && response.status().as_u16() == 200
+        {
+            let body = response.text().await;
+            let body: ChainConfig = serde_json::from_str(&body.unwrap()).unwrap();
+            return Some(body);
+        }
+        None
+    }
+
+    pub async fn find_asset(&self, id: &str) -> Option<Asset> {
+        if let Ok(response) = self.client.get(format!("/api/assets/{}", id)).send().await
+            && response.status().as_u16() == 200
+        {
+            let body = response.text().await;
+            let body: Asset = serde_json::from_str(&body.unwrap()).unwrap();
+            return Some(body);
+        }
+        None
+    }
+
+    pub async fn get_asset_holders(&self, id: &str) -> Option<BTreeMap<String, Amount>> {
+        if let Ok(response) = self
+            .client
+            .get(format!("/api/assets/{}/holders", id))
+            .send()
+            .await
+            && response.status().as_u16() == 200
+        {
+            let body = response.text().await;
+            let body: BTreeMap<String, Amount> = serde_json::from_str(&body.unwrap()).unwrap();
+            return Some(body);
+        }
+        None
+    }
+
     pub async fn add_tx(&self, tx: TxData) -> Option<String> {
         if let Ok(response) = self
             .client
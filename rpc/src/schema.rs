@@ -1,6 +1,26 @@
+use account::account::Account;
+use block::block::Block;
+use common::amount::Amount;
 use serde::{Deserialize, Serialize};
+use storage::finality_storage::Finality;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockInfo {
+    #[serde(flatten)]
+    pub block: Block,
+    #[serde(flatten)]
+    pub finality: Finality,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountInfo {
+    #[serde(flatten)]
+    pub account: Account,
+    pub unlocked: Amount,
+    pub locked: Amount,
+}
@@ -1,15 +1,18 @@
-use crate::schema::ErrorResponse;
-use account::account::Account;
+use crate::schema::{AccountInfo, BlockInfo, ErrorResponse};
+use account::asset::Asset;
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
-use block::block::Block;
-use libp2p::PeerId;
+use common::amount::Amount;
 use p2p::network::{Client, FeeResponse};
 use serde::Serialize;
+use state::pool::PoolMetrics;
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use storage::config_storage::ChainConfig;
+use storage::inflation::Supply;
 use storage::storage::Storage;
 use tx::tx::Tx;
 use tx::tx_data::TxData;
@@ -25,19 +28,9 @@ impl P2pClientHolder {
         }
     }
 
-    async fn get_account(&self, wallet: String, peer_id: PeerId) -> Option<Account> {
+    async fn publish_tx(&self, data: TxData) {
         let mut client = self.client.lock().await;
-        client.get_account(wallet, peer_id).await
-    }
-
-    async fn add_tx(&self, data: TxData, peer_id: PeerId) -> Result<Tx, String> {
-        let mut client = self.client.lock().await;
-        client.add_tx(data, peer_id).await
-    }
-
-    async fn get_fee(&self, peer_id: PeerId) -> FeeResponse {
-        let mut client = self.client.lock().await;
-        client.get_fee(peer_id).await
+        client.publish_tx(data).await
     }
 }
 
@@ -63,105 +56,86 @@ impl IntoResponse for AppError {
 
 #[derive(Clone)]
 struct AppState {
-    wallet: String,
     storage: Arc<Storage>,
     state: Arc<state::state::State>,
     client: Arc<P2pClientHolder>,
 }
 
 impl AppState {
-    fn new(
-        wallet: String,
-        storage: &Arc<Storage>,
-        state: &Arc<state::state::State>,
-        client: Client,
-    ) -> Self {
+    fn new(storage: &Arc<Storage>, state: &Arc<state::state::State>, client: Client) -> Self {
         Self {
-            wallet,
             storage: Arc::clone(storage),
             state: Arc::clone(state),
             client: Arc::new(P2pClientHolder::new(client)),
         }
     }
 
-    async fn find_block(&self, idx: u64) -> Option<Block> {
-        self.storage.find_block_by_idx(idx).unwrap_or(None)
+    async fn find_block(&self, idx: u64) -> Option<BlockInfo> {
+        let block = self.storage.find_block_by_idx(idx).unwrap_or(None)?;
+        let finality = self.storage.finality(idx).unwrap_or_default();
+        Some(BlockInfo { block, finality })
     }
 
-    async fn get_account(&self, wallet: String) -> Option<Account> {
-        let current_validator = self.get_current_validator();
-        if current_validator == self.wallet {
-            self.state.get_account(wallet).await
-        } else {
-            if let Some(peer_id) = self.address_to_peer_id(current_validator) {
-                self.client.get_account(wallet, peer_id).await
-            } else {
-                None
-            }
-        }
+    async fn get_account(&self, wallet: String) -> Option<AccountInfo> {
+        let account = self.state.get_account(wallet).await?;
+        let height = self.state.current_block().await;
+        Some(AccountInfo {
+            unlocked: account.unlocked(height)?,
+            locked: account.vesting_locked(height)?,
+            account,
+        })
     }
 
-    async fn add_tx(&self, tx: TxData) -> Result<Tx, String> {
-        let current_validator = self.get_current_validator();
-        if current_validator == self.wallet {
-            self.state.add_tx(tx).await
-        } else {
-            if let Some(peer_id) = self.address_to_peer_id(current_validator) {
-                self.client.add_tx(tx, peer_id).await
-            } else {
-                Err(String::from("Internal server error"))
-            }
-        }
+    async fn add_tx(&self, data: TxData) -> Result<Tx, String> {
+        let (result, dropped) = self.state.add_tx_dropping(data.clone()).await;
+        let saved = match &result {
+            Ok(_) => self.storage.save_pending_tx(&data, &dropped),
+            Err(_) => self.storage.remove_pending_txs(&dropped),
+        };
+        let tx = result?;
+        saved.map_err(|e| e.to_string())?;
+        self.client.publish_tx(data).await;
+        Ok(tx)
     }
 
     async fn get_fee(&self) -> Result<FeeResponse, String> {
-        let current_validator = self.get_current_validator();
-        if current_validator == self.wallet {
-            Ok(FeeResponse {
-                fee: self.state.current_fee().await,
-            })
-        } else {
-            if let Some(peer_id) = self.address_to_peer_id(current_validator) {
-                Ok(self.client.get_fee(peer_id).await)
-            } else {
-                Err(String::from("Internal server error"))
-            }
-        }
+        Ok(FeeResponse {
+            fee: self.state.current_fee().await,
+        })
+    }
+
+    async fn get_mempool(&self) -> PoolMetrics {
+        self.state.metrics().await
+    }
+
+    async fn get_supply(&self) -> Result<Supply, String> {
+        self.storage.supply().map_err(|e| e.to_string())
+    }
+
+    async fn get_chain(&self) -> Result<ChainConfig, String> {
+        self.storage.chain_config().map_err(|e| e.to_string())
     }
 
     async fn get_wallet_txs(&self, wallet: String) -> Vec<Tx> {
         self.storage.find_wallet_txs(wallet)
     }
 
-    fn address_to_peer_id(&self, address: String) -> Option<PeerId> {
-        let Ok(public) = bs58::decode(address).into_vec() else {
-            return None;
-        };
-        let Ok(public) = libp2p::identity::secp256k1::PublicKey::try_from_bytes(public.as_slice())
-        else {
-            return None;
-        };
-        let public = libp2p::identity::PublicKey::from(public);
-        Some(PeerId::from_public_key(&public))
+    async fn find_asset(&self, id: String) -> Result<Option<Asset>, String> {
+        self.storage.find_asset(&id).map_err(|e| e.to_string())
     }
 
-    fn get_current_validator(&self) -> String {
-        if let Ok(address) = self.storage.current_validator() {
-            address
-        } else {
-            self.wallet.clone()
-        }
+    async fn get_asset_holders(&self, id: String) -> Result<BTreeMap<String, Amount>, String> {
+        self.storage.asset_holders(&id).map_err(|e| e.to_string())
     }
 }
 
 pub async fn run(
     port: i32,
-    wallet: String,
     storage: &Arc<Storage>,
     state: &Arc<state::state::State>,
     client: Client,
 ) {
-    let state = AppState::new(wallet, storage, state, client);
+    let state = AppState::new(storage, state, client);
     let state = Arc::new(state);
 
     let app = Router::new()
@@ -170,6 +144,11 @@ pub async fn run(
         .route("/api/wallets/{wallet}/txs", get(get_wallet_txs))
         .route("/api/txs", post(add_tx))
         .route("/api/fee", get(get_fee))
+        .route("/api/mempool", get(get_mempool))
+        .route("/api/supply", get(get_supply))
+        .route("/api/chain", get(get_chain))
+        .route("/api/assets/{id}", get(find_asset))
+        .route("/api/assets/{id}/holders", get(get_asset_holders))
         .with_state(state);
 
     println!("Listening http RPC on port: {}", port);
@@ -183,7 +162,7 @@ pub async fn run(
 async fn find_block_by_idx(
     Path(idx): Path<u64>,
     state: State<Arc<AppState>>,
-) -> Result<Json<Block>, AppError> {
+) -> Result<Json<BlockInfo>, AppError> {
     if let Some(block) = state.find_block(idx).await {
         Ok(Json::from(block))
     } else {
@@ -195,7 +174,7 @@ async fn find_block_by_idx(
 async fn get_wallet(
     Path(wallet): Path<String>,
     state: State<Arc<AppState>>,
-) -> Result<Json<Account>, AppError> {
+) -> Result<Json<AccountInfo>, AppError> {
     match state.get_account(wallet.clone()).await {
         Some(account) => Ok(Json(account)),
         None => Err(AppError::NotFound(format!("Account #{} not found", wallet))),
@@ -228,3 +207,47 @@ async fn get_fee(state: State<Arc<AppState>>) -> Result<Json<FeeResponse>, AppEr
         Err(err) => Err(AppError::BadRequest(err)),
     }
 }
+
+#[axum::debug_handler]
+async fn get_mempool(state: State<Arc<AppState>>) -> Json<PoolMetrics> {
+    Json(state.get_mempool().await)
+}
+
+#[axum::debug_handler]
+async fn get_supply(state: State<Arc<AppState>>) -> Result<Json<Supply>, AppError> {
+    match state.get_supply().await {
+        Ok(supply) => Ok(Json(supply)),
+        Err(err) => Err(AppError::BadRequest(err)),
+    }
+}
+
+#[axum::debug_handler]
+async fn get_chain(state: State<Arc<AppState>>) -> Result<Json<ChainConfig>, AppError> {
+    match state.get_chain().await {
+        Ok(config) => Ok(Json(config)),
+        Err(err) => Err(AppError::BadRequest(err)),
+    }
+}
+
+#[axum::debug_handler]
+async fn find_asset(
+    Path(id): Path<String>,
+    state: State<Arc<AppState>>,
+) -> Result<Json<Asset>, AppError> {
+    match state.find_asset(id.clone()).await {
+        Ok(Some(asset)) => Ok(Json(asset)),
+        Ok(None) => Err(AppError::NotFound(format!("Asset {} not found", id))),
+        Err(err) => Err(AppError::BadRequest(err)),
+    }
+}
+
+#[axum::debug_handler]
+async fn get_asset_holders(
+    Path(id): Path<String>,
+    state: State<Arc<AppState>>,
+) -> Result<Json<BTreeMap<String, Amount>>, AppError> {
+    match state.get_asset_holders(id).await {
+        Ok(holders) => Ok(Json(holders)),
+        Err(err) => Err(AppError::BadRequest(err)),
+    }
+}
@@ -1,2 +1,3 @@
+pub mod pool;
 mod mem_pool;
 pub mod state;
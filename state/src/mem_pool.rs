@@ -1,14 +1,25 @@
+use crate::pool::{PoolLimits, PoolMetrics};
 use account::account::Account;
-use block::block::Block;
-use common::bigdecimal::BigDecimal;
+use block::block::{Block, MAX_BLOCK_TXS};
+use common::amount::Amount;
+use common::biginteger::BigInt;
+use common::codec::encode;
 use log::debug;
+use operation::params::Params;
 use operation::tx::process_tx;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
+use tx::evidence::Evidence;
+use tx::payload::Payload;
 use tx::tx::Tx;
 use tx::tx_data::TxData;
 use wallet::wallet::Wallet;
 
-static INITIAL_FEE: &str = "0.00000000001";
+/// Fee rate per token moved with an empty pool: 0.00000000001 in base units.
+const INITIAL_FEE: u128 = 10_000_000;
+/// Percent a replacement must add to the fee of the pending transaction.
+const REPLACEMENT_FEE_BUMP: u128 = 10;
+/// Evidence pays no fee, so only this many reports wait in the pool at once.
+const MAX_PENDING_EVIDENCE: usize = 16;
 
 #[derive(Debug)]
 pub struct MemPool {
@@ -16,19 +27,45 @@ pub struct MemPool {
     current_block: u64,
     prev_block_hash: String,
     last_event: String,
+    /// Hash of the last transaction returned by `add_tx`, chained in order of
+    /// arrival until a block fixes the order.
+    pending_event: String,
+    committed: BTreeMap<String, Account>,
     accounts: BTreeMap<String, Account>,
-    pending_txs: Vec<Tx>,
+    /// Pooled transactions of each sender by nonce, including future ones
+    /// waiting for the nonces before them.
+    queues: BTreeMap<String, BTreeMap<u64, TxData>>,
+    /// Every pooled transaction as `(fee rate, sender, nonce)`.
+    by_fee_rate: BTreeSet<(Amount, String, u64)>,
+    /// Executable transactions in the order they were applied to `accounts`.
+    executable: Vec<(String, u64)>,
+    /// Encoded size of the pooled transactions.
+    bytes: usize,
+    /// Keys of transactions removed from the pool since `take_dropped`.
+    dropped: BTreeSet<(String, u64)>,
+    limits: PoolLimits,
+    metrics: PoolMetrics,
+    params: Params,
 }
 
 impl MemPool {
-    pub fn new(wallet: Wallet) -> Self {
+    pub fn new(wallet: Wallet, params: Params, limits: PoolLimits) -> Self {
         Self {
             wallet,
             current_block: 0,
             prev_block_hash: String::default(),
             last_event: String::default(),
+            pending_event: String::default(),
+            committed: BTreeMap::new(),
             accounts: BTreeMap::new(),
-            pending_txs: Vec::new(),
+            queues: BTreeMap::new(),
+            by_fee_rate: BTreeSet::new(),
+            executable: Vec::new(),
+            bytes: 0,
+            dropped: BTreeSet::new(),
+            limits,
+            metrics: PoolMetrics::default(),
+            params,
         }
     }
 
@@ -38,62 +75,487 @@ impl MemPool {
         current_block: u64,
         last_event: String,
         accounts: BTreeMap<String, Account>,
-    ) {
+    ) -> Vec<(String, u64)> {
         self.prev_block_hash = prev_block_hash;
         self.current_block = current_block;
+        self.pending_event = last_event.clone();
         self.last_event = last_event;
-        self.accounts = accounts;
+        self.committed = accounts;
+        self.rebuild();
+        self.take_dropped()
+    }
+
+    /// Returns the keys of the transactions evicted, expired or dropped from
+    /// the pool since the last call, so they can be deleted from the journal.
+    pub fn take_dropped(&mut self) -> Vec<(String, u64)> {
+        std::mem::take(&mut self.dropped)
+            .into_iter()
+            .filter(|(sender, nonce)| self.pending(sender, *nonce).is_none())
+            .collect()
+    }
+
+    /// Applies the pooled transactions again on the committed accounts, in
+    /// the order they were applied before, and returns why any were dropped.
+    fn rebuild(&mut self) -> BTreeMap<(String, u64), String> {
+        let pending_txs = self.pending_txs();
+        self.accounts = self.committed.clone();
+        self.by_fee_rate.clear();
+        self.bytes = 0;
+        self.queues.clear();
+        self.executable.clear();
+        let mut dropped = BTreeMap::new();
+        for data in pending_txs {
+            let key = (data.from(), data.nonce);
+            if let Err(err) = self.insert(data) {
+                debug!("Dropping pending tx: {}", err);
+                self.dropped.insert(key.clone());
+                dropped.insert(key, err);
+            }
+        }
+        dropped
     }
 
     pub fn add_tx(&mut self, tx_data: TxData) -> Result<Tx, String> {
+        let result = self.admit(tx_data);
+        match result {
+            Ok(_) => self.metrics.accepted += 1,
+            Err(_) => self.metrics.rejected += 1,
+        }
+        result
+    }
+
+    fn admit(&mut self, tx_data: TxData) -> Result<Tx, String> {
         if !tx_data.valid() {
             return Err("Invalid transaction".to_string());
         }
-        if tx_data.fee_amount() < self.current_fee() {
+        if tx_data.chain_id != self.params.chain_id {
+            return Err(String::from("Invalid chain id"));
+        }
+        if let Payload::Evidence { evidence } = &tx_data.payload {
+            self.check_evidence(&tx_data, evidence)?;
+        } else if tx_data
+            .required_fee(self.current_fee())
+            .is_none_or(|required| tx_data.fee < required)
+        {
             return Err(String::from("Fee is to low"));
         }
-        let tx = Tx::from_tx(tx_data, self.last_event.clone(), self.current_block);
-        if let Err(err) = process_tx(self.wallet.address_str(), &tx, &mut self.accounts) {
-            Err(err)
-        } else {
-            self.pending_txs.push(tx.clone());
-            self.last_event = tx.hash_str();
-            Ok(tx)
+        self.check_funds(&tx_data)?;
+        self.check_limits(&tx_data)?;
+        self.insert(tx_data.clone())?;
+        self.evict();
+        if self.pending(&tx_data.from(), tx_data.nonce) != Some(&tx_data) {
+            return Err(String::from("Mempool is full"));
         }
+        let tx = Tx::from_tx(tx_data, self.pending_event.clone(), self.current_block);
+        self.pending_event = tx.hash_str();
+        Ok(tx)
     }
 
-    pub fn get_account(&mut self, wallet: String) -> Option<Account> {
-        if let Some(account) = self.accounts.get(&wallet) {
-            Some(account.clone())
-        } else {
-            None
+    /// Refuses evidence that would not slash anything, and caps the fee-less
+    /// reports waiting in the pool.
+    fn check_evidence(&self, data: &TxData, evidence: &Evidence) -> Result<(), String> {
+        if !evidence.valid() {
+            return Err(String::from("Invalid evidence"));
         }
+        if evidence.first.chain_id != self.params.chain_id
+            || evidence.second.chain_id != self.params.chain_id
+        {
+            return Err(String::from("Evidence from other chain"));
+        }
+        let slashable = self
+            .committed
+            .get(&evidence.validator())
+            .is_some_and(|offender| {
+                !offender.slashed.contains(&evidence.idx())
+                    && (offender.stake > BigInt::zero() || !offender.unbonding.is_empty())
+            });
+        if !slashable {
+            return Err(String::from("Nothing to slash"));
+        }
+        let mut pending = 0;
+        for (_, sender, nonce) in self.by_fee_rate.iter().rev() {
+            let Some(Payload::Evidence { evidence: other }) =
+                self.pending(sender, *nonce).map(|pending| &pending.payload)
+            else {
+                break;
+            };
+            if (sender, *nonce) == (&data.from(), data.nonce) {
+                return Ok(());
+            }
+            if other.validator() == evidence.validator() && other.idx() == evidence.idx() {
+                return Err(String::from("Evidence already pending"));
+            }
+            pending += 1;
+        }
+        if pending >= MAX_PENDING_EVIDENCE {
+            return Err(String::from("Too many pending evidence"));
+        }
+        Ok(())
     }
 
-    pub fn new_block(&mut self, validator: String) -> Option<Block> {
-        if validator != self.wallet.address_str() {
-            debug!("Other validator selected");
-            return None;
+    /// Refuses transactions the sender cannot pay for along with its queued
+    /// future ones, so declared fees are backed and unfunded senders cannot
+    /// fill the pool.
+    fn check_funds(&self, data: &TxData) -> Result<(), String> {
+        let sender = data.from();
+        let Some(mut account) = self.accounts.get(&sender).cloned() else {
+            return Err(String::from("Not enough balance"));
+        };
+        account.release_unbonded(self.current_block)?;
+        account.release_vested(self.current_block)?;
+        let overflow = || String::from("Amount overflow");
+        let next_nonce = self.next_nonce(&sender);
+        let mut available = account.balance;
+        let mut required = cost(data).ok_or_else(overflow)?;
+        for (nonce, pending) in self.queues.get(&sender).into_iter().flatten() {
+            let cost = cost(pending).ok_or_else(overflow)?;
+            if *nonce < next_nonce {
+                if *nonce == data.nonce {
+                    available = available.checked_add(cost).ok_or_else(overflow)?;
+                }
+            } else if *nonce != data.nonce {
+                required = required.checked_add(cost).ok_or_else(overflow)?;
+            }
+        }
+        if required > available {
+            return Err(String::from("Not enough balance"));
+        }
+        Ok(())
+    }
+
+    /// Throttles senders with too many pending transactions, and refuses
+    /// transactions paying no more than the cheapest one of a full pool.
+    fn check_limits(&mut self, data: &TxData) -> Result<(), String> {
+        let sender = data.from();
+        let queue = self.queues.get(&sender);
+        if queue.is_some_and(|queue| queue.contains_key(&data.nonce)) {
+            return Ok(());
+        }
+        if queue.map_or(0, BTreeMap::len) >= self.limits.max_txs_per_sender {
+            self.metrics.throttled += 1;
+            return Err(format!("Too many pending transactions from {}", sender));
+        }
+        let full = self.by_fee_rate.len() >= self.limits.max_txs
+            || self.bytes + size(data) > self.limits.max_bytes;
+        if full
+            && self
+                .by_fee_rate
+                .first()
+                .is_none_or(|(lowest, ..)| fee_rate(data) <= *lowest)
+        {
+            return Err(String::from("Mempool is full"));
+        }
+        Ok(())
+    }
+
+    /// Evicts the transactions with the lowest fee rate, with the later
+    /// nonces of their senders, until the pool fits its limits.
+    fn evict(&mut self) {
+        let mut evicted = Vec::new();
+        while self.by_fee_rate.len() > self.limits.max_txs || self.bytes > self.limits.max_bytes {
+            let Some((_, sender, nonce)) = self.by_fee_rate.first().cloned() else {
+                break;
+            };
+            let nonces: Vec<u64> = self.queues[&sender]
+                .range(nonce..)
+                .map(|(nonce, _)| *nonce)
+                .collect();
+            for nonce in nonces {
+                self.remove(&sender, nonce);
+                evicted.push((sender.clone(), nonce));
+            }
+        }
+        self.metrics.evicted += evicted.len() as u64;
+        if self.executable.iter().any(|key| evicted.contains(key)) {
+            self.executable.retain(|key| !evicted.contains(key));
+            self.rebuild();
+        }
+    }
+
+    /// Queues the transaction under its sender and applies it, with the
+    /// queued ones following it, once its nonce is the next one.
+    fn insert(&mut self, data: TxData) -> Result<(), String> {
+        if !data.valid_at(self.current_block) {
+            return Err(format!(
+                "Transaction not valid at block #{}",
+                self.current_block
+            ));
+        }
+        let sender = data.from();
+        let nonce = data.nonce;
+        if let Some(pending) = self.queues.get(&sender).and_then(|queue| queue.get(&nonce)) {
+            return self.replace(pending.clone(), data);
+        }
+        let next_nonce = self.next_nonce(&sender);
+        if nonce < next_nonce {
+            return Err(format!("Nonce {} already used", nonce));
+        }
+        if nonce == next_nonce {
+            self.apply(&data)?;
+        }
+        self.push(data);
+        self.promote(&sender);
+        Ok(())
+    }
+
+    /// Swaps the pending transaction for one with the same nonce paying at
+    /// least `REPLACEMENT_FEE_BUMP` percent more. The pool is applied again
+    /// without the replaced one, or left as it was if the new one fails.
+    fn replace(&mut self, pending: TxData, data: TxData) -> Result<(), String> {
+        let required = pending
+            .fee
+            .mul_div(100 + REPLACEMENT_FEE_BUMP, 100)
+            .unwrap_or(Amount::from_units(u128::MAX));
+        if data.fee <= pending.fee || data.fee < required {
+            return Err(format!("Replacement fee must be at least {}", required));
+        }
+        let key = (data.from(), data.nonce);
+        let queues = self.queues.clone();
+        let executable = self.executable.clone();
+        self.remove(&key.0, key.1);
+        self.push(data.clone());
+        if !self.executable.contains(&key) {
+            return Ok(());
+        }
+        let dropped = self.rebuild();
+        if self.pending(&key.0, key.1) == Some(&data) {
+            return Ok(());
+        }
+        self.queues = queues;
+        self.executable = executable;
+        self.rebuild();
+        Err(dropped
+            .get(&key)
+            .cloned()
+            .unwrap_or(String::from("Replacement cannot be applied")))
+    }
+
+    /// Applies the queued transactions of `sender` whose nonce gap was filled.
+    fn promote(&mut self, sender: &String) {
+        loop {
+            let nonce = self.next_nonce(sender);
+            let Some(data) = self
+                .queues
+                .get(sender)
+                .and_then(|queue| queue.get(&nonce))
+                .cloned()
+            else {
+                break;
+            };
+            if let Err(err) = self.apply(&data) {
+                debug!("Dropping pending tx: {}", err);
+                self.remove(sender, nonce);
+                break;
+            }
+        }
+    }
+
+    fn apply(&mut self, data: &TxData) -> Result<(), String> {
+        let tx = Tx::from_tx(data.clone(), self.last_event.clone(), self.current_block);
+        if let Err(err) = process_tx(
+            self.wallet.address_str(),
+            &tx,
+            self.current_block,
+            &mut self.accounts,
+            &self.params,
+        ) {
+            self.replay();
+            return Err(err);
         }
-        if let Ok(block) = Block::new(
+        self.executable.push((data.from(), data.nonce));
+        Ok(())
+    }
+
+    /// Rebuilds `accounts` from the executable transactions, dropping changes
+    /// a failed transaction made before failing.
+    fn replay(&mut self) {
+        let mut accounts = self.committed.clone();
+        for (sender, nonce) in &self.executable {
+            if let Some(data) = self.queues.get(sender).and_then(|queue| queue.get(nonce)) {
+                let tx = Tx::from_tx(data.clone(), self.last_event.clone(), self.current_block);
+                if let Err(err) = process_tx(
+                    self.wallet.address_str(),
+                    &tx,
+                    self.current_block,
+                    &mut accounts,
+                    &self.params,
+                ) {
+                    debug!("Cannot replay pending tx: {}", err);
+                }
+            }
+        }
+        self.accounts = accounts;
+    }
+
+    fn pending(&self, sender: &String, nonce: u64) -> Option<&TxData> {
+        self.queues.get(sender).and_then(|queue| queue.get(&nonce))
+    }
+
+    fn push(&mut self, data: TxData) {
+        self.bytes += size(&data);
+        self.by_fee_rate
+            .insert((fee_rate(&data), data.from(), data.nonce));
+        self.queues
+            .entry(data.from())
+            .or_default()
+            .insert(data.nonce, data);
+    }
+
+    fn remove(&mut self, sender: &String, nonce: u64) -> Option<TxData> {
+        let queue = self.queues.get_mut(sender)?;
+        let data = queue.remove(&nonce)?;
+        if queue.is_empty() {
+            self.queues.remove(sender);
+        }
+        self.bytes -= size(&data);
+        self.by_fee_rate
+            .remove(&(fee_rate(&data), sender.clone(), nonce));
+        self.dropped.insert((sender.clone(), nonce));
+        Some(data)
+    }
+
+    fn next_nonce(&self, sender: &String) -> u64 {
+        self.accounts.get(sender).map_or(0, Account::nonce) + 1
+    }
+
+    pub fn get_account(&mut self, wallet: String) -> Option<Account> {
+        self.accounts.get(&wallet).cloned()
+    }
+
+    pub fn new_block(&mut self, timestamp: u64, reward: Amount) -> Option<Block> {
+        let mut txs = self.select_txs();
+        if reward > Amount::zero() {
+            let coinbase = TxData::coinbase(
+                self.chain_id(),
+                self.wallet.address_str(),
+                reward,
+                self.current_block,
+            );
+            let prev_hash = txs.last().map_or(self.last_event.clone(), Tx::hash_str);
+            txs.push(Tx::from_tx(coinbase, prev_hash, self.current_block));
+        }
+        Block::new(
             &self.wallet,
-            self.current_block.clone(),
+            self.chain_id(),
+            self.current_block,
             self.prev_block_hash.clone(),
-            self.pending_txs.clone(),
-        ) {
-            self.pending_txs.clear();
-            Some(block)
-        } else {
-            None
+            timestamp,
+            txs,
+        )
+        .ok()
+    }
+
+    /// Picks up to `MAX_BLOCK_TXS` executable transactions, highest fee rate
+    /// first. Only the lowest pending nonce of each sender is a candidate, so
+    /// senders' transactions keep their nonce order.
+    fn select_txs(&self) -> Vec<Tx> {
+        let mut accounts = self.committed.clone();
+        let mut candidates = BinaryHeap::new();
+        for (sender, queue) in &self.queues {
+            let nonce = accounts.get(sender).map_or(0, Account::nonce) + 1;
+            if let Some(data) = queue.get(&nonce) {
+                candidates.push((fee_rate(data), sender, nonce));
+            }
+        }
+        let mut txs: Vec<Tx> = Vec::new();
+        while txs.len() < MAX_BLOCK_TXS
+            && let Some((_, sender, nonce)) = candidates.pop()
+        {
+            let prev_hash = txs.last().map_or(self.last_event.clone(), Tx::hash_str);
+            let data = self.queues[sender][&nonce].clone();
+            let tx = Tx::from_tx(data, prev_hash, self.current_block);
+            if let Err(err) = process_tx(
+                self.wallet.address_str(),
+                &tx,
+                self.current_block,
+                &mut accounts,
+                &self.params,
+            ) {
+                debug!("Skipping pending txs of {}: {}", sender, err);
+                accounts = self.committed.clone();
+                for tx in &txs {
+                    let _ = process_tx(
+                        self.wallet.address_str(),
+                        tx,
+                        self.current_block,
+                        &mut accounts,
+                        &self.params,
+                    );
+                }
+                continue;
+            }
+            txs.push(tx);
+            if let Some(data) = self.queues[sender].get(&(nonce + 1)) {
+                candidates.push((fee_rate(data), sender, nonce + 1));
+            }
         }
+        txs
+    }
+
+    pub fn current_block(&self) -> u64 {
+        self.current_block
+    }
+
+    pub fn chain_id(&self) -> String {
+        self.params.chain_id.clone()
     }
 
-    pub fn current_fee(&self) -> BigDecimal {
-        let current_txs = self.pending_txs.len();
-        if current_txs == 0 {
-            return BigDecimal::from_str(INITIAL_FEE).unwrap();
+    /// Returns the pooled transactions, executable ones first in the order
+    /// they were applied.
+    pub fn pending_txs(&self) -> Vec<TxData> {
+        let mut pending_txs: Vec<TxData> = self
+            .executable
+            .iter()
+            .filter_map(|(sender, nonce)| self.pending(sender, *nonce).cloned())
+            .collect();
+        for (sender, queue) in &self.queues {
+            for (nonce, data) in queue {
+                if !self.executable.contains(&(sender.clone(), *nonce)) {
+                    pending_txs.push(data.clone());
+                }
+            }
+        }
+        pending_txs
+    }
+
+    pub fn metrics(&self) -> PoolMetrics {
+        PoolMetrics {
+            pending_txs: self.by_fee_rate.len(),
+            pending_bytes: self.bytes,
+            ..self.metrics.clone()
+        }
+    }
+
+    pub fn current_fee(&self) -> Amount {
+        let current_txs = self.by_fee_rate.len().max(1) as u128;
+        Amount::from_units(INITIAL_FEE.saturating_mul(current_txs))
+    }
+}
+
+/// Returns the balance the transaction takes from its sender.
+fn cost(data: &TxData) -> Option<Amount> {
+    match data.payload {
+        Payload::Unstake { .. } | Payload::Undelegate { .. } => Some(data.fee),
+        _ => data.amount().checked_add(data.fee),
+    }
+}
+
+fn size(data: &TxData) -> usize {
+    encode(data).map_or(0, |bytes| bytes.len())
+}
+
+/// Returns the fee paid per token moved or memo byte, the unit of
+/// `current_fee`. Evidence pays no fee but always goes first.
+fn fee_rate(data: &TxData) -> Amount {
+    let max = Amount::from_units(u128::MAX);
+    if matches!(data.payload, Payload::Evidence { .. }) {
+        return max;
+    }
+    let one = Amount::from_whole(1).unwrap_or_default();
+    match data.required_fee(one) {
+        Some(weight) if weight > Amount::zero() => {
+            data.fee.mul_div(one.units(), weight.units()).unwrap_or(max)
         }
-        let current_txs = BigDecimal::from_usize(current_txs).unwrap();
-        current_txs * BigDecimal::from_str(INITIAL_FEE).unwrap()
+        _ => Amount::zero(),
     }
 }
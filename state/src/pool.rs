@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_MAX_TXS: usize = 10_000;
+const DEFAULT_MAX_BYTES: usize = 16 * 1024 * 1024;
+const DEFAULT_MAX_TXS_PER_SENDER: usize = 64;
+
+/// Caps on the mempool, with transactions counted at their encoded size.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PoolLimits {
+    pub max_txs: usize,
+    pub max_bytes: usize,
+    pub max_txs_per_sender: usize,
+}
+
+impl Default for PoolLimits {
+    fn default() -> Self {
+        Self {
+            max_txs: DEFAULT_MAX_TXS,
+            max_bytes: DEFAULT_MAX_BYTES,
+            max_txs_per_sender: DEFAULT_MAX_TXS_PER_SENDER,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PoolMetrics {
+    pub pending_txs: usize,
+    pub pending_bytes: usize,
+    pub accepted: u64,
+    pub rejected: u64,
+    pub throttled: u64,
+    pub evicted: u64,
+}
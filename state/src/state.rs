@@ -1,7 +1,9 @@
 use crate::mem_pool::MemPool;
+use crate::pool::{PoolLimits, PoolMetrics};
 use account::account::Account;
 use block::block::Block;
-use common::bigdecimal::BigDecimal;
+use common::amount::Amount;
+use operation::params::Params;
 use std::collections::BTreeMap;
 use tx::tx::Tx;
 use tx::tx_data::TxData;
@@ -12,26 +14,41 @@ pub struct State {
 }
 
 impl State {
-    pub fn new(wallet: Wallet) -> Self {
+    pub fn new(wallet: Wallet, params: Params) -> Self {
+        Self::with_limits(wallet, params, PoolLimits::default())
+    }
+
+    pub fn with_limits(wallet: Wallet, params: Params, limits: PoolLimits) -> Self {
         Self {
-            mem_pool: futures::lock::Mutex::new(MemPool::new(wallet)),
+            mem_pool: futures::lock::Mutex::new(MemPool::new(wallet, params, limits)),
         }
     }
 
+    /// Returns the keys of the pending transactions dropped by the update.
     pub async fn update(
         &self,
         prev_block_hash: String,
         current_block: u64,
         last_event: String,
         accounts: BTreeMap<String, Account>,
-    ) {
+    ) -> Vec<(String, u64)> {
         let mut mem_pool = self.mem_pool.lock().await;
-        mem_pool.update(prev_block_hash, current_block, last_event, accounts);
+        mem_pool.update(prev_block_hash, current_block, last_event, accounts)
     }
 
     pub async fn add_tx(&self, tx_data: TxData) -> Result<Tx, String> {
+        self.add_tx_dropping(tx_data).await.0
+    }
+
+    /// Adds the tx and returns the keys of the pending transactions dropped
+    /// to make room for it, so the journal can be updated in the same step.
+    pub async fn add_tx_dropping(
+        &self,
+        tx_data: TxData,
+    ) -> (Result<Tx, String>, Vec<(String, u64)>) {
         let mut mem_pool = self.mem_pool.lock().await;
-        mem_pool.add_tx(tx_data)
+        let result = mem_pool.add_tx(tx_data);
+        (result, mem_pool.take_dropped())
     }
 
     pub async fn get_account(&self, wallet: String) -> Option<Account> {
@@ -39,12 +56,32 @@ impl State {
         mem_pool.get_account(wallet)
     }
 
-    pub async fn new_block(&self, validator: String) -> Option<Block> {
+    pub async fn new_block(&self, timestamp: u64, reward: Amount) -> Option<Block> {
         let mut mem_pool = self.mem_pool.lock().await;
-        mem_pool.new_block(validator)
+        mem_pool.new_block(timestamp, reward)
+    }
+
+    pub async fn chain_id(&self) -> String {
+        let mem_pool = self.mem_pool.lock().await;
+        mem_pool.chain_id()
+    }
+
+    pub async fn current_block(&self) -> u64 {
+        let mem_pool = self.mem_pool.lock().await;
+        mem_pool.current_block()
+    }
+
+    pub async fn pending_txs(&self) -> Vec<TxData> {
+        let mem_pool = self.mem_pool.lock().await;
+        mem_pool.pending_txs()
     }
-    
-    pub async fn current_fee(&self) -> BigDecimal {
+
+    pub async fn metrics(&self) -> PoolMetrics {
+        let mem_pool = self.mem_pool.lock().await;
+        mem_pool.metrics()
+    }
+
+    pub async fn current_fee(&self) -> Amount {
         let mem_pool = self.mem_pool.lock().await;
         mem_pool.current_fee()
     }
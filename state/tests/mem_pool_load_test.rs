@@ -0,0 +1,215 @@
+use account::account::Account;
+use block::block::Block;
+use common::amount::Amount;
+use common::codec::encode;
+use operation::params::Params;
+use state::pool::PoolLimits;
+use state::state::State;
+use std::collections::BTreeMap;
+use tx::evidence::Evidence;
+use tx::tx_data::TxData;
+use wallet::wallet::Wallet;
+
+fn transfer(wallet: &Wallet, fee: &str, nonce: u64) -> TxData {
+    TxData::new(
+        wallet,
+        String::default(),
+        String::from("to"),
+        String::from("10"),
+        String::from(fee),
+        nonce,
+    )
+    .unwrap()
+}
+
+async fn limited_state(wallets: &[Wallet], limits: PoolLimits) -> State {
+    let mut accounts = BTreeMap::new();
+    for wallet in wallets {
+        let mut account = Account::new(wallet.address_str());
+        account.debit(Amount::from_str("10000").unwrap()).unwrap();
+        accounts.insert(account.wallet(), account);
+    }
+    let state = State::with_limits(Wallet::new(), Params::default(), limits);
+    state
+        .update(String::default(), 1, String::default(), accounts)
+        .await;
+    state
+}
+
+#[tokio::test]
+async fn flood_is_capped_and_evicted_by_higher_fees() {
+    let spammers: Vec<Wallet> = (0..20).map(|_| Wallet::new()).collect();
+    let payers: Vec<Wallet> = (0..4).map(|_| Wallet::new()).collect();
+    let limits = PoolLimits {
+        max_txs: 50,
+        max_txs_per_sender: 5,
+        ..PoolLimits::default()
+    };
+    let wallets: Vec<Wallet> = spammers.iter().chain(&payers).cloned().collect();
+    let state = limited_state(&wallets, limits).await;
+
+    for wallet in &spammers {
+        for nonce in 1..=8 {
+            let _ = state.add_tx(transfer(wallet, "1", nonce)).await;
+        }
+    }
+    let metrics = state.metrics().await;
+    assert_eq!(metrics.pending_txs, 50);
+    assert_eq!(metrics.accepted, 50);
+    assert_eq!(metrics.rejected, 160 - 50);
+    assert_eq!(metrics.throttled, 10 * 3);
+    assert_eq!(metrics.evicted, 0);
+
+    let result = state.add_tx(transfer(&payers[0], "1", 1)).await;
+    assert_eq!(result.err(), Some(String::from("Mempool is full")));
+
+    for wallet in &payers {
+        for nonce in 1..=5 {
+            state.add_tx(transfer(wallet, "2", nonce)).await.unwrap();
+        }
+    }
+    let metrics = state.metrics().await;
+    assert_eq!(metrics.pending_txs, 50);
+    assert_eq!(metrics.evicted, 20);
+
+    let block = state.new_block(0, Amount::zero()).await.unwrap();
+    let txs = block.txs().unwrap_or_default();
+    assert_eq!(txs.len(), 50);
+    let payer = payers[0].address_str();
+    assert!(txs.iter().take(20).any(|tx| tx.from() == payer));
+}
+
+#[tokio::test]
+async fn pool_stays_within_byte_limit() {
+    let wallets: Vec<Wallet> = (0..30).map(|_| Wallet::new()).collect();
+    let size = encode(&transfer(&wallets[0], "1", 1)).unwrap().len();
+    let limits = PoolLimits {
+        max_bytes: size * 10,
+        ..PoolLimits::default()
+    };
+    let state = limited_state(&wallets, limits.clone()).await;
+
+    for (i, wallet) in wallets.iter().enumerate() {
+        let fee = if i % 2 == 0 { "1" } else { "3" };
+        let _ = state.add_tx(transfer(wallet, fee, 1)).await;
+        let metrics = state.metrics().await;
+        assert!(metrics.pending_bytes <= limits.max_bytes);
+    }
+    let metrics = state.metrics().await;
+    assert!(metrics.pending_txs < wallets.len());
+    assert!(metrics.evicted > 0);
+    let block = state.new_block(0, Amount::zero()).await.unwrap();
+    let txs = block.txs().unwrap_or_default();
+    assert!(
+        txs.iter()
+            .all(|tx| tx.fee() == Amount::from_str("3").unwrap())
+    );
+}
+
+#[tokio::test]
+async fn flood_from_unfunded_addresses_is_rejected() {
+    let spammers: Vec<Wallet> = (0..20).map(|_| Wallet::new()).collect();
+    let payer = Wallet::new();
+    let limits = PoolLimits {
+        max_txs: 50,
+        ..PoolLimits::default()
+    };
+    let state = limited_state(std::slice::from_ref(&payer), limits).await;
+
+    for wallet in &spammers {
+        for nonce in 1..=8 {
+            let result = state.add_tx(transfer(wallet, "1000", nonce)).await;
+            assert_eq!(result.err(), Some(String::from("Not enough balance")));
+        }
+    }
+    let metrics = state.metrics().await;
+    assert_eq!(metrics.pending_txs, 0);
+    assert_eq!(metrics.rejected, 160);
+
+    for nonce in (2..=9).rev() {
+        state.add_tx(transfer(&payer, "1000", nonce)).await.unwrap();
+    }
+    state.add_tx(transfer(&payer, "1000", 1)).await.unwrap();
+    let result = state.add_tx(transfer(&payer, "1000", 10)).await;
+    assert_eq!(result.err(), Some(String::from("Not enough balance")));
+    assert_eq!(state.metrics().await.pending_txs, 9);
+}
+
+fn equivocation(offender: &Wallet, idx: u64) -> Evidence {
+    let header = |parent_hash: &str| {
+        Block::new(
+            offender,
+            String::default(),
+            idx,
+            String::from(parent_hash),
+            0,
+            vec![],
+        )
+        .unwrap()
+        .header()
+    };
+    Evidence::new(header("first"), header("second"))
+}
+
+fn report(reporter: &Wallet, evidence: Evidence, nonce: u64) -> TxData {
+    TxData::new_evidence(
+        reporter,
+        String::default(),
+        evidence,
+        String::from("0"),
+        nonce,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn evidence_is_validated_and_capped() {
+    let offenders: Vec<Wallet> = (0..20).map(|_| Wallet::new()).collect();
+    let reporter = Wallet::new();
+    let mut accounts = BTreeMap::new();
+    for wallet in &offenders {
+        let mut account = Account::new(wallet.address_str());
+        account.debit(Amount::from_str("100").unwrap()).unwrap();
+        account
+            .stake_amount(Amount::from_str("100").unwrap(), Amount::zero())
+            .unwrap();
+        accounts.insert(account.wallet(), account);
+    }
+    accounts.insert(reporter.address_str(), Account::new(reporter.address_str()));
+    let state = State::new(Wallet::new(), Params::default());
+    state
+        .update(String::default(), 1, String::default(), accounts)
+        .await;
+
+    let mut forged = equivocation(&offenders[0], 1);
+    forged.second.signature = forged.first.signature.clone();
+    let result = state.add_tx(report(&reporter, forged, 1)).await;
+    assert_eq!(result.err(), Some(String::from("Invalid evidence")));
+    let result = state
+        .add_tx(report(&reporter, equivocation(&Wallet::new(), 1), 1))
+        .await;
+    assert_eq!(result.err(), Some(String::from("Nothing to slash")));
+
+    state
+        .add_tx(report(&reporter, equivocation(&offenders[0], 1), 1))
+        .await
+        .unwrap();
+    let result = state
+        .add_tx(report(&reporter, equivocation(&offenders[0], 1), 2))
+        .await;
+    assert_eq!(result.err(), Some(String::from("Evidence already pending")));
+
+    for (offender, nonce) in offenders[1..].iter().zip(2..) {
+        let _ = state
+            .add_tx(report(&reporter, equivocation(offender, 1), nonce))
+            .await;
+    }
+    assert_eq!(state.metrics().await.pending_txs, 16);
+    let result = state
+        .add_tx(report(&reporter, equivocation(&offenders[0], 2), 17))
+        .await;
+    assert_eq!(
+        result.err(),
+        Some(String::from("Too many pending evidence"))
+    );
+}
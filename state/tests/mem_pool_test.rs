@@ -1,7 +1,12 @@
 use account::account::Account;
-use common::bigdecimal::BigDecimal;
+use block::block::MAX_BLOCK_TXS;
+use common::amount::Amount;
+use operation::params::Params;
+use state::pool::PoolLimits;
 use state::state::State;
 use std::collections::BTreeMap;
+use tx::multisig::Multisig;
+use tx::payload::Payload;
 use tx::tx_data::TxData;
 use wallet::wallet::Wallet;
 
@@ -15,14 +20,14 @@ async fn add_tx() {
     let wallet = Wallet::new();
 
     let nonce = 0;
-    let amount = BigDecimal::from_str("1000").unwrap();
+    let amount = Amount::from_str("1000").unwrap();
 
     let mut account = Account::new(wallet.address_str());
     account.debit(amount).unwrap();
 
     accounts.insert(account.wallet(), account.clone());
 
-    let state = State::new(wallet.clone());
+    let state = State::new(wallet.clone(), Params::default());
     state
         .update(account.wallet(), current_block, last_event, accounts)
         .await;
@@ -33,6 +38,7 @@ async fn add_tx() {
     assert_eq!(nonce, account.nonce);
     let tx = TxData::new(
         &wallet,
+        String::default(),
         String::from("to"),
         String::from("100"),
         String::from("1"),
@@ -42,3 +48,382 @@ async fn add_tx() {
     let tx = state.add_tx(tx).await.unwrap();
     println!("{:?}", tx);
 }
+
+#[tokio::test]
+async fn reject_tx_from_other_chain() {
+    let wallet = Wallet::new();
+    let mut account = Account::new(wallet.address_str());
+    account.debit(Amount::from_str("1000").unwrap()).unwrap();
+    let mut accounts = BTreeMap::new();
+    accounts.insert(account.wallet(), account.clone());
+
+    let params = Params {
+        chain_id: String::from("xchg-testnet"),
+        ..Params::default()
+    };
+    let state = State::new(wallet.clone(), params);
+    state
+        .update(account.wallet(), 0, String::default(), accounts)
+        .await;
+
+    let tx = |chain_id: &str| {
+        TxData::new(
+            &wallet,
+            String::from(chain_id),
+            String::from("to"),
+            String::from("100"),
+            String::from("1"),
+            1,
+        )
+        .unwrap()
+    };
+    let result = state.add_tx(tx("xchg-mainnet")).await;
+    assert_eq!(result.unwrap_err(), "Invalid chain id");
+    state.add_tx(tx("xchg-testnet")).await.unwrap();
+}
+
+#[tokio::test]
+async fn reject_fee_below_rate() {
+    let wallet = Wallet::new();
+    let mut account = Account::new(wallet.address_str());
+    account.debit(Amount::from_str("1000").unwrap()).unwrap();
+    let mut accounts = BTreeMap::new();
+    accounts.insert(account.wallet(), account.clone());
+
+    let state = State::new(wallet.clone(), Params::default());
+    state
+        .update(account.wallet(), 0, String::default(), accounts)
+        .await;
+    assert_eq!(state.current_fee().await.to_string(), "0.00000000001");
+
+    let tx = |fee: &str| {
+        TxData::new(
+            &wallet,
+            String::default(),
+            String::from("to"),
+            String::from("100"),
+            String::from(fee),
+            1,
+        )
+        .unwrap()
+    };
+    for fee in ["0", "0.000000000999999999"] {
+        let result = state.add_tx(tx(fee)).await;
+        assert_eq!(result.unwrap_err(), "Fee is to low");
+    }
+    state.add_tx(tx("0.000000001")).await.unwrap();
+}
+
+#[tokio::test]
+async fn refuse_and_evict_expired_tx() {
+    let wallet = Wallet::new();
+    let mut account = Account::new(wallet.address_str());
+    account.debit(Amount::from_str("1000").unwrap()).unwrap();
+    let mut accounts = BTreeMap::new();
+    accounts.insert(account.wallet(), account.clone());
+
+    let state = State::new(wallet.clone(), Params::default());
+    state
+        .update(account.wallet(), 5, String::default(), accounts.clone())
+        .await;
+
+    let tx = |nonce: u64, valid_until: u64| {
+        TxData::new(
+            &wallet,
+            String::default(),
+            String::from("to"),
+            String::from("100"),
+            String::from("1"),
+            nonce,
+        )
+        .unwrap()
+        .valid_between(&wallet, None, Some(valid_until))
+        .unwrap()
+    };
+    let result = state.add_tx(tx(1, 4)).await;
+    assert_eq!(result.unwrap_err(), "Transaction not valid at block #5");
+    state.add_tx(tx(1, 5)).await.unwrap();
+
+    state
+        .update(account.wallet(), 6, String::default(), accounts)
+        .await;
+    let block = state.new_block(0, Amount::zero()).await.unwrap();
+    assert!(block.txs().unwrap_or_default().is_empty());
+}
+
+#[tokio::test]
+async fn memo_raises_required_fee() {
+    let wallet = Wallet::new();
+    let mut account = Account::new(wallet.address_str());
+    account.debit(Amount::from_str("1000").unwrap()).unwrap();
+    let mut accounts = BTreeMap::new();
+    accounts.insert(account.wallet(), account.clone());
+
+    let state = State::new(wallet.clone(), Params::default());
+    state
+        .update(account.wallet(), 0, String::default(), accounts)
+        .await;
+
+    let tx = |fee: &str| {
+        TxData::new(
+            &wallet,
+            String::default(),
+            String::from("to"),
+            String::from("100"),
+            String::from(fee),
+            1,
+        )
+        .unwrap()
+        .with_memo(&wallet, "x".repeat(100))
+        .unwrap()
+    };
+    let result = state.add_tx(tx("0.000000001")).await;
+    assert_eq!(result.unwrap_err(), "Fee is to low");
+    let tx = state.add_tx(tx("0.000000002")).await.unwrap();
+    assert_eq!(tx.data.memo_size(), 100);
+}
+
+#[tokio::test]
+async fn multisig_spend_needs_threshold() {
+    let wallet = Wallet::new();
+    let keys: Vec<Wallet> = (0..3).map(|_| Wallet::new()).collect();
+    let multisig = Multisig::new(2, keys.iter().map(Wallet::address_str).collect()).unwrap();
+    let mut account = Account::new(multisig.address());
+    account.debit(Amount::from_str("1000").unwrap()).unwrap();
+    let mut accounts = BTreeMap::new();
+    accounts.insert(account.wallet(), account.clone());
+
+    let state = State::new(wallet.clone(), Params::default());
+    state
+        .update(wallet.address_str(), 0, String::default(), accounts)
+        .await;
+
+    let payload = Payload::Transfer {
+        to: String::from("to"),
+        amount: Amount::from_str("100").unwrap(),
+    };
+    let tx = TxData::with_multisig(
+        &keys[0],
+        String::default(),
+        multisig.clone(),
+        payload,
+        String::from("1"),
+        1,
+    )
+    .unwrap();
+    let result = state.add_tx(tx.clone()).await;
+    assert_eq!(result.unwrap_err(), "Invalid transaction");
+
+    let mut signed = tx.clone();
+    signed.sign_partial(&keys[1]).unwrap();
+    state.add_tx(signed).await.unwrap();
+    let account = state.get_account(multisig.address()).await.unwrap();
+    assert_eq!(account.balance.to_string(), "899");
+}
+
+fn funded_state(wallets: &[&Wallet]) -> (State, BTreeMap<String, Account>) {
+    let mut accounts = BTreeMap::new();
+    for wallet in wallets {
+        let mut account = Account::new(wallet.address_str());
+        account.debit(Amount::from_str("10000").unwrap()).unwrap();
+        accounts.insert(account.wallet(), account);
+    }
+    (State::new(Wallet::new(), Params::default()), accounts)
+}
+
+fn transfer(wallet: &Wallet, amount: &str, fee: &str, nonce: u64) -> TxData {
+    TxData::new(
+        wallet,
+        String::default(),
+        String::from("to"),
+        String::from(amount),
+        String::from(fee),
+        nonce,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn future_nonce_waits_for_gap() {
+    let wallet = Wallet::new();
+    let (state, accounts) = funded_state(&[&wallet]);
+    state
+        .update(wallet.address_str(), 1, String::default(), accounts)
+        .await;
+
+    state.add_tx(transfer(&wallet, "10", "1", 3)).await.unwrap();
+    state.add_tx(transfer(&wallet, "10", "1", 2)).await.unwrap();
+    let account = state.get_account(wallet.address_str()).await.unwrap();
+    assert_eq!(account.nonce, 0);
+    let block = state.new_block(0, Amount::zero()).await.unwrap();
+    assert!(block.txs().unwrap_or_default().is_empty());
+
+    state.add_tx(transfer(&wallet, "10", "1", 1)).await.unwrap();
+    let account = state.get_account(wallet.address_str()).await.unwrap();
+    assert_eq!(account.nonce, 3);
+    let block = state.new_block(0, Amount::zero()).await.unwrap();
+    let nonces: Vec<u64> = block
+        .txs()
+        .unwrap_or_default()
+        .iter()
+        .map(|tx| tx.nonce())
+        .collect();
+    assert_eq!(nonces, vec![1, 2, 3]);
+
+    let result = state.add_tx(transfer(&wallet, "20", "1", 2)).await;
+    assert_eq!(result.unwrap_err(), "Replacement fee must be at least 1.1");
+}
+
+#[tokio::test]
+async fn block_orders_by_fee_rate() {
+    let low = Wallet::new();
+    let high = Wallet::new();
+    let (state, accounts) = funded_state(&[&low, &high]);
+    state
+        .update(low.address_str(), 1, String::default(), accounts.clone())
+        .await;
+
+    state.add_tx(transfer(&low, "10", "0.1", 1)).await.unwrap();
+    state.add_tx(transfer(&high, "100", "5", 1)).await.unwrap();
+    state
+        .add_tx(transfer(&high, "10", "0.01", 2))
+        .await
+        .unwrap();
+    state.add_tx(transfer(&low, "1", "1", 2)).await.unwrap();
+    let block = state.new_block(0, Amount::zero()).await.unwrap();
+    let order: Vec<(String, u64)> = block
+        .txs()
+        .unwrap_or_default()
+        .iter()
+        .map(|tx| (tx.from(), tx.nonce()))
+        .collect();
+    assert_eq!(
+        order,
+        vec![
+            (high.address_str(), 1),
+            (low.address_str(), 1),
+            (low.address_str(), 2),
+            (high.address_str(), 2),
+        ]
+    );
+
+    let mut committed = accounts;
+    committed.get_mut(&high.address_str()).unwrap().nonce = 1;
+    state
+        .update(low.address_str(), 2, String::default(), committed)
+        .await;
+    let block = state.new_block(0, Amount::zero()).await.unwrap();
+    assert_eq!(block.txs().unwrap_or_default().len(), 3);
+    let result = state.add_tx(transfer(&high, "10", "1", 1)).await;
+    assert_eq!(result.unwrap_err(), "Nonce 1 already used");
+}
+
+#[tokio::test]
+async fn block_limited_to_max_txs() {
+    let wallet = Wallet::new();
+    let (_, accounts) = funded_state(&[&wallet]);
+    let limits = PoolLimits {
+        max_txs_per_sender: MAX_BLOCK_TXS + 1,
+        ..PoolLimits::default()
+    };
+    let state = State::with_limits(Wallet::new(), Params::default(), limits);
+    state
+        .update(wallet.address_str(), 1, String::default(), accounts)
+        .await;
+
+    for nonce in 1..=MAX_BLOCK_TXS as u64 + 1 {
+        state
+            .add_tx(transfer(&wallet, "1", "0.001", nonce))
+            .await
+            .unwrap();
+    }
+    let block = state.new_block(0, Amount::zero()).await.unwrap();
+    let txs = block.txs().unwrap_or_default();
+    assert_eq!(txs.len(), MAX_BLOCK_TXS);
+    assert_eq!(txs.last().unwrap().nonce(), MAX_BLOCK_TXS as u64);
+}
+
+#[tokio::test]
+async fn replace_pending_tx_by_fee() {
+    let wallet = Wallet::new();
+    let (state, accounts) = funded_state(&[&wallet]);
+    state
+        .update(wallet.address_str(), 1, String::default(), accounts)
+        .await;
+
+    state
+        .add_tx(transfer(&wallet, "100", "1", 1))
+        .await
+        .unwrap();
+    state
+        .add_tx(transfer(&wallet, "100", "1", 2))
+        .await
+        .unwrap();
+    for fee in ["1", "1.09"] {
+        let result = state.add_tx(transfer(&wallet, "100", fee, 1)).await;
+        assert_eq!(result.unwrap_err(), "Replacement fee must be at least 1.1");
+    }
+    let result = state.add_tx(transfer(&wallet, "9999", "2", 1)).await;
+    assert_eq!(result.unwrap_err(), "Not enough balance");
+    let account = state.get_account(String::from("to")).await.unwrap();
+    assert_eq!(account.balance.to_string(), "200");
+
+    let cancel = TxData::new(
+        &wallet,
+        String::default(),
+        wallet.address_str(),
+        String::from("0"),
+        String::from("1.1"),
+        1,
+    )
+    .unwrap();
+    state.add_tx(cancel).await.unwrap();
+    let account = state.get_account(wallet.address_str()).await.unwrap();
+    assert_eq!(account.nonce, 2);
+    assert_eq!(account.balance.to_string(), "9897.9");
+    let account = state.get_account(String::from("to")).await.unwrap();
+    assert_eq!(account.balance.to_string(), "100");
+
+    let block = state.new_block(0, Amount::zero()).await.unwrap();
+    let txs = block.txs().unwrap_or_default();
+    assert_eq!(txs.len(), 2);
+    assert_eq!(txs[0].to(), Some(wallet.address_str()));
+}
+
+#[tokio::test]
+async fn replacement_drops_dependent_txs() {
+    let wallet = Wallet::new();
+    let other = Wallet::new();
+    let (state, accounts) = funded_state(&[&wallet]);
+    state
+        .update(wallet.address_str(), 1, String::default(), accounts)
+        .await;
+
+    let pay = |fee: &str, to: &Wallet| {
+        TxData::new(
+            &wallet,
+            String::default(),
+            to.address_str(),
+            String::from("100"),
+            String::from(fee),
+            1,
+        )
+        .unwrap()
+    };
+    state.add_tx(pay("1", &other)).await.unwrap();
+    let spend = TxData::new(
+        &other,
+        String::default(),
+        String::from("to"),
+        String::from("50"),
+        String::from("1"),
+        1,
+    )
+    .unwrap();
+    state.add_tx(spend).await.unwrap();
+
+    state.add_tx(pay("2", &wallet)).await.unwrap();
+    assert!(state.get_account(other.address_str()).await.is_none());
+    let block = state.new_block(0, Amount::zero()).await.unwrap();
+    assert_eq!(block.txs().unwrap_or_default().len(), 1);
+}
@@ -0,0 +1,119 @@
+use crate::inflation::Inflation;
+use common::amount::Amount;
+use common::codec::{decode, encode};
+use db::batch::Batch;
+use operation::params::Params;
+use rocksdb::{DBWithThreadMode, MultiThreaded};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tx::tx_data::TxData;
+
+const CHAIN_CONFIG_KEY: &str = "config.chain";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChainConfig {
+    #[serde(default)]
+    pub inflation: Inflation,
+    #[serde(flatten)]
+    pub params: Params,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Genesis {
+    #[serde(flatten)]
+    pub config: ChainConfig,
+    pub txs: Vec<TxData>,
+}
+
+impl From<Vec<TxData>> for Genesis {
+    fn from(txs: Vec<TxData>) -> Self {
+        Self {
+            config: ChainConfig::default(),
+            txs,
+        }
+    }
+}
+
+/// Stored form of the inflation schedule. The genesis layout tags it by a
+/// `type` field, which the binary codec cannot read back.
+#[derive(Serialize, Deserialize)]
+enum StoredInflation {
+    None,
+    Fixed {
+        reward: Amount,
+    },
+    Decaying {
+        reward: Amount,
+        halving_interval: u64,
+    },
+    StakeProportional {
+        rate: Amount,
+    },
+}
+
+impl From<&Inflation> for StoredInflation {
+    fn from(inflation: &Inflation) -> Self {
+        match *inflation {
+            Inflation::None => Self::None,
+            Inflation::Fixed { reward } => Self::Fixed { reward },
+            Inflation::Decaying {
+                reward,
+                halving_interval,
+            } => Self::Decaying {
+                reward,
+                halving_interval,
+            },
+            Inflation::StakeProportional { rate } => Self::StakeProportional { rate },
+        }
+    }
+}
+
+impl From<StoredInflation> for Inflation {
+    fn from(inflation: StoredInflation) -> Self {
+        match inflation {
+            StoredInflation::None => Self::None,
+            StoredInflation::Fixed { reward } => Self::Fixed { reward },
+            StoredInflation::Decaying {
+                reward,
+                halving_interval,
+            } => Self::Decaying {
+                reward,
+                halving_interval,
+            },
+            StoredInflation::StakeProportional { rate } => Self::StakeProportional { rate },
+        }
+    }
+}
+
+pub struct ConfigStorage {
+    db: Arc<DBWithThreadMode<MultiThreaded>>,
+}
+
+impl ConfigStorage {
+    pub fn new(db: &Arc<DBWithThreadMode<MultiThreaded>>) -> Self {
+        Self { db: Arc::clone(db) }
+    }
+
+    pub fn stage(&self, batch: &mut Batch, config: &ChainConfig) -> Result<(), std::io::Error> {
+        let data = encode(&(StoredInflation::from(&config.inflation), &config.params))?;
+        batch.put(CHAIN_CONFIG_KEY, data);
+        Ok(())
+    }
+
+    pub fn find(&self) -> Result<ChainConfig, std::io::Error> {
+        let data = self
+            .db
+            .get(CHAIN_CONFIG_KEY)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        match data {
+            Some(data) => {
+                let (inflation, params): (StoredInflation, Params) = decode(&data)?;
+                Ok(ChainConfig {
+                    inflation: inflation.into(),
+                    params,
+                })
+            }
+            None => Ok(ChainConfig::default()),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use block::vote::Vote;
+use common::codec::{decode, encode};
+use db::batch::Batch;
+use rocksdb::{DBWithThreadMode, MultiThreaded};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+const FINALIZED_IDX_KEY: &str = "finality.finalized";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Finality {
+    pub justified: bool,
+    pub finalized: bool,
+}
+
+pub struct FinalityStorage {
+    db: Arc<DBWithThreadMode<MultiThreaded>>,
+}
+
+impl FinalityStorage {
+    pub fn new(db: &Arc<DBWithThreadMode<MultiThreaded>>) -> Self {
+        Self { db: Arc::clone(db) }
+    }
+
+    pub fn stage_votes(
+        &self,
+        batch: &mut Batch,
+        idx: u64,
+        votes: &BTreeMap<String, Vote>,
+    ) -> Result<(), std::io::Error> {
+        let data = encode(votes)?;
+        batch.put(self.votes_key(idx), data);
+        Ok(())
+    }
+
+    pub fn stage_justified(&self, batch: &mut Batch, idx: u64, hash: &str) {
+        batch.put(self.justified_key(idx), hash);
+    }
+
+    pub fn stage_finalized(&self, batch: &mut Batch, idx: u64) {
+        batch.put(FINALIZED_IDX_KEY, idx.to_string());
+    }
+
+    pub fn stage_remove(&self, batch: &mut Batch, idx: u64) {
+        batch.delete(self.votes_key(idx));
+        batch.delete(self.justified_key(idx));
+    }
+
+    pub fn find_votes(&self, idx: u64) -> Result<BTreeMap<String, Vote>, std::io::Error> {
+        if let Some(data) = self.get(&self.votes_key(idx))? {
+            decode(&data)
+        } else {
+            Ok(BTreeMap::new())
+        }
+    }
+
+    pub fn find_justified(&self, idx: u64) -> Result<Option<String>, std::io::Error> {
+        match self.get(&self.justified_key(idx))? {
+            Some(hash) => Ok(Some(String::from_utf8_lossy(&hash).to_string())),
+            None => Ok(None),
+        }
+    }
+
+    pub fn find_finalized(&self) -> Result<Option<u64>, std::io::Error> {
+        match self.get(FINALIZED_IDX_KEY)? {
+            Some(idx) => {
+                let idx = u64::from_str(&String::from_utf8_lossy(&idx))
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                Ok(Some(idx))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, std::io::Error> {
+        self.db
+            .get(key)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    fn votes_key(&self, idx: u64) -> String {
+        format!("finality.votes.{}", idx)
+    }
+
+    fn justified_key(&self, idx: u64) -> String {
+        format!("finality.justified.{}", idx)
+    }
+}
@@ -0,0 +1,64 @@
+use common::amount::Amount;
+use common::biginteger::BigInt;
+use serde::{Deserialize, Serialize};
+
+const MAX_HALVINGS: u64 = 128;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Inflation {
+    #[default]
+    None,
+    Fixed {
+        reward: Amount,
+    },
+    Decaying {
+        reward: Amount,
+        halving_interval: u64,
+    },
+    StakeProportional {
+        rate: Amount,
+    },
+}
+
+impl Inflation {
+    /// Returns the amount minted to the proposer of block `idx`.
+    ///
+    /// `total_stake` is the stake before the block is applied, so proposer and
+    /// validators compute the same reward from the parent state. Returns None
+    /// if the reward does not fit into an amount.
+    pub fn reward(&self, idx: u64, total_stake: &BigInt) -> Option<Amount> {
+        if idx == 0 {
+            return Some(Amount::zero());
+        }
+        match self {
+            Inflation::None => Some(Amount::zero()),
+            Inflation::Fixed { reward } => Some(*reward),
+            Inflation::Decaying {
+                reward,
+                halving_interval,
+            } => {
+                if *halving_interval == 0 {
+                    return Some(*reward);
+                }
+                let halvings = idx / halving_interval;
+                if halvings >= MAX_HALVINGS {
+                    return Some(Amount::zero());
+                }
+                Some(Amount::from_units(reward.units() >> halvings))
+            }
+            Inflation::StakeProportional { rate } => {
+                rate.checked_mul(Amount::from_bigint(total_stake)?)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Supply {
+    pub total: Amount,
+    pub circulating: Amount,
+    pub staked: Amount,
+    #[serde(default)]
+    pub vesting: Amount,
+}
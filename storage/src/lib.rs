@@ -1 +1,7 @@
-pub mod storage;
\ No newline at end of file
+pub mod config_storage;
+pub mod finality_storage;
+pub mod inflation;
+pub mod mem_pool_storage;
+pub mod stake_storage;
+pub mod storage;
+pub mod undo_storage;
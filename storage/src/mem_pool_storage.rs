@@ -0,0 +1,49 @@
+use common::codec::{decode, encode};
+use db::batch::Batch;
+use rocksdb::{DBWithThreadMode, MultiThreaded};
+use std::sync::Arc;
+use tx::tx_data::TxData;
+
+const PENDING_PREFIX: &str = "mempool.tx.";
+
+/// Journal of the transactions accepted into the mempool, replayed when the
+/// node starts. Each transaction is stored under its own key.
+pub struct MemPoolStorage {
+    db: Arc<DBWithThreadMode<MultiThreaded>>,
+}
+
+impl MemPoolStorage {
+    pub fn new(db: &Arc<DBWithThreadMode<MultiThreaded>>) -> Self {
+        Self { db: Arc::clone(db) }
+    }
+
+    pub fn stage(&self, batch: &mut Batch, data: &TxData) -> Result<(), std::io::Error> {
+        batch.put(Self::build_key(&data.from(), data.nonce), encode(data)?);
+        Ok(())
+    }
+
+    pub fn stage_remove(&self, batch: &mut Batch, txs: &[(String, u64)]) {
+        for (sender, nonce) in txs {
+            batch.delete(Self::build_key(sender, *nonce));
+        }
+    }
+
+    /// Returns the journaled transactions ordered by sender and nonce.
+    pub fn find_all(&self) -> Result<Vec<TxData>, std::io::Error> {
+        let mut txs = Vec::new();
+        for item in self.db.prefix_iterator(PENDING_PREFIX) {
+            let (key, value) =
+                item.map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            if !key.starts_with(PENDING_PREFIX.as_bytes()) {
+                break;
+            }
+            txs.push(decode(&value)?);
+        }
+        Ok(txs)
+    }
+
+    /// Pads the nonce so keys iterate in nonce order.
+    fn build_key(sender: &str, nonce: u64) -> String {
+        format!("{}{}.{:020}", PENDING_PREFIX, sender, nonce)
+    }
+}
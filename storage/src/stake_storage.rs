@@ -0,0 +1,51 @@
+use account::account::Account;
+use common::biginteger::BigInt;
+use common::codec::{decode, encode};
+use db::batch::Batch;
+use std::collections::BTreeMap;
+
+/// Proposer weight of each staked wallet.
+pub type Stakes = BTreeMap<String, BigInt>;
+
+/// Stake weights as left by each block, so the proposer of a child block is
+/// checked against its parent's stake rather than the latest state. Reads go
+/// through the batch, so snapshots staged along with their block are seen.
+/// Snapshots below the finalized block are pruned.
+pub struct StakeStorage;
+
+impl StakeStorage {
+    pub fn stage(
+        &self,
+        batch: &mut Batch,
+        hash: &str,
+        stakes: &Stakes,
+    ) -> Result<(), std::io::Error> {
+        let data = encode(stakes)?;
+        batch.put(self.build_key(hash), data);
+        Ok(())
+    }
+
+    pub fn stage_remove(&self, batch: &mut Batch, hash: &str) {
+        batch.delete(self.build_key(hash));
+    }
+
+    pub fn find(&self, batch: &Batch, hash: &str) -> Result<Option<Stakes>, std::io::Error> {
+        match batch.get(self.build_key(hash))? {
+            Some(data) => Ok(Some(decode(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Keeps the weight of the accounts carrying any.
+    pub fn weights(accounts: impl IntoIterator<Item = Account>) -> Stakes {
+        accounts
+            .into_iter()
+            .map(|account| (account.wallet(), account.weight()))
+            .filter(|(_, weight)| *weight > BigInt::zero())
+            .collect()
+    }
+
+    fn build_key(&self, hash: &str) -> String {
+        format!("stake.{}", hash)
+    }
+}
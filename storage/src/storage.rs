@@ -1,26 +1,53 @@
+use crate::config_storage::{ChainConfig, ConfigStorage, Genesis};
+use crate::finality_storage::{Finality, FinalityStorage};
+use crate::inflation::Supply;
+use crate::mem_pool_storage::MemPoolStorage;
+use crate::stake_storage::{StakeStorage, Stakes};
+use crate::undo_storage::{BlockUndo, UndoStorage};
 use account::account::Account;
 use account::account_storage::AccountStorage;
-use block::block::Block;
+use account::asset::Asset;
+use block::block::{Block, MAX_BLOCK_TXS, SLOT_DURATION};
 use block::block_storage::BlockStorage;
+use block::vote::Vote;
+use common::amount::Amount;
 use common::biginteger::BigInt;
+use db::batch::Batch;
 use log::{debug, error};
 use operation::tx::process_tx;
-use std::collections::{BTreeMap, HashSet};
+use rocksdb::{DBWithThreadMode, MultiThreaded};
+use sha2::Digest;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::error::Error;
 use std::fs;
-use std::hash::{DefaultHasher, Hash, Hasher};
 use std::path::Path;
 use std::process::exit;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tx::evidence::Evidence;
+use tx::payload::Payload;
 use tx::tx::Tx;
 use tx::tx_data::TxData;
 use tx::tx_storage::TxStorage;
 
 const FIRST_EVENT_HASH: [u8; 32] = [0u8; 32];
+const MAX_CLOCK_DRIFT: u64 = SLOT_DURATION;
+const MAX_EARLY_VOTES: usize = 4096;
+/// Votes are only buffered for blocks this far past the latest one.
+const EARLY_VOTE_WINDOW: u64 = 64;
 
 pub struct Storage {
+    db: Arc<DBWithThreadMode<MultiThreaded>>,
     tx_storage: TxStorage,
     account_storage: AccountStorage,
     block_storage: BlockStorage,
+    undo_storage: UndoStorage,
+    finality_storage: FinalityStorage,
+    config_storage: ConfigStorage,
+    mem_pool_storage: MemPoolStorage,
+    stake_storage: StakeStorage,
+    write_lock: Mutex<()>,
+    early_votes: Mutex<HashMap<String, Vec<Vote>>>,
 }
 
 impl Storage {
@@ -30,6 +57,14 @@ impl Storage {
                 tx_storage: TxStorage::new(&db),
                 block_storage: BlockStorage::new(&db),
                 account_storage: AccountStorage::new(&db),
+                undo_storage: UndoStorage::new(&db),
+                finality_storage: FinalityStorage::new(&db),
+                config_storage: ConfigStorage::new(&db),
+                mem_pool_storage: MemPoolStorage::new(&db),
+                stake_storage: StakeStorage,
+                write_lock: Mutex::new(()),
+                early_votes: Mutex::new(HashMap::new()),
+                db,
             },
             Err(e) => {
                 eprintln!("Cannot initialize local storage: {}", e);
@@ -39,26 +74,50 @@ impl Storage {
     }
 
     pub fn load_genesis_from_file(&self, genesis_path: &Path) -> Result<(), Box<dyn Error>> {
-        if let Some(_) = self.block_storage.find_by_idx(0)? {
+        if self.block_storage.find_by_idx(0)?.is_some() {
             return Ok(());
         }
         let json = fs::read_to_string(genesis_path)?;
-        let txs_data: Vec<TxData> = serde_json::from_str(&json)?;
-        self.load_genesis(txs_data)
+        let genesis = match serde_json::from_str::<Vec<TxData>>(&json) {
+            Ok(txs_data) => Genesis::from(txs_data),
+            Err(_) => serde_json::from_str(&json)?,
+        };
+        self.init_genesis(genesis)
     }
 
     pub fn load_genesis(&self, txs_data: Vec<TxData>) -> Result<(), Box<dyn Error>> {
-        let txs = Self::build_genesis_txs(txs_data)?;
+        self.init_genesis(Genesis::from(txs_data))
+    }
+
+    /// Stores the genesis block and state. A genesis without a `chain_id`
+    /// gets the hash of its block without one.
+    pub fn init_genesis(&self, mut genesis: Genesis) -> Result<(), Box<dyn Error>> {
+        let txs = Self::build_genesis_txs(genesis.txs.clone())?;
+        if genesis.config.params.chain_id.is_empty() {
+            genesis.config.params.chain_id = Block::genesis(String::new(), txs.clone()).hash_str();
+        }
         let mut accounts: BTreeMap<String, Account> = BTreeMap::new();
 
         for tx in &txs {
-            process_tx("GENESIS".to_string(), tx, &mut accounts)?;
+            process_tx(
+                "GENESIS".to_string(),
+                tx,
+                0,
+                &mut accounts,
+                &genesis.config.params,
+            )?;
         }
         let accounts: Vec<Account> = accounts.into_values().collect();
-        self.account_storage.save_all(&accounts)?;
-        self.tx_storage.save(&txs, 0)?;
-        let genesis = Block::genesis(txs);
-        self.block_storage.save(&genesis)?;
+        let mut batch = Batch::new(&self.db);
+        self.account_storage.stage_all(&mut batch, &accounts)?;
+        self.config_storage.stage(&mut batch, &genesis.config)?;
+        self.tx_storage.stage(&mut batch, &txs, 0)?;
+        let genesis = Block::genesis(genesis.config.params.chain_id.clone(), txs);
+        self.block_storage.stage(&mut batch, &genesis)?;
+        let stakes = StakeStorage::weights(accounts);
+        self.stake_storage
+            .stage(&mut batch, &genesis.hash_str(), &stakes)?;
+        batch.commit()?;
         Ok(())
     }
 
@@ -74,8 +133,31 @@ impl Storage {
     }
 
     pub fn add_block(&self, block: &Block) -> Result<(), Box<dyn Error>> {
+        {
+            let _guard = self.write_lock();
+            let mut batch = Batch::new(&self.db);
+            self.stage_block(&mut batch, block)?;
+            if let Err(e) = batch.commit() {
+                return Err(format!("Cannot add block to storage: {}", e).into());
+            }
+        }
+        self.replay_early_votes(block);
+        Ok(())
+    }
+
+    /// Validates `block` on top of the chain as staged in `batch` and stages it.
+    fn stage_block(&self, batch: &mut Batch, block: &Block) -> Result<(), Box<dyn Error>> {
         debug!("Adding block: {:?}", block);
-        if let Some(latest) = self.block_storage.find_latest()? {
+        let parent = self.block_storage.find_staged_latest(batch)?;
+        if let Some(latest) = &parent {
+            let chain_id = self.config_storage.find()?.params.chain_id;
+            if block.chain_id != chain_id {
+                return Err(format!(
+                    "Invalid chain id, expected: {}, was: {}",
+                    chain_id, block.chain_id
+                )
+                .into());
+            }
             let expected_idx = latest.idx + 1;
             if expected_idx != block.idx {
                 return Err(format!(
@@ -84,37 +166,308 @@ impl Storage {
                 )
                 .into());
             }
-        }
-        if let Some(txs) = block.txs() {
-            if let Some(latest_hash) = self.tx_storage.find_latest_hash()?
-                && let Some(first) = txs.first()
-                && latest_hash != first.prev_hash()
-            {
+            if latest.hash_str() != block.parent_hash {
+                return Err(format!(
+                    "Invalid parent hash, expected: {}, was: {}",
+                    latest.hash_str(),
+                    block.parent_hash
+                )
+                .into());
+            }
+            if block.slot <= latest.slot {
+                return Err(format!(
+                    "Invalid block slot, expected after: {}, was: {}",
+                    latest.slot, block.slot
+                )
+                .into());
+            }
+            if block.timestamp > Self::now() + MAX_CLOCK_DRIFT {
+                return Err(format!("Block #{} is from the future", block.idx).into());
+            }
+            if !block.valid() {
+                return Err(format!("Invalid block #{}", block.idx).into());
+            }
+            let Some(output) = block.vrf_output() else {
+                return Err(format!("Invalid VRF proof in block #{}", block.idx).into());
+            };
+            if !self.staged_can_propose(batch, &block.validator, latest, block.slot, output)? {
                 return Err(format!(
-                    "PoH error, expected: {}, was: {}",
-                    latest_hash,
-                    first.prev_hash()
+                    "Validator {} is not a proposer for block #{} in slot {}",
+                    block.validator, block.idx, block.slot
                 )
                 .into());
             }
-            if self.save_txs(&txs, block.validator(), block.idx)? {
-                self.block_storage.save(&block)?;
+        }
+        let latest_hash = self.tx_storage.find_staged_latest_hash(batch)?;
+        let mut undo = BlockUndo::new(latest_hash.clone());
+        let txs = block.txs().unwrap_or_default();
+        if txs.iter().filter(|tx| !tx.is_coinbase()).count() > MAX_BLOCK_TXS {
+            return Err(format!(
+                "Block #{} exceeds {} transactions",
+                block.idx, MAX_BLOCK_TXS
+            )
+            .into());
+        }
+        if let Some(latest_hash) = latest_hash
+            && let Some(first) = txs.first()
+            && latest_hash != first.prev_hash()
+        {
+            return Err(format!(
+                "PoH error, expected: {}, was: {}",
+                latest_hash,
+                first.prev_hash()
+            )
+            .into());
+        }
+        let Some(updated) = self.save_txs(batch, &mut undo, &txs, block.validator(), block.idx)?
+        else {
+            return Err("Invalid transactions".into());
+        };
+        let mut stakes = match &parent {
+            Some(parent) => self.stakes_at(batch, parent)?,
+            None => BTreeMap::new(),
+        };
+        for account in updated {
+            if account.weight() > BigInt::zero() {
+                stakes.insert(account.wallet(), account.weight());
             } else {
-                return Err("Invalid transactions".into());
+                stakes.remove(&account.wallet());
             }
         }
-        if let Err(e) = self.block_storage.save(&block) {
-            return Err(format!("Cannot add block to storage: {}", e).into());
+        self.stake_storage
+            .stage(batch, &block.hash_str(), &stakes)?;
+        let mined: Vec<(String, u64)> = txs.iter().map(|tx| (tx.from(), tx.nonce())).collect();
+        self.mem_pool_storage.stage_remove(batch, &mined);
+        self.undo_storage.stage(batch, block.idx, &undo)?;
+        self.block_storage.stage(batch, block)?;
+        Ok(())
+    }
+
+    pub fn rollback_to(&self, idx: u64) -> Result<(), Box<dyn Error>> {
+        let _guard = self.write_lock();
+        let mut batch = Batch::new(&self.db);
+        self.stage_rollback(&mut batch, idx)?;
+        batch.commit()?;
+        Ok(())
+    }
+
+    fn stage_rollback(&self, batch: &mut Batch, idx: u64) -> Result<(), Box<dyn Error>> {
+        let Some(latest) = self.block_storage.find_latest()? else {
+            return Err("No latest block".into());
+        };
+        if idx > latest.idx {
+            return Err(
+                format!("Cannot rollback to block #{}, latest: {}", idx, latest.idx).into(),
+            );
+        }
+        let finalized = self.finality_storage.find_finalized()?.unwrap_or(0);
+        if idx < finalized {
+            return Err(format!(
+                "Cannot rollback to block #{}, finalized: {}",
+                idx, finalized
+            )
+            .into());
+        }
+        for block_idx in (idx + 1..=latest.idx).rev() {
+            let Some(block) = self.block_storage.find_by_idx(block_idx)? else {
+                return Err(format!("Block #{} not found", block_idx).into());
+            };
+            let Some(undo) = self.undo_storage.find(block_idx)? else {
+                return Err(format!("Undo record for block #{} not found", block_idx).into());
+            };
+            let txs = self.tx_storage.find_by_block_idx(block_idx)?;
+            self.account_storage.stage_all(batch, &undo.accounts)?;
+            self.account_storage.stage_remove(batch, &undo.created)?;
+            self.tx_storage
+                .stage_remove(batch, &txs, block_idx, undo.prev_tx_hash)?;
+            self.undo_storage.stage_remove(batch, block_idx);
+            self.finality_storage.stage_remove(batch, block_idx);
+            self.stake_storage.stage_remove(batch, &block.hash_str());
+            self.block_storage.stage_remove(batch, &block);
         }
         Ok(())
     }
 
-    pub fn accounts(&self) -> BTreeMap<String, Account> {
-        if let Ok(stakes) = self.account_storage.load_all() {
-            stakes
-        } else {
-            BTreeMap::new()
+    /// Replaces the blocks after `ancestor` with `blocks`. The rollback and the
+    /// new blocks are committed in one batch, so a failed switch leaves the
+    /// current chain untouched.
+    pub fn switch_chain(&self, ancestor: u64, blocks: &Vec<Block>) -> Result<(), Box<dyn Error>> {
+        let guard = self.write_lock();
+        let Some(latest) = self.block_storage.find_latest()? else {
+            return Err("No latest block".into());
+        };
+        let Some(head) = blocks.last() else {
+            return Err("Empty chain".into());
+        };
+        if head.idx <= latest.idx {
+            return Err(format!(
+                "Competing chain is not longer, latest: {}, was: {}",
+                latest.idx, head.idx
+            )
+            .into());
+        }
+        for block in blocks {
+            if !block.valid() {
+                return Err(format!("Invalid block #{}", block.idx).into());
+            }
+        }
+        let mut batch = Batch::new(&self.db);
+        self.stage_rollback(&mut batch, ancestor)?;
+        for block in blocks {
+            if let Err(e) = self.stage_block(&mut batch, block) {
+                return Err(format!("Cannot switch chain: {}", e).into());
+            }
+        }
+        batch.commit()?;
+        drop(guard);
+        for block in blocks {
+            self.replay_early_votes(block);
         }
+        Ok(())
+    }
+
+    /// Records a validator vote and updates finality.
+    ///
+    /// A block is justified once votes for its hash carry at least two thirds of
+    /// the total stake, and its parent becomes finalized when the parent was
+    /// justified as well. Finalized blocks are never rolled back. Votes for
+    /// blocks past the local chain are buffered until the block is added.
+    pub fn add_vote(&self, vote: &Vote) -> Result<(), Box<dyn Error>> {
+        let _guard = self.write_lock();
+        if !vote.valid() {
+            return Err("Invalid vote signature".into());
+        }
+        let Some(block) = self.block_storage.find_by_idx(vote.idx)? else {
+            return self.buffer_early_vote(vote);
+        };
+        if block.hash_str() != vote.block_hash {
+            return Err(format!(
+                "Vote for unknown block #{}, expected: {}, was: {}",
+                vote.idx,
+                block.hash_str(),
+                vote.block_hash
+            )
+            .into());
+        }
+        let stakes = self.stakes_at(&Batch::new(&self.db), &block)?;
+        let Some(weight) = stakes.get(&vote.validator) else {
+            return Err(format!("Unknown validator {}", vote.validator).into());
+        };
+        if *weight <= BigInt::zero() {
+            return Err(format!("Validator {} has no stake", vote.validator).into());
+        }
+        let mut votes = self.finality_storage.find_votes(vote.idx)?;
+        if let Some(previous) = votes.get(&vote.validator) {
+            if previous.block_hash == vote.block_hash {
+                return Ok(());
+            }
+            return Err(format!(
+                "Conflicting vote from {} for block #{}",
+                vote.validator, vote.idx
+            )
+            .into());
+        }
+        votes.insert(vote.validator.clone(), vote.clone());
+
+        let mut batch = Batch::new(&self.db);
+        self.finality_storage
+            .stage_votes(&mut batch, vote.idx, &votes)?;
+        if self.finality_storage.find_justified(vote.idx)?.is_none()
+            && Self::supermajority(&votes, &vote.block_hash, &stakes)
+        {
+            self.finality_storage
+                .stage_justified(&mut batch, vote.idx, &vote.block_hash);
+            let previous = self.finality_storage.find_finalized()?.unwrap_or(0);
+            let mut finalized = previous;
+            if vote.idx > finalized + 1
+                && self.finality_storage.find_justified(vote.idx - 1)?
+                    == Some(block.parent_hash.clone())
+            {
+                finalized = vote.idx - 1;
+            }
+            if let Some(child) = self.block_storage.find_by_idx(vote.idx + 1)?
+                && vote.idx > finalized
+                && child.parent_hash == vote.block_hash
+                && self.finality_storage.find_justified(child.idx)? == Some(child.hash_str())
+            {
+                finalized = vote.idx;
+            }
+            self.finality_storage.stage_finalized(&mut batch, finalized);
+            for idx in previous..finalized {
+                if let Some(pruned) = self.block_storage.find_by_idx(idx)? {
+                    self.stake_storage
+                        .stage_remove(&mut batch, &pruned.hash_str());
+                }
+            }
+        }
+        batch.commit()?;
+        Ok(())
+    }
+
+    pub fn finality(&self, idx: u64) -> Result<Finality, Box<dyn Error>> {
+        let Some(block) = self.block_storage.find_by_idx(idx)? else {
+            return Ok(Finality::default());
+        };
+        let finalized = self.finality_storage.find_finalized()?.unwrap_or(0);
+        let justified = self.finality_storage.find_justified(idx)? == Some(block.hash_str());
+        Ok(Finality {
+            justified: justified || idx <= finalized,
+            finalized: idx <= finalized,
+        })
+    }
+
+    pub fn find_finalized_idx(&self) -> Result<u64, Box<dyn Error>> {
+        Ok(self.finality_storage.find_finalized()?.unwrap_or(0))
+    }
+
+    fn supermajority(votes: &BTreeMap<String, Vote>, block_hash: &str, stakes: &Stakes) -> bool {
+        let total = Self::total_stake(stakes);
+        if total <= BigInt::zero() {
+            return false;
+        }
+        let mut voted = BigInt::zero();
+        for vote in votes.values() {
+            if vote.block_hash == block_hash
+                && let Some(weight) = stakes.get(&vote.validator)
+            {
+                voted += weight.clone();
+            }
+        }
+        voted * BigInt::from_u64(3).unwrap() >= total * BigInt::from_u64(2).unwrap()
+    }
+
+    /// Journals a transaction accepted into the mempool, replacing any
+    /// journaled one with the same sender and nonce, and deletes the ones it
+    /// dropped from the mempool.
+    pub fn save_pending_tx(
+        &self,
+        data: &TxData,
+        dropped: &[(String, u64)],
+    ) -> Result<(), Box<dyn Error>> {
+        let mut batch = Batch::new(&self.db);
+        self.mem_pool_storage.stage_remove(&mut batch, dropped);
+        self.mem_pool_storage.stage(&mut batch, data)?;
+        batch.commit()?;
+        Ok(())
+    }
+
+    /// Deletes transactions dropped from the mempool from the journal.
+    pub fn remove_pending_txs(&self, dropped: &[(String, u64)]) -> Result<(), Box<dyn Error>> {
+        if dropped.is_empty() {
+            return Ok(());
+        }
+        let mut batch = Batch::new(&self.db);
+        self.mem_pool_storage.stage_remove(&mut batch, dropped);
+        batch.commit()?;
+        Ok(())
+    }
+
+    pub fn pending_txs(&self) -> Result<Vec<TxData>, Box<dyn Error>> {
+        Ok(self.mem_pool_storage.find_all()?)
+    }
+
+    pub fn accounts(&self) -> BTreeMap<String, Account> {
+        self.account_storage.load_all().unwrap_or_default()
     }
 
     pub fn find_latest_event_hash(&self) -> String {
@@ -132,29 +485,150 @@ impl Storage {
 
     fn save_txs(
         &self,
+        batch: &mut Batch,
+        undo: &mut BlockUndo,
         txs: &Vec<Tx>,
         validator: String,
         block_idx: u64,
-    ) -> Result<bool, Box<dyn Error>> {
+    ) -> Result<Option<Vec<Account>>, Box<dyn Error>> {
+        let reward = self.staged_block_reward(batch, block_idx)?;
+        if !Self::valid_coinbase(txs, &validator, &reward) {
+            debug!("Invalid coinbase in block #{}", block_idx);
+            return Ok(None);
+        }
+        let params = self.config_storage.find()?.params;
         let mut wallets = HashSet::new();
         for tx in txs {
-            if !tx.valid() {
-                return Ok(false);
+            if tx.block != block_idx || tx.is_genesis() || !tx.valid() {
+                debug!("Invalid tx {} in block #{}", tx.hash_str(), block_idx);
+                return Ok(None);
+            }
+            if tx.data.chain_id != params.chain_id {
+                debug!("Invalid chain id of tx: {}", tx.hash_str());
+                return Ok(None);
+            }
+            if !tx.data.valid_at(block_idx) {
+                debug!("Tx {} not valid at block #{}", tx.hash_str(), block_idx);
+                return Ok(None);
             }
             wallets.insert(tx.from());
-            wallets.insert(tx.to());
+            wallets.extend(tx.payload().touched_accounts());
+        }
+        wallets.insert(validator.clone());
+        if let Some(account) = self.account_storage.find_staged(batch, validator.clone())? {
+            wallets.extend(account.delegators.into_keys());
         }
-        let mut accounts = self.account_storage.find_all(&wallets)?;
         for tx in txs {
-            if let Err(err) = process_tx(validator.clone(), &tx, &mut accounts) {
+            if let Payload::Evidence { evidence } = tx.payload()
+                && let Some(offender) = self
+                    .account_storage
+                    .find_staged(batch, evidence.validator())?
+            {
+                wallets.extend(offender.delegators.into_keys());
+                wallets.extend(offender.undelegating.into_keys());
+            }
+        }
+        let mut accounts = self.account_storage.find_all_staged(batch, &wallets)?;
+        let existing: HashSet<String> = accounts.keys().cloned().collect();
+        undo.accounts = accounts.values().cloned().collect();
+        for tx in txs {
+            if let Err(err) = process_tx(validator.clone(), tx, block_idx, &mut accounts, &params) {
                 debug!("Invalid tx: {}", err);
-                return Ok(false);
+                return Ok(None);
             }
         }
+        undo.created = accounts
+            .keys()
+            .filter(|wallet| !existing.contains(*wallet))
+            .cloned()
+            .collect();
         let accounts: Vec<Account> = accounts.into_values().collect();
-        self.account_storage.save_all(&accounts)?;
-        self.tx_storage.save(txs, block_idx)?;
-        Ok(true)
+        self.account_storage.stage_all(batch, &accounts)?;
+        self.tx_storage.stage(batch, txs, block_idx)?;
+        Ok(Some(accounts))
+    }
+
+    fn valid_coinbase(txs: &[Tx], validator: &str, reward: &Amount) -> bool {
+        let coinbase: Vec<&Tx> = txs.iter().filter(|tx| tx.is_coinbase()).collect();
+        if *reward == Amount::zero() {
+            return coinbase.is_empty();
+        }
+        match (coinbase.as_slice(), txs.last()) {
+            ([coinbase], Some(last)) => {
+                coinbase.hash == last.hash
+                    && coinbase.to().as_deref() == Some(validator)
+                    && coinbase.amount() == *reward
+                    && coinbase.fee() == Amount::zero()
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the reward minted to the proposer of block `idx` under the
+    /// genesis inflation schedule, based on the current total stake.
+    pub fn block_reward(&self, idx: u64) -> Result<Amount, Box<dyn Error>> {
+        self.staged_block_reward(&Batch::new(&self.db), idx)
+    }
+
+    fn staged_block_reward(&self, batch: &Batch, idx: u64) -> Result<Amount, Box<dyn Error>> {
+        let config = self.config_storage.find()?;
+        let stakes = match self.block_storage.find_staged_latest(batch)? {
+            Some(latest) => self.stakes_at(batch, &latest)?,
+            None => StakeStorage::weights(self.account_storage.load_all()?.into_values()),
+        };
+        let reward = config.inflation.reward(idx, &Self::total_stake(&stakes));
+        Ok(reward.ok_or("Block reward overflow")?)
+    }
+
+    pub fn chain_config(&self) -> Result<ChainConfig, Box<dyn Error>> {
+        Ok(self.config_storage.find()?)
+    }
+
+    pub fn supply(&self) -> Result<Supply, Box<dyn Error>> {
+        let mut circulating = Amount::zero();
+        let mut staked = BigInt::zero();
+        let mut vesting = Amount::zero();
+        for account in self.account_storage.load_all()?.into_values() {
+            vesting = account
+                .unreleased_vesting()
+                .and_then(|unreleased| vesting.checked_add(unreleased))
+                .ok_or("Supply overflow")?;
+            staked += account.stake() + account.bonded() + account.unbonding_amount();
+            circulating = account
+                .locked_amount()
+                .and_then(|locked| circulating.checked_add(locked))
+                .and_then(|circulating| circulating.checked_add(account.balance))
+                .ok_or("Supply overflow")?;
+        }
+        let staked = Amount::from_bigint(&staked).ok_or("Supply overflow")?;
+        let total = circulating
+            .checked_add(staked)
+            .and_then(|total| total.checked_add(vesting))
+            .ok_or("Supply overflow")?;
+        Ok(Supply {
+            total,
+            circulating,
+            staked,
+            vesting,
+        })
+    }
+
+    pub fn find_asset(&self, id: &str) -> Result<Option<Asset>, Box<dyn Error>> {
+        let Some((issuer, symbol)) = Asset::parse_id(id) else {
+            return Ok(None);
+        };
+        let issuer = self.account_storage.find(issuer.to_string())?;
+        Ok(issuer.and_then(|mut account| account.issued.remove(symbol)))
+    }
+
+    pub fn asset_holders(&self, id: &str) -> Result<BTreeMap<String, Amount>, Box<dyn Error>> {
+        let mut holders = BTreeMap::new();
+        for (wallet, account) in self.account_storage.load_all()? {
+            if let Some(balance) = account.assets.get(id) {
+                holders.insert(wallet, *balance);
+            }
+        }
+        Ok(holders)
     }
 
     pub fn find_block_by_idx(&self, idx: u64) -> Result<Option<Block>, Box<dyn Error>> {
@@ -177,42 +651,249 @@ impl Storage {
         }
     }
 
-    pub fn current_validator(&self) -> Result<String, Box<dyn Error>> {
-        if let Some(block) = self.block_storage.find_latest()? {
-            let hash = block.hash();
-            let hash = Self::hash_to_int(hash);
-            let stakes = self.account_storage.load_all()?;
-            let index = BigInt::from_u64(hash).unwrap() % Self::total_stake(&stakes);
-            let mut latest = BigInt::zero();
-            for (_, stake) in stakes {
-                if stake.stake() + latest.clone() > index {
-                    return Ok(stake.wallet());
+    pub fn is_eligible(
+        &self,
+        validator: &str,
+        vrf_output: [u8; 32],
+    ) -> Result<bool, Box<dyn Error>> {
+        let stakes = StakeStorage::weights(self.account_storage.load_all()?.into_values());
+        Ok(Self::eligible(validator, vrf_output, &stakes))
+    }
+
+    pub fn can_propose(
+        &self,
+        validator: &str,
+        parent: &Block,
+        slot: u64,
+        vrf_output: [u8; 32],
+    ) -> Result<bool, Box<dyn Error>> {
+        self.staged_can_propose(&Batch::new(&self.db), validator, parent, slot, vrf_output)
+    }
+
+    fn staged_can_propose(
+        &self,
+        batch: &Batch,
+        validator: &str,
+        parent: &Block,
+        slot: u64,
+        vrf_output: [u8; 32],
+    ) -> Result<bool, Box<dyn Error>> {
+        let stakes = self.stakes_at(batch, parent)?;
+        if Self::eligible(validator, vrf_output, &stakes) {
+            return Ok(true);
+        }
+        let backup = Self::backup_proposer(&parent.hash_str(), parent.slot, slot, &stakes);
+        Ok(backup.as_deref() == Some(validator))
+    }
+
+    /// Returns the stake weights as left by `parent`. Blocks without a
+    /// snapshot, stored before snapshots were kept or pruned once finalized,
+    /// fall back to the current accounts.
+    fn stakes_at(&self, batch: &Batch, parent: &Block) -> Result<Stakes, Box<dyn Error>> {
+        match self.stake_storage.find(batch, &parent.hash_str())? {
+            Some(stakes) => Ok(stakes),
+            None => Ok(StakeStorage::weights(
+                self.account_storage.load_all()?.into_values(),
+            )),
+        }
+    }
+
+    /// Returns the backup proposer allowed to fill `slot` after the parent block.
+    ///
+    /// Backups only step in once at least one slot after the parent went empty.
+    /// Every further empty slot hands the turn to the next validator of the
+    /// parent's backup ranking, so the chain keeps moving while any staked
+    /// validator is online.
+    pub fn backup_proposer(
+        parent_hash: &str,
+        parent_slot: u64,
+        slot: u64,
+        stakes: &Stakes,
+    ) -> Option<String> {
+        if slot < parent_slot + 2 {
+            return None;
+        }
+        let ranking = Self::backup_ranking(parent_hash, stakes);
+        if ranking.is_empty() {
+            return None;
+        }
+        let rank = (slot - parent_slot - 2) % ranking.len() as u64;
+        Some(ranking[rank as usize].clone())
+    }
+
+    pub fn backup_ranking(parent_hash: &str, stakes: &Stakes) -> Vec<String> {
+        let mut stakes = stakes.clone();
+        let mut ranking = Vec::new();
+        loop {
+            let mut hasher = sha2::Sha256::new();
+            hasher.update(parent_hash.as_bytes());
+            hasher.update((ranking.len() as u64).to_be_bytes());
+            match Self::select_validator(hasher.finalize().into(), &stakes) {
+                Some(validator) => {
+                    stakes.remove(&validator);
+                    ranking.push(validator);
                 }
-                latest = latest + stake.stake();
+                None => return ranking,
             }
         }
-        Err(std::io::Error::new(std::io::ErrorKind::Other, "No latest block").into())
     }
 
-    pub fn find_wallet_txs(&self, wallet: String) -> Vec<Tx> {
-        if let Ok(txs) = self.tx_storage.find_wallet_txs(wallet) {
-            txs
+    pub fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default()
+    }
+
+    /// Checks whether `validator` may propose with the given VRF output.
+    ///
+    /// The output is read as an unsigned big-endian integer and compared with
+    /// `2^256 * weight / total_weight`, so each validator wins a slot with a
+    /// probability proportional to its own and delegated stake, and only it
+    /// knows whether it won until the proof is published.
+    pub fn eligible(validator: &str, vrf_output: [u8; 32], stakes: &Stakes) -> bool {
+        let total = Self::total_stake(stakes);
+        let Some(weight) = stakes.get(validator) else {
+            return false;
+        };
+        if total <= BigInt::zero() || *weight <= BigInt::zero() {
+            return false;
+        }
+        BigInt::from_bytes_be(&vrf_output) * total < weight.clone() << 256
+    }
+
+    /// Selects the proposer of the block following the block with `hash`.
+    ///
+    /// The 32 bytes of the SHA-256 block hash are read as an unsigned big-endian
+    /// integer and reduced modulo the total weight. Accounts are then walked in
+    /// ascending byte order of their wallet address while summing weights, and
+    /// the first account whose running sum exceeds the reduced value is selected.
+    /// Accounts without stake of their own are never selected.
+    pub fn select_validator(hash: [u8; 32], stakes: &Stakes) -> Option<String> {
+        let total = Self::total_stake(stakes);
+        if total <= BigInt::zero() {
+            return None;
+        }
+        let index = BigInt::from_bytes_be(&hash) % total;
+        let mut cumulative = BigInt::zero();
+        for (wallet, weight) in stakes {
+            cumulative += weight.clone();
+            if cumulative > index {
+                return Some(wallet.clone());
+            }
+        }
+        None
+    }
+
+    /// Tells whether a block that could not be added is worth syncing a
+    /// competing chain for: it must extend past the local chain from an unknown
+    /// parent, be validly signed and come from a proposer. Without its parent
+    /// the proposer is checked against the latest stake.
+    pub fn needs_sync(&self, block: &Block) -> Result<bool, Box<dyn Error>> {
+        let Some(latest) = self.block_storage.find_latest()? else {
+            return Ok(false);
+        };
+        if block.idx <= latest.idx {
+            return Ok(false);
+        }
+        if let Some(parent) = self.block_storage.find_by_idx(block.idx - 1)?
+            && parent.hash_str() == block.parent_hash
+        {
+            return Ok(false);
+        }
+        if block.chain_id != self.config_storage.find()?.params.chain_id || !block.valid() {
+            return Ok(false);
+        }
+        let Some(output) = block.vrf_output() else {
+            return Ok(false);
+        };
+        if let Some(parent) = self.block_storage.find_by_hash(block.parent_hash.clone())? {
+            return self.can_propose(&block.validator, &parent, block.slot, output);
+        }
+        let stakes = self.stakes_at(&Batch::new(&self.db), &latest)?;
+        Ok(stakes.contains_key(&block.validator))
+    }
+
+    pub fn find_equivocation(&self, block: &Block) -> Result<Option<Evidence>, Box<dyn Error>> {
+        let Some(local) = self.block_storage.find_by_idx(block.idx)? else {
+            return Ok(None);
+        };
+        let evidence = Evidence::new(local.header(), block.header());
+        if evidence.valid() {
+            Ok(Some(evidence))
         } else {
-            Vec::new()
+            Ok(None)
         }
     }
 
-    fn total_stake(stakes: &BTreeMap<String, Account>) -> BigInt {
-        let mut total = BigInt::from_str("0").unwrap();
-        for (_, stake) in stakes {
-            total += stake.stake()
+    pub fn find_wallet_txs(&self, wallet: String) -> Vec<Tx> {
+        self.tx_storage.find_wallet_txs(wallet).unwrap_or_default()
+    }
+
+    /// Keeps a vote for a block not stored yet, if it comes from a validator
+    /// staked at the latest block and is at most `EARLY_VOTE_WINDOW` ahead.
+    fn buffer_early_vote(&self, vote: &Vote) -> Result<(), Box<dyn Error>> {
+        let latest = match self.block_storage.find_latest()? {
+            Some(latest) if vote.idx > latest.idx && vote.idx <= latest.idx + EARLY_VOTE_WINDOW => {
+                latest
+            }
+            _ => return Err(format!("Block #{} not found", vote.idx).into()),
+        };
+        let stakes = self.stakes_at(&Batch::new(&self.db), &latest)?;
+        if stakes
+            .get(&vote.validator)
+            .is_none_or(|weight| *weight <= BigInt::zero())
+        {
+            return Err(format!("Validator {} has no stake", vote.validator).into());
+        }
+        let mut early_votes = self
+            .early_votes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        early_votes.retain(|_, votes| votes.iter().any(|vote| vote.idx > latest.idx));
+        let buffered: usize = early_votes.values().map(Vec::len).sum();
+        if buffered >= MAX_EARLY_VOTES {
+            return Err(format!("Block #{} not found", vote.idx).into());
+        }
+        let votes = early_votes.entry(vote.block_hash.clone()).or_default();
+        if !votes.contains(vote) {
+            votes.push(vote.clone());
+        }
+        Ok(())
+    }
+
+    /// Counts the votes that arrived before `block`, and drops buffered votes
+    /// for other blocks at its height or below.
+    fn replay_early_votes(&self, block: &Block) {
+        let votes = {
+            let mut early_votes = self
+                .early_votes
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let votes = early_votes.remove(&block.hash_str()).unwrap_or_default();
+            early_votes.retain(|_, votes| votes.iter().any(|vote| vote.idx > block.idx));
+            votes
+        };
+        for vote in votes {
+            if let Err(e) = self.add_vote(&vote) {
+                debug!("Skipping buffered vote: {}", e);
+            }
         }
-        total
     }
 
-    fn hash_to_int(data: [u8; 32]) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        data.hash(&mut hasher);
-        hasher.finish()
+    /// Serializes writers, so a chain switch cannot interleave with blocks and
+    /// votes added by the event loop or the validator.
+    fn write_lock(&self) -> MutexGuard<'_, ()> {
+        self.write_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn total_stake(stakes: &Stakes) -> BigInt {
+        let mut total = BigInt::from_str("0").unwrap();
+        for weight in stakes.values() {
+            total += weight.clone()
+        }
+        total
     }
 }
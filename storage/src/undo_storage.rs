@@ -0,0 +1,64 @@
+use account::account::Account;
+use common::codec::{decode, encode};
+use db::batch::Batch;
+use rocksdb::{DBWithThreadMode, MultiThreaded};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlockUndo {
+    pub accounts: Vec<Account>,
+    pub created: Vec<String>,
+    pub prev_tx_hash: Option<String>,
+}
+
+impl BlockUndo {
+    pub fn new(prev_tx_hash: Option<String>) -> Self {
+        Self {
+            accounts: Vec::new(),
+            created: Vec::new(),
+            prev_tx_hash,
+        }
+    }
+}
+
+pub struct UndoStorage {
+    db: Arc<DBWithThreadMode<MultiThreaded>>,
+}
+
+impl UndoStorage {
+    pub fn new(db: &Arc<DBWithThreadMode<MultiThreaded>>) -> Self {
+        Self { db: Arc::clone(db) }
+    }
+
+    pub fn stage(
+        &self,
+        batch: &mut Batch,
+        idx: u64,
+        undo: &BlockUndo,
+    ) -> Result<(), std::io::Error> {
+        let data = encode(undo)?;
+        batch.put(self.build_key(idx), data);
+        Ok(())
+    }
+
+    pub fn stage_remove(&self, batch: &mut Batch, idx: u64) {
+        batch.delete(self.build_key(idx));
+    }
+
+    pub fn find(&self, idx: u64) -> Result<Option<BlockUndo>, std::io::Error> {
+        if let Some(data) = self
+            .db
+            .get(self.build_key(idx))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        {
+            Ok(Some(decode(&data)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn build_key(&self, idx: u64) -> String {
+        format!("undo.{}", idx)
+    }
+}
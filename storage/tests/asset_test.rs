@@ -0,0 +1,75 @@
+mod support;
+
+use account::asset::Asset;
+use common::amount::Amount;
+use support::{chained, next_block, node_with};
+use tx::payload::Payload;
+use wallet::wallet::Wallet;
+
+fn amount(value: &str) -> Amount {
+    Amount::from_str(value).unwrap()
+}
+
+#[test]
+fn mine_asset_transfers_between_holders() {
+    let validator = Wallet::new();
+    let issuer = Wallet::new();
+    let authority = Wallet::new();
+    let holder = Wallet::new();
+    let receiver = Wallet::new();
+    let (_dir, storage) = node_with(
+        "",
+        &[
+            (&validator, "50"),
+            (&issuer, "0"),
+            (&authority, "0"),
+            (&holder, "0"),
+            (&receiver, "0"),
+        ],
+    );
+    let id = Asset::build_id(&issuer.address_str(), "PTS");
+
+    let create = Payload::AssetCreate {
+        symbol: String::from("PTS"),
+        decimals: 2,
+        max_supply: amount("1000"),
+        mint_authority: authority.address_str(),
+    };
+    let mint = Payload::AssetMint {
+        asset: id.clone(),
+        to: holder.address_str(),
+        amount: amount("100"),
+    };
+    let txs = chained(&storage, vec![(&issuer, create, 1), (&authority, mint, 1)]);
+    storage
+        .add_block(&next_block(&storage, &validator, txs))
+        .unwrap();
+
+    let transfer = Payload::AssetTransfer {
+        asset: id.clone(),
+        to: receiver.address_str(),
+        amount: amount("30"),
+    };
+    let burn = Payload::AssetBurn {
+        asset: id.clone(),
+        amount: amount("20"),
+    };
+    let txs = chained(&storage, vec![(&holder, transfer, 1), (&holder, burn, 2)]);
+    storage
+        .add_block(&next_block(&storage, &validator, txs))
+        .unwrap();
+
+    let accounts = storage.accounts();
+    assert_eq!(
+        accounts[&holder.address_str()].asset_balance(&id),
+        amount("50")
+    );
+    assert_eq!(
+        accounts[&receiver.address_str()].asset_balance(&id),
+        amount("30")
+    );
+    assert_eq!(
+        storage.find_asset(&id).unwrap().unwrap().supply,
+        amount("80")
+    );
+}
@@ -0,0 +1,75 @@
+mod support;
+
+use storage::storage::Storage;
+use support::{next_block_on, node_with};
+use tempfile::TempDir;
+use tx::tx::Tx;
+use tx::tx_data::TxData;
+use wallet::wallet::Wallet;
+
+const CHAIN_ID: &str = "xchg-testnet";
+
+#[test]
+fn genesis_fixes_chain_id() {
+    let wallet = Wallet::new();
+    let (_dir, storage) = node(&wallet);
+    let config = storage.chain_config().unwrap();
+    assert_eq!(config.params.chain_id, CHAIN_ID);
+    let genesis = storage.find_latest_block().unwrap();
+    assert_eq!(genesis.chain_id, CHAIN_ID);
+}
+
+#[test]
+fn genesis_without_chain_id_derives_it() {
+    let wallet = Wallet::new();
+    let (_dir, storage) = node_with("", &[(&wallet, "50")]);
+    let (_other_dir, other) = node_with("", &[(&Wallet::new(), "50")]);
+    let chain_id = storage.chain_config().unwrap().params.chain_id;
+    assert!(!chain_id.is_empty());
+    assert_eq!(storage.find_latest_block().unwrap().chain_id, chain_id);
+    assert_ne!(other.chain_config().unwrap().params.chain_id, chain_id);
+}
+
+#[test]
+fn reject_block_from_other_chain() {
+    let wallet = Wallet::new();
+    let (_dir, storage) = node(&wallet);
+
+    let block = next_block_on(&storage, &wallet, "xchg-mainnet", vec![]);
+    let err = storage.add_block(&block).unwrap_err();
+    assert!(err.to_string().starts_with("Invalid chain id"));
+
+    let block = next_block_on(&storage, &wallet, CHAIN_ID, vec![]);
+    storage.add_block(&block).unwrap();
+}
+
+#[test]
+fn reject_tx_replayed_from_other_chain() {
+    let wallet = Wallet::new();
+    let (_dir, storage) = node(&wallet);
+    let transfer = |chain_id: &str| {
+        let data = TxData::new(
+            &wallet,
+            String::from(chain_id),
+            Wallet::new().address_str(),
+            String::from("10"),
+            String::from("0"),
+            2,
+        )
+        .unwrap();
+        Tx::from_tx(data, storage.find_latest_event_hash(), 1)
+    };
+
+    let block = next_block_on(&storage, &wallet, CHAIN_ID, vec![transfer("xchg-mainnet")]);
+    assert!(storage.add_block(&block).is_err());
+
+    let block = next_block_on(&storage, &wallet, CHAIN_ID, vec![transfer(CHAIN_ID)]);
+    storage.add_block(&block).unwrap();
+}
+
+fn node(wallet: &Wallet) -> (TempDir, Storage) {
+    node_with(
+        &format!("\"chain_id\": \"{}\"", CHAIN_ID),
+        &[(wallet, "50")],
+    )
+}
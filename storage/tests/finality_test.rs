@@ -0,0 +1,215 @@
+mod support;
+
+use block::block::Block;
+use block::vote::Vote;
+use db::batch::Batch;
+use storage::finality_storage::Finality;
+use storage::stake_storage::StakeStorage;
+use support::{next_proposal, node_with, propose_block};
+use wallet::wallet::Wallet;
+
+const JUSTIFIED: Finality = Finality {
+    justified: true,
+    finalized: false,
+};
+const FINALIZED: Finality = Finality {
+    justified: true,
+    finalized: true,
+};
+
+#[test]
+fn finalize_with_two_thirds_of_stake() {
+    let wallets = [Wallet::new(), Wallet::new(), Wallet::new()];
+    let (_dir, storage) = node_with(
+        "",
+        &[
+            (&wallets[0], "50"),
+            (&wallets[1], "30"),
+            (&wallets[2], "20"),
+        ],
+    );
+    let first = propose_block(&storage, &wallets.each_ref());
+    let second = propose_block(&storage, &wallets.each_ref());
+
+    storage
+        .add_vote(&Vote::new(&wallets[1], &first).unwrap())
+        .unwrap();
+    storage
+        .add_vote(&Vote::new(&wallets[2], &first).unwrap())
+        .unwrap();
+    assert_eq!(storage.finality(1).unwrap(), Finality::default());
+
+    storage
+        .add_vote(&Vote::new(&wallets[0], &first).unwrap())
+        .unwrap();
+    assert_eq!(storage.finality(1).unwrap(), JUSTIFIED);
+
+    storage
+        .add_vote(&Vote::new(&wallets[1], &second).unwrap())
+        .unwrap();
+    storage
+        .add_vote(&Vote::new(&wallets[2], &second).unwrap())
+        .unwrap();
+    assert_eq!(storage.finality(2).unwrap(), Finality::default());
+    storage
+        .add_vote(&Vote::new(&wallets[0], &second).unwrap())
+        .unwrap();
+    assert_eq!(storage.finality(1).unwrap(), FINALIZED);
+    assert_eq!(storage.finality(2).unwrap(), JUSTIFIED);
+    assert_eq!(storage.find_finalized_idx().unwrap(), 1);
+
+    assert!(storage.rollback_to(0).is_err());
+    assert!(storage.rollback_to(1).is_ok());
+    assert_eq!(storage.finality(1).unwrap(), FINALIZED);
+}
+
+#[test]
+fn prune_stake_snapshots_below_finality() {
+    let wallets = [Wallet::new(), Wallet::new()];
+    let (dir, storage) = node_with("", &[(&wallets[0], "50"), (&wallets[1], "50")]);
+    let genesis = storage.find_block_by_idx(0).unwrap().unwrap();
+    let first = propose_block(&storage, &wallets.each_ref());
+    let second = propose_block(&storage, &wallets.each_ref());
+    for block in [&first, &second] {
+        for wallet in &wallets {
+            storage
+                .add_vote(&Vote::new(wallet, block).unwrap())
+                .unwrap();
+        }
+    }
+    assert_eq!(storage.find_finalized_idx().unwrap(), 1);
+    drop(storage);
+
+    let db = db::open(dir.path()).unwrap();
+    let batch = Batch::new(&db);
+    assert_eq!(
+        StakeStorage.find(&batch, &genesis.hash_str()).unwrap(),
+        None
+    );
+    for block in [&first, &second] {
+        let stakes = StakeStorage
+            .find(&batch, &block.hash_str())
+            .unwrap()
+            .unwrap();
+        assert_eq!(stakes.len(), 2);
+    }
+}
+
+#[test]
+fn finalize_parent_when_votes_arrive_out_of_order() {
+    let wallets = [Wallet::new(), Wallet::new(), Wallet::new()];
+    let (_dir, storage) = node_with(
+        "",
+        &[
+            (&wallets[0], "50"),
+            (&wallets[1], "30"),
+            (&wallets[2], "20"),
+        ],
+    );
+    let first = propose_block(&storage, &wallets.each_ref());
+    let second = propose_block(&storage, &wallets.each_ref());
+    for wallet in &wallets[..2] {
+        storage
+            .add_vote(&Vote::new(wallet, &second).unwrap())
+            .unwrap();
+    }
+    assert_eq!(storage.finality(1).unwrap(), Finality::default());
+    for wallet in &wallets[..2] {
+        storage
+            .add_vote(&Vote::new(wallet, &first).unwrap())
+            .unwrap();
+    }
+    assert_eq!(storage.finality(1).unwrap(), FINALIZED);
+}
+
+#[test]
+fn count_votes_that_arrive_before_their_block() {
+    let wallets = [Wallet::new(), Wallet::new(), Wallet::new()];
+    let (_dir, storage) = node_with(
+        "",
+        &[
+            (&wallets[0], "50"),
+            (&wallets[1], "30"),
+            (&wallets[2], "20"),
+        ],
+    );
+    let first = propose_block(&storage, &wallets.each_ref());
+    let second = next_proposal(&storage, &wallets.each_ref());
+    for wallet in &wallets[..2] {
+        storage
+            .add_vote(&Vote::new(wallet, &first).unwrap())
+            .unwrap();
+        storage
+            .add_vote(&Vote::new(wallet, &second).unwrap())
+            .unwrap();
+    }
+    assert_eq!(storage.finality(1).unwrap(), JUSTIFIED);
+    assert_eq!(storage.finality(2).unwrap(), Finality::default());
+
+    storage.add_block(&second).unwrap();
+    assert_eq!(storage.finality(1).unwrap(), FINALIZED);
+    assert_eq!(storage.finality(2).unwrap(), JUSTIFIED);
+}
+
+#[test]
+fn buffer_early_votes_only_from_stakers_near_the_tip() {
+    let wallets = [Wallet::new(), Wallet::new()];
+    let (_dir, storage) = node_with("", &[(&wallets[0], "50"), (&wallets[1], "50")]);
+    let ahead = |idx| {
+        Block::new(
+            &wallets[0],
+            String::default(),
+            idx,
+            String::default(),
+            0,
+            vec![],
+        )
+        .unwrap()
+    };
+
+    let outsider = Wallet::new();
+    assert!(
+        storage
+            .add_vote(&Vote::new(&outsider, &ahead(1)).unwrap())
+            .is_err()
+    );
+    assert!(
+        storage
+            .add_vote(&Vote::new(&wallets[0], &ahead(1_000_000)).unwrap())
+            .is_err()
+    );
+    storage
+        .add_vote(&Vote::new(&wallets[0], &ahead(64)).unwrap())
+        .unwrap();
+}
+
+#[test]
+fn reject_invalid_votes() {
+    let wallets = [Wallet::new(), Wallet::new()];
+    let (_dir, storage) = node_with("", &[(&wallets[0], "50"), (&wallets[1], "50")]);
+    let block = propose_block(&storage, &wallets.each_ref());
+
+    let outsider = Wallet::new();
+    assert!(
+        storage
+            .add_vote(&Vote::new(&outsider, &block).unwrap())
+            .is_err()
+    );
+
+    let mut forged = Vote::new(&wallets[0], &block).unwrap();
+    forged.validator = wallets[1].address_str();
+    assert!(storage.add_vote(&forged).is_err());
+
+    let mut unknown = block.clone();
+    unknown.merkle_root = String::from("unknown");
+    assert!(
+        storage
+            .add_vote(&Vote::new(&wallets[0], &unknown).unwrap())
+            .is_err()
+    );
+
+    let vote = Vote::new(&wallets[0], &block).unwrap();
+    storage.add_vote(&vote).unwrap();
+    storage.add_vote(&vote).unwrap();
+    assert_eq!(storage.finality(1).unwrap(), Finality::default());
+}
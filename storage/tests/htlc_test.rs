@@ -0,0 +1,111 @@
+mod support;
+
+use common::amount::Amount;
+use operation::htlc::htlc_id;
+use storage::storage::Storage;
+use support::{chain_id, chained, next_block, node_with};
+use tempfile::TempDir;
+use tx::payload::Payload;
+use tx::tx::Tx;
+use tx::tx_data::TxData;
+use wallet::wallet::Wallet;
+
+/// SHA-256 of the 32 zero bytes in `PREIMAGE`.
+const HASHLOCK: &str = "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925";
+const PREIMAGE: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Mines a contract locking 10 from `sender` to `recipient` until block #3.
+fn locked(validator: &Wallet, sender: &Wallet, recipient: &Wallet) -> (TempDir, Storage, String) {
+    let (dir, storage) = node_with("", &[(validator, "50"), (sender, "0"), (recipient, "0")]);
+    let create = Payload::HtlcCreate {
+        to: recipient.address_str(),
+        amount: Amount::from_str("10").unwrap(),
+        hashlock: String::from(HASHLOCK),
+        timeout: 3,
+    };
+    let txs = chained(&storage, vec![(sender, create, 1)]);
+    let id = htlc_id(&txs[0]);
+    storage
+        .add_block(&next_block(&storage, validator, txs))
+        .unwrap();
+    (dir, storage, id)
+}
+
+fn balance(storage: &Storage, wallet: &Wallet) -> String {
+    storage.accounts()[&wallet.address_str()]
+        .balance
+        .to_string()
+}
+
+#[test]
+fn refund_only_from_timeout_block() {
+    let validator = Wallet::new();
+    let sender = Wallet::new();
+    let recipient = Wallet::new();
+    let (_dir, storage, id) = locked(&validator, &sender, &recipient);
+    let refund = Payload::HtlcRefund { id };
+
+    let txs = chained(&storage, vec![(&sender, refund.clone(), 2)]);
+    assert!(
+        storage
+            .add_block(&next_block(&storage, &validator, txs))
+            .is_err()
+    );
+    let data = TxData::with_payload(
+        &sender,
+        chain_id(&storage),
+        refund.clone(),
+        String::from("0"),
+        2,
+    )
+    .unwrap();
+    let stamped = Tx::from_tx(data, storage.find_latest_event_hash(), 3);
+    let block = next_block(&storage, &validator, vec![stamped]);
+    assert!(storage.add_block(&block).is_err());
+    assert_eq!(balance(&storage, &sender), "90");
+
+    storage
+        .add_block(&next_block(&storage, &validator, vec![]))
+        .unwrap();
+    let txs = chained(&storage, vec![(&sender, refund, 2)]);
+    storage
+        .add_block(&next_block(&storage, &validator, txs))
+        .unwrap();
+    assert_eq!(balance(&storage, &sender), "100");
+}
+
+#[test]
+fn claim_only_before_timeout_block() {
+    let validator = Wallet::new();
+    let sender = Wallet::new();
+    let recipient = Wallet::new();
+    let (_dir, storage, id) = locked(&validator, &sender, &recipient);
+    let claim = Payload::HtlcClaim {
+        sender: sender.address_str(),
+        id,
+        preimage: String::from(PREIMAGE),
+    };
+
+    storage
+        .add_block(&next_block(&storage, &validator, vec![]))
+        .unwrap();
+    let txs = chained(&storage, vec![(&recipient, claim.clone(), 1)]);
+    assert!(
+        storage
+            .add_block(&next_block(&storage, &validator, txs))
+            .is_err()
+    );
+    assert_eq!(balance(&storage, &recipient), "100");
+
+    let (_dir, storage, id) = locked(&validator, &sender, &recipient);
+    let claim = Payload::HtlcClaim {
+        sender: sender.address_str(),
+        id,
+        preimage: String::from(PREIMAGE),
+    };
+    let txs = chained(&storage, vec![(&recipient, claim, 1)]);
+    storage
+        .add_block(&next_block(&storage, &validator, txs))
+        .unwrap();
+    assert_eq!(balance(&storage, &recipient), "110");
+}
@@ -0,0 +1,97 @@
+mod support;
+
+use block::block::Block;
+use common::amount::Amount;
+use common::biginteger::BigInt;
+use storage::inflation::Inflation;
+use storage::storage::Storage;
+use support::{chain_id, node_with};
+use tempfile::TempDir;
+use tx::tx::Tx;
+use tx::tx_data::TxData;
+use wallet::wallet::Wallet;
+
+#[test]
+fn reward_schedules() {
+    let stake = BigInt::from_u64(1000).unwrap();
+    let fixed = Inflation::Fixed {
+        reward: amount("10"),
+    };
+    assert_eq!(fixed.reward(0, &stake).unwrap(), Amount::zero());
+    assert_eq!(fixed.reward(5, &stake).unwrap(), amount("10"));
+
+    let decaying = Inflation::Decaying {
+        reward: amount("8"),
+        halving_interval: 10,
+    };
+    assert_eq!(decaying.reward(9, &stake).unwrap(), amount("8"));
+    assert_eq!(decaying.reward(10, &stake).unwrap(), amount("4"));
+    assert_eq!(decaying.reward(25, &stake).unwrap(), amount("2"));
+    assert_eq!(decaying.reward(10_000, &stake).unwrap(), Amount::zero());
+
+    let proportional = Inflation::StakeProportional {
+        rate: amount("0.001"),
+    };
+    assert_eq!(proportional.reward(1, &stake).unwrap(), amount("1"));
+    assert_eq!(Inflation::None.reward(1, &stake).unwrap(), Amount::zero());
+}
+
+#[test]
+fn coinbase_mints_block_reward() {
+    let wallet = Wallet::new();
+    let (_dir, storage) = node(&wallet, "{\"type\": \"fixed\", \"reward\": \"5\"}");
+    assert_eq!(storage.supply().unwrap().total, amount("100"));
+
+    let reward = storage.block_reward(1).unwrap();
+    assert_eq!(reward, amount("5"));
+    let block = next_block(&storage, &wallet, Some(reward));
+    storage.add_block(&block).unwrap();
+
+    let supply = storage.supply().unwrap();
+    assert_eq!(supply.total, amount("105"));
+    assert_eq!(supply.circulating, amount("55"));
+    assert_eq!(supply.staked, amount("50"));
+
+    storage.rollback_to(0).unwrap();
+    assert_eq!(storage.supply().unwrap().total, amount("100"));
+}
+
+#[test]
+fn reject_invalid_coinbase() {
+    let wallet = Wallet::new();
+    let (_dir, storage) = node(&wallet, "{\"type\": \"fixed\", \"reward\": \"5\"}");
+
+    let missing = next_block(&storage, &wallet, None);
+    assert!(storage.add_block(&missing).is_err());
+    let inflated = next_block(&storage, &wallet, Some(amount("6")));
+    assert!(storage.add_block(&inflated).is_err());
+    assert_eq!(storage.supply().unwrap().total, amount("100"));
+}
+
+#[test]
+fn reject_coinbase_without_inflation() {
+    let wallet = Wallet::new();
+    let (_dir, storage) = node(&wallet, "{\"type\": \"none\"}");
+    assert_eq!(storage.block_reward(1).unwrap(), Amount::zero());
+
+    let block = next_block(&storage, &wallet, Some(amount("5")));
+    assert!(storage.add_block(&block).is_err());
+}
+
+fn amount(value: &str) -> Amount {
+    Amount::from_str(value).unwrap()
+}
+
+fn next_block(storage: &Storage, wallet: &Wallet, reward: Option<Amount>) -> Block {
+    let idx = storage.find_latest_block().unwrap().idx + 1;
+    let mut txs = Vec::new();
+    if let Some(reward) = reward {
+        let coinbase = TxData::coinbase(chain_id(storage), wallet.address_str(), reward, idx);
+        txs.push(Tx::from_tx(coinbase, storage.find_latest_event_hash(), idx));
+    }
+    support::next_block(storage, wallet, txs)
+}
+
+fn node(wallet: &Wallet, inflation: &str) -> (TempDir, Storage) {
+    node_with(&format!("\"inflation\": {}", inflation), &[(wallet, "50")])
+}
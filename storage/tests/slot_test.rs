@@ -0,0 +1,109 @@
+mod support;
+
+use block::block::{Block, SLOT_DURATION};
+use common::amount::Amount;
+use storage::storage::Storage;
+use support::{can_propose, chain_id, next_block, node, node_with};
+use tempfile::TempDir;
+use tx::payload::Payload;
+use tx::tx::Tx;
+use tx::tx_data::TxData;
+use wallet::wallet::Wallet;
+
+const SLOTS: u64 = 120;
+
+/// Both nodes are storages fed the same blocks in this process; networking is
+/// not exercised.
+#[test]
+fn chain_progresses_with_offline_validator() {
+    let wallets = [Wallet::new(), Wallet::new(), Wallet::new()];
+    let (online, offline) = wallets.split_at(2);
+    let nodes: Vec<(TempDir, Storage)> = online.iter().map(|_| node(&wallets.each_ref())).collect();
+
+    let first_slot = Storage::now() / SLOT_DURATION - SLOTS;
+    for slot in first_slot..first_slot + SLOTS {
+        for (wallet, (_, storage)) in online.iter().zip(&nodes) {
+            let parent = storage.find_latest_block().unwrap();
+            if !can_propose(storage, wallet, &parent, slot) {
+                continue;
+            }
+            let block = Block::new(
+                wallet,
+                chain_id(storage),
+                parent.idx + 1,
+                parent.hash_str(),
+                slot * SLOT_DURATION,
+                vec![],
+            )
+            .unwrap();
+            for (_, storage) in &nodes {
+                storage.add_block(&block).unwrap();
+            }
+            break;
+        }
+    }
+
+    let head = nodes[0].1.find_latest_block().unwrap();
+    assert_eq!(head, nodes[1].1.find_latest_block().unwrap());
+    assert!(head.idx >= SLOTS / 3, "chain height: {}", head.idx);
+    for idx in 1..=head.idx {
+        let block = nodes[0].1.find_block_by_idx(idx).unwrap().unwrap();
+        assert_ne!(block.validator, offline[0].address_str());
+    }
+}
+
+#[test]
+fn reject_block_from_wrong_proposer() {
+    let wallets = [Wallet::new(), Wallet::new(), Wallet::new()];
+    let (_dir, storage) = node(&wallets.each_ref());
+    let genesis = storage.find_latest_block().unwrap();
+    let first_slot = Storage::now() / SLOT_DURATION - SLOTS;
+    let slot = (first_slot..first_slot + SLOTS)
+        .find(|slot| !can_propose(&storage, &wallets[0], &genesis, *slot))
+        .unwrap();
+    let block = Block::new(
+        &wallets[0],
+        chain_id(&storage),
+        1,
+        genesis.hash_str(),
+        slot * SLOT_DURATION,
+        vec![],
+    )
+    .unwrap();
+    assert!(block.valid());
+    assert!(storage.add_block(&block).is_err());
+}
+
+#[test]
+fn proposer_checked_against_parent_stake() {
+    let validator = Wallet::new();
+    let newcomer = Wallet::new();
+    let (_dir, storage) = node_with("", &[(&validator, "50"), (&newcomer, "0")]);
+    let genesis = storage.find_latest_block().unwrap();
+    let stake = TxData::with_payload(
+        &newcomer,
+        chain_id(&storage),
+        Payload::Stake {
+            amount: Amount::from_str("50").unwrap(),
+        },
+        String::from("0"),
+        1,
+    )
+    .unwrap();
+    let stake = Tx::from_tx(stake, storage.find_latest_event_hash(), 1);
+    let block = next_block(&storage, &validator, vec![stake]);
+    storage.add_block(&block).unwrap();
+
+    let first_slot = Storage::now() / SLOT_DURATION - SLOTS;
+    let slots = first_slot..first_slot + SLOTS;
+    assert!(
+        slots
+            .clone()
+            .all(|slot| !can_propose(&storage, &newcomer, &genesis, slot))
+    );
+    assert!(
+        slots
+            .into_iter()
+            .any(|slot| can_propose(&storage, &newcomer, &block, slot))
+    );
+}
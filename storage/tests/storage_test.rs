@@ -1,11 +1,23 @@
-use common::bigdecimal::BigDecimal;
+use block::block::{Block, SLOT_DURATION};
+use common::amount::Amount;
+use std::env;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
 use storage::storage::Storage;
 use tempfile::{tempdir, NamedTempFile};
+use tx::tx::Tx;
+use tx::tx_data::TxData;
 use wallet::wallet::Wallet;
 
+const STORAGE_PATH_ENV: &str = "XCHG_TEST_STORAGE_PATH";
+const WALLET_SECRET_ENV: &str = "XCHG_TEST_WALLET_SECRET";
+const RECEIVER: &str = "receiver";
+const TXS_PER_BLOCK: u64 = 16;
+
 #[test]
 fn load_genesis_and_add_valid_block() {
     let temp_dir = tempdir().unwrap();
@@ -19,15 +31,36 @@ fn load_genesis_and_add_valid_block() {
 }
 
 #[test]
-fn current_validator() {
+fn only_staked_validators_are_eligible() {
     let temp_dir = tempdir().unwrap();
     let storage = Storage::new(temp_dir.path());
     let temp_file = NamedTempFile::new().unwrap();
     let wallet = Wallet::new();
     wallet_with_balance(&wallet, temp_file.path()).unwrap();
     storage.load_genesis_from_file(temp_file.path()).unwrap();
-    let validator = storage.current_validator().unwrap();
-    println!("Current validator: {:?}", validator);
+    let outsider = Wallet::new();
+    assert!(
+        storage
+            .is_eligible(&wallet.address_str(), [0xff; 32])
+            .unwrap()
+    );
+    assert!(
+        !storage
+            .is_eligible(&outsider.address_str(), [0; 32])
+            .unwrap()
+    );
+
+    let genesis = storage.find_latest_block().unwrap();
+    let block = Block::new(&outsider, chain_id(&storage), 1, genesis.hash_str(), SLOT_DURATION, vec![]).unwrap();
+    assert!(storage.add_block(&block).is_err());
+    let block = Block::new(&wallet, chain_id(&storage), 1, genesis.hash_str(), SLOT_DURATION, vec![]).unwrap();
+    assert!(storage.add_block(&block).is_ok());
+
+    let next = Block::new(&wallet, chain_id(&storage), 2, block.hash_str(), SLOT_DURATION, vec![]).unwrap();
+    assert!(storage.add_block(&next).is_err());
+    let future = Storage::now() + 10 * SLOT_DURATION;
+    let next = Block::new(&wallet, chain_id(&storage), 2, block.hash_str(), future, vec![]).unwrap();
+    assert!(storage.add_block(&next).is_err());
 }
 
 #[test]
@@ -58,11 +91,271 @@ fn check_genesis_state() {
 
     let balances = storage.accounts();
     println!("Current balances: {:?}", balances);
-    assert_eq!(balances.len(), 3);
+    assert_eq!(balances.len(), 2);
 
     let balance = balances.get(&wallet.address_str()).unwrap();
     assert_eq!(balance.nonce, 1);
-    assert_eq!(balance.balance(), BigDecimal::from_str("500000").unwrap());
+    assert_eq!(balance.balance(), Amount::from_str("500000").unwrap());
+}
+
+#[test]
+fn killed_commit_keeps_storage_consistent() {
+    let temp_dir = tempdir().unwrap();
+    let genesis_file = NamedTempFile::new().unwrap();
+    let wallet = Wallet::new();
+    wallet_with_balance(&wallet, genesis_file.path()).unwrap();
+    Storage::new(temp_dir.path())
+        .load_genesis_from_file(genesis_file.path())
+        .unwrap();
+
+    let mut latest_idx = 0;
+    for round in 0..8 {
+        let mut child = Command::new(env::current_exe().unwrap())
+            .args(["commit_blocks_until_killed", "--exact", "--ignored"])
+            .env(STORAGE_PATH_ENV, temp_dir.path())
+            .env(WALLET_SECRET_ENV, wallet.secret_str())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        thread::sleep(Duration::from_millis(200 + 50 * round));
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        let storage = Storage::new(temp_dir.path());
+        let latest = storage.find_latest_block().unwrap();
+        let accounts = storage.accounts();
+        let committed_txs = latest.idx * TXS_PER_BLOCK;
+        assert_eq!(
+            accounts.get(&wallet.address_str()).unwrap().nonce,
+            1 + committed_txs
+        );
+        if committed_txs > 0 {
+            assert_eq!(
+                accounts.get(RECEIVER).unwrap().balance(),
+                Amount::from_whole(committed_txs as u128).unwrap()
+            );
+        }
+        assert_eq!(storage.find_latest_event_hash(), latest.last_event());
+        assert_eq!(
+            storage.find_wallet_txs(RECEIVER.to_string()).len() as u64,
+            committed_txs
+        );
+        latest_idx = latest.idx;
+    }
+    assert!(latest_idx > 0, "No blocks were committed");
+}
+
+#[test]
+#[ignore]
+fn commit_blocks_until_killed() {
+    let (Ok(path), Ok(secret)) = (env::var(STORAGE_PATH_ENV), env::var(WALLET_SECRET_ENV)) else {
+        return;
+    };
+    let storage = Storage::new(Path::new(&path));
+    let wallet = Wallet::from_secret_str(secret).unwrap();
+    loop {
+        let block = next_block(&storage, &wallet, RECEIVER, TXS_PER_BLOCK);
+        storage.add_block(&block).unwrap();
+    }
+}
+
+#[test]
+fn rollback_restores_previous_state() {
+    let temp_dir = tempdir().unwrap();
+    let storage = Storage::new(temp_dir.path());
+    let genesis_file = NamedTempFile::new().unwrap();
+    let wallet = Wallet::new();
+    wallet_with_balance(&wallet, genesis_file.path()).unwrap();
+    storage.load_genesis_from_file(genesis_file.path()).unwrap();
+    let genesis_state = snapshot(&storage, &wallet);
+
+    let block = next_block(&storage, &wallet, RECEIVER, 2);
+    storage.add_block(&block).unwrap();
+    let first_state = snapshot(&storage, &wallet);
+
+    let block = next_block(&storage, &wallet, &wallet.address_str(), 3);
+    storage.add_block(&block).unwrap();
+    assert_ne!(snapshot(&storage, &wallet), first_state);
+
+    storage.rollback_to(1).unwrap();
+    assert_eq!(snapshot(&storage, &wallet), first_state);
+    assert!(storage.find_block_by_idx(2).unwrap().is_none());
+
+    storage.rollback_to(0).unwrap();
+    assert_eq!(snapshot(&storage, &wallet), genesis_state);
+    assert!(!storage.accounts().contains_key(RECEIVER));
+
+    let block = next_block(&storage, &wallet, RECEIVER, 1);
+    storage.add_block(&block).unwrap();
+    assert_eq!(storage.find_latest_block().unwrap().idx, 1);
+    assert!(storage.rollback_to(2).is_err());
+}
+
+#[test]
+fn switch_to_longer_chain() {
+    let temp_dir_1 = tempdir().unwrap();
+    let temp_dir_2 = tempdir().unwrap();
+    let storage_1 = Storage::new(temp_dir_1.path());
+    let storage_2 = Storage::new(temp_dir_2.path());
+    let genesis_file = NamedTempFile::new().unwrap();
+    let wallet = Wallet::new();
+    wallet_with_balance(&wallet, genesis_file.path()).unwrap();
+    storage_1
+        .load_genesis_from_file(genesis_file.path())
+        .unwrap();
+    storage_2
+        .load_genesis_from_file(genesis_file.path())
+        .unwrap();
+
+    let mut fork = Vec::new();
+    for _ in 0..2 {
+        let block = next_block(&storage_1, &wallet, RECEIVER, 2);
+        storage_1.add_block(&block).unwrap();
+        fork.push(storage_1.find_block_by_idx(block.idx).unwrap().unwrap());
+    }
+    let block = next_block(&storage_2, &wallet, "other", 3);
+    storage_2.add_block(&block).unwrap();
+
+    assert!(storage_2.add_block(&fork[0]).is_err());
+    assert!(storage_1.switch_chain(0, &vec![block.clone()]).is_err());
+
+    storage_2.switch_chain(0, &fork).unwrap();
+    assert_eq!(snapshot(&storage_2, &wallet), snapshot(&storage_1, &wallet));
+    assert!(!storage_2.accounts().contains_key("other"));
+    assert!(storage_2.find_wallet_txs(String::from("other")).is_empty());
+}
+
+#[test]
+fn failed_switch_keeps_current_chain() {
+    let temp_dir_1 = tempdir().unwrap();
+    let temp_dir_2 = tempdir().unwrap();
+    let storage_1 = Storage::new(temp_dir_1.path());
+    let storage_2 = Storage::new(temp_dir_2.path());
+    let genesis_file = NamedTempFile::new().unwrap();
+    let wallet = Wallet::new();
+    wallet_with_balance(&wallet, genesis_file.path()).unwrap();
+    storage_1
+        .load_genesis_from_file(genesis_file.path())
+        .unwrap();
+    storage_2
+        .load_genesis_from_file(genesis_file.path())
+        .unwrap();
+
+    let mut fork = Vec::new();
+    for _ in 0..2 {
+        let block = next_block(&storage_1, &wallet, RECEIVER, 2);
+        storage_1.add_block(&block).unwrap();
+        fork.push(storage_1.find_block_by_idx(block.idx).unwrap().unwrap());
+    }
+    let block = next_block(&storage_2, &wallet, "other", 3);
+    storage_2.add_block(&block).unwrap();
+    let current = snapshot(&storage_2, &wallet);
+
+    fork.push(fork[1].clone());
+    assert!(storage_2.switch_chain(0, &fork).is_err());
+    assert_eq!(snapshot(&storage_2, &wallet), current);
+    assert!(storage_2.accounts().contains_key("other"));
+}
+
+#[test]
+fn sync_only_for_blocks_from_unknown_parents() {
+    let temp_dir_1 = tempdir().unwrap();
+    let temp_dir_2 = tempdir().unwrap();
+    let storage_1 = Storage::new(temp_dir_1.path());
+    let storage_2 = Storage::new(temp_dir_2.path());
+    let genesis_file = NamedTempFile::new().unwrap();
+    let wallet = Wallet::new();
+    wallet_with_balance(&wallet, genesis_file.path()).unwrap();
+    storage_1
+        .load_genesis_from_file(genesis_file.path())
+        .unwrap();
+    storage_2
+        .load_genesis_from_file(genesis_file.path())
+        .unwrap();
+
+    let mut fork = Vec::new();
+    for _ in 0..3 {
+        let block = next_block(&storage_1, &wallet, RECEIVER, 1);
+        storage_1.add_block(&block).unwrap();
+        fork.push(storage_1.find_block_by_idx(block.idx).unwrap().unwrap());
+    }
+    let block = next_block(&storage_2, &wallet, "other", 1);
+    storage_2.add_block(&block).unwrap();
+
+    assert!(storage_2.needs_sync(&fork[1]).unwrap());
+    assert!(storage_2.needs_sync(&fork[2]).unwrap());
+    assert!(!storage_2.needs_sync(&fork[0]).unwrap());
+
+    let unstaked = Wallet::new();
+    let parent = &fork[1];
+    let timestamp = (parent.slot + 1) * SLOT_DURATION;
+    let outsider = Block::new(
+        &unstaked,
+        chain_id(&storage_2),
+        parent.idx + 1,
+        parent.hash_str(),
+        timestamp,
+        Vec::new(),
+    )
+    .unwrap();
+    assert!(!storage_2.needs_sync(&outsider).unwrap());
+
+    let mut forged = fork[2].clone();
+    forged.slot += 1;
+    assert!(!storage_2.needs_sync(&forged).unwrap());
+
+    let invalid = next_block(&storage_2, &wallet, "other", 1);
+    let invalid = Block::new(
+        &wallet,
+        chain_id(&storage_2),
+        invalid.idx,
+        invalid.parent_hash,
+        0,
+        invalid.txs.unwrap(),
+    )
+    .unwrap();
+    assert!(storage_2.add_block(&invalid).is_err());
+    assert!(!storage_2.needs_sync(&invalid).unwrap());
+}
+
+fn next_block(storage: &Storage, wallet: &Wallet, to: &str, txs_count: u64) -> Block {
+    let latest = storage.find_latest_block().unwrap();
+    let nonce = storage.accounts().get(&wallet.address_str()).unwrap().nonce;
+    let mut prev_hash = storage.find_latest_event_hash();
+    let mut txs = Vec::new();
+    for i in 1..=txs_count {
+        let tx_data = TxData::new(
+            wallet,
+            chain_id(storage),
+            to.to_string(),
+            String::from("1"),
+            String::from("0"),
+            nonce + i,
+        )
+        .unwrap();
+        let tx = Tx::from_tx(tx_data, prev_hash, latest.idx + 1);
+        prev_hash = tx.hash_str();
+        txs.push(tx);
+    }
+    let timestamp = (latest.slot + 1) * SLOT_DURATION;
+    Block::new(wallet, chain_id(storage), latest.idx + 1, latest.hash_str(), timestamp, txs).unwrap()
+}
+
+fn chain_id(storage: &Storage) -> String {
+    storage.chain_config().unwrap().params.chain_id
+}
+
+fn snapshot(storage: &Storage, wallet: &Wallet) -> String {
+    let latest = storage.find_latest_block().unwrap();
+    format!(
+        "{} {} {} {} {}",
+        latest.hash_str(),
+        storage.find_latest_event_hash(),
+        serde_json::to_string(&storage.accounts()).unwrap(),
+        serde_json::to_string(&storage.find_wallet_txs(wallet.address_str())).unwrap(),
+        serde_json::to_string(&storage.find_wallet_txs(RECEIVER.to_string())).unwrap(),
+    )
 }
 
 fn wallet_with_balance(wallet: &Wallet, path: &Path) -> Result<(), std::io::Error> {
@@ -72,11 +365,11 @@ fn wallet_with_balance(wallet: &Wallet, path: &Path) -> Result<(), std::io::Erro
         .truncate(true)
         .open(path)?;
     let json = format!(
-        "[{{\"from\": \"GENESIS\",\"to\": \"{}\",\"amount\": \"1000000\",\"fee\": \"0\",\"nonce\": 1,\"signature\": \"GENESIS\"}},\
-        {{\"from\": \"{}\",\"to\": \"STAKE\",\"amount\": \"500000\",\"fee\": \"0\",\"nonce\": 1,\"signature\": \"GENESIS\"}}]",
+        "[{{\"version\": 1,\"from\": \"GENESIS\",\"payload\": {{\"genesis\": {{\"to\": \"{}\",\"amount\": \"1000000\"}}}},\"fee\": \"0\",\"nonce\": 1,\"signature\": \"GENESIS\"}},\
+        {{\"version\": 1,\"from\": \"{}\",\"payload\": {{\"stake\": {{\"amount\": \"500000\"}}}},\"fee\": \"0\",\"nonce\": 1,\"signature\": \"GENESIS\"}}]",
         wallet.address_str(),
         wallet.address_str()
     );
-    file.write(json.as_bytes())?;
+    file.write_all(json.as_bytes())?;
     Ok(())
 }
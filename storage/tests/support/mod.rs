@@ -0,0 +1,125 @@
+#![allow(dead_code)]
+
+use block::block::{Block, SLOT_DURATION};
+use std::io::Write;
+use storage::storage::Storage;
+use tempfile::{NamedTempFile, TempDir, tempdir};
+use tx::payload::Payload;
+use tx::tx::Tx;
+use tx::tx_data::TxData;
+use wallet::wallet::Wallet;
+
+/// Starts a node from a genesis funding each wallet with 100 and staking the
+/// paired amount. `config` holds extra genesis fields, e.g. `"chain_id": "x"`.
+pub fn node_with(config: &str, stakes: &[(&Wallet, &str)]) -> (TempDir, Storage) {
+    let dir = tempdir().unwrap();
+    let storage = Storage::new(dir.path());
+    let mut txs = Vec::new();
+    for (wallet, stake) in stakes {
+        txs.push(format!(
+            "{{\"version\": 1,\"from\": \"GENESIS\",\"payload\": {{\"genesis\": {{\"to\": \"{}\",\"amount\": \"100\"}}}},\"fee\": \"0\",\"nonce\": 1,\"signature\": \"GENESIS\"}}",
+            wallet.address_str()
+        ));
+        if *stake != "0" {
+            txs.push(format!(
+                "{{\"version\": 1,\"from\": \"{}\",\"payload\": {{\"stake\": {{\"amount\": \"{}\"}}}},\"fee\": \"0\",\"nonce\": 1,\"signature\": \"GENESIS\"}}",
+                wallet.address_str(),
+                stake
+            ));
+        }
+    }
+    let mut fields: Vec<String> = Vec::new();
+    if !config.is_empty() {
+        fields.push(config.to_string());
+    }
+    fields.push(format!("\"txs\": [{}]", txs.join(",")));
+    let mut genesis = NamedTempFile::new().unwrap();
+    genesis
+        .write_all(format!("{{{}}}", fields.join(",")).as_bytes())
+        .unwrap();
+    storage.load_genesis_from_file(genesis.path()).unwrap();
+    (dir, storage)
+}
+
+/// Starts a node where every wallet stakes 50.
+pub fn node(wallets: &[&Wallet]) -> (TempDir, Storage) {
+    let stakes: Vec<(&Wallet, &str)> = wallets.iter().map(|wallet| (*wallet, "50")).collect();
+    node_with("", &stakes)
+}
+
+pub fn chain_id(storage: &Storage) -> String {
+    storage.chain_config().unwrap().params.chain_id
+}
+
+/// Builds a child of the latest block on the given chain, in a past slot.
+pub fn next_block_on(storage: &Storage, wallet: &Wallet, chain_id: &str, txs: Vec<Tx>) -> Block {
+    let parent = storage.find_latest_block().unwrap();
+    let slot = parent.slot.max(Storage::now() / SLOT_DURATION - 10) + 1;
+    Block::new(
+        wallet,
+        String::from(chain_id),
+        parent.idx + 1,
+        parent.hash_str(),
+        slot * SLOT_DURATION,
+        txs,
+    )
+    .unwrap()
+}
+
+/// Chains the txs after the latest stored one, as they would be mined.
+pub fn chained(storage: &Storage, payloads: Vec<(&Wallet, Payload, u64)>) -> Vec<Tx> {
+    let block = storage.find_latest_block().unwrap().idx + 1;
+    let mut prev_hash = storage.find_latest_event_hash();
+    let mut txs = Vec::new();
+    for (wallet, payload, nonce) in payloads {
+        let data =
+            TxData::with_payload(wallet, chain_id(storage), payload, String::from("0"), nonce)
+                .unwrap();
+        let tx = Tx::from_tx(data, prev_hash, block);
+        prev_hash = tx.hash_str();
+        txs.push(tx);
+    }
+    txs
+}
+
+pub fn next_block(storage: &Storage, wallet: &Wallet, txs: Vec<Tx>) -> Block {
+    next_block_on(storage, wallet, &chain_id(storage), txs)
+}
+
+pub fn can_propose(storage: &Storage, wallet: &Wallet, parent: &Block, slot: u64) -> bool {
+    let input = Block::vrf_input(&parent.hash_str(), parent.idx + 1, slot);
+    let (_, output) = wallet.vrf_prove(&input).unwrap();
+    storage
+        .can_propose(&wallet.address_str(), parent, slot, output)
+        .unwrap()
+}
+
+/// Adds an empty block from the first wallet allowed to propose in the
+/// earliest open slot.
+pub fn propose_block(storage: &Storage, wallets: &[&Wallet]) -> Block {
+    let block = next_proposal(storage, wallets);
+    storage.add_block(&block).unwrap();
+    block
+}
+
+/// Builds the next block from the first eligible wallet without adding it.
+pub fn next_proposal(storage: &Storage, wallets: &[&Wallet]) -> Block {
+    let parent = storage.find_latest_block().unwrap();
+    let mut slot = parent.slot.max(Storage::now() / SLOT_DURATION - 1000) + 1;
+    loop {
+        for wallet in wallets {
+            if can_propose(storage, wallet, &parent, slot) {
+                return Block::new(
+                    wallet,
+                    chain_id(storage),
+                    parent.idx + 1,
+                    parent.hash_str(),
+                    slot * SLOT_DURATION,
+                    vec![],
+                )
+                .unwrap();
+            }
+        }
+        slot += 1;
+    }
+}
@@ -0,0 +1,91 @@
+mod support;
+
+use common::amount::Amount;
+use std::collections::BTreeMap;
+use storage::storage::Storage;
+use support::{chain_id, next_block, node};
+use tx::payload::Payload;
+use tx::tx::Tx;
+use tx::tx_data::{GENESIS_WALLET, TX_VERSION, TxData};
+use wallet::wallet::Wallet;
+
+#[test]
+fn reject_unsigned_tx_stamped_as_genesis() {
+    let proposer = Wallet::new();
+    let victim = Wallet::new();
+    let (_dir, storage) = node(&[&proposer, &victim]);
+
+    let mut data = transfer(&storage, &proposer);
+    data.from = victim.address_str();
+    let tx = Tx::from_tx(data, storage.find_latest_event_hash(), 0);
+    let block = next_block(&storage, &proposer, vec![tx]);
+    assert!(storage.add_block(&block).is_err());
+    assert_eq!(balance(&storage, &victim), "50");
+}
+
+#[test]
+fn reject_genesis_tx_outside_genesis() {
+    let proposer = Wallet::new();
+    let (_dir, storage) = node(&[&proposer]);
+
+    let data = TxData {
+        version: TX_VERSION,
+        chain_id: chain_id(&storage),
+        from: String::from(GENESIS_WALLET),
+        payload: Payload::Genesis {
+            to: proposer.address_str(),
+            amount: Amount::from_str("1000").unwrap(),
+        },
+        fee: Amount::zero(),
+        nonce: 1,
+        valid_from_block: None,
+        valid_until_block: None,
+        memo: None,
+        multisig: None,
+        signature: String::from(GENESIS_WALLET),
+        signatures: BTreeMap::new(),
+    };
+    for block_idx in [0, 1] {
+        let tx = Tx::from_tx(data.clone(), storage.find_latest_event_hash(), block_idx);
+        let block = next_block(&storage, &proposer, vec![tx]);
+        assert!(storage.add_block(&block).is_err());
+    }
+    assert_eq!(balance(&storage, &proposer), "50");
+}
+
+#[test]
+fn reject_tx_stamped_with_other_block() {
+    let wallet = Wallet::new();
+    let (_dir, storage) = node(&[&wallet]);
+
+    for block_idx in [0, 2] {
+        let data = transfer(&storage, &wallet);
+        let tx = Tx::from_tx(data, storage.find_latest_event_hash(), block_idx);
+        let block = next_block(&storage, &wallet, vec![tx]);
+        assert!(storage.add_block(&block).is_err());
+    }
+
+    let data = transfer(&storage, &wallet);
+    let tx = Tx::from_tx(data, storage.find_latest_event_hash(), 1);
+    let block = next_block(&storage, &wallet, vec![tx]);
+    storage.add_block(&block).unwrap();
+    assert_eq!(balance(&storage, &wallet), "40");
+}
+
+fn transfer(storage: &Storage, signer: &Wallet) -> TxData {
+    TxData::new(
+        signer,
+        chain_id(storage),
+        Wallet::new().address_str(),
+        String::from("10"),
+        String::from("0"),
+        2,
+    )
+    .unwrap()
+}
+
+fn balance(storage: &Storage, wallet: &Wallet) -> String {
+    storage.accounts()[&wallet.address_str()]
+        .balance
+        .to_string()
+}
@@ -0,0 +1,66 @@
+mod support;
+
+use storage::storage::Storage;
+use support::{chain_id, next_block, node};
+use tx::tx::Tx;
+use tx::tx_data::TxData;
+use wallet::wallet::Wallet;
+
+#[test]
+fn reject_block_with_expired_tx() {
+    let wallet = Wallet::new();
+    let (_dir, storage) = node(&[&wallet]);
+
+    let block = next_block(
+        &storage,
+        &wallet,
+        vec![transfer(&storage, &wallet, None, Some(0))],
+    );
+    assert!(storage.add_block(&block).is_err());
+
+    let block = next_block(
+        &storage,
+        &wallet,
+        vec![transfer(&storage, &wallet, None, Some(1))],
+    );
+    storage.add_block(&block).unwrap();
+}
+
+#[test]
+fn reject_block_with_premature_tx() {
+    let wallet = Wallet::new();
+    let (_dir, storage) = node(&[&wallet]);
+
+    let block = next_block(
+        &storage,
+        &wallet,
+        vec![transfer(&storage, &wallet, Some(2), None)],
+    );
+    assert!(storage.add_block(&block).is_err());
+
+    let block = next_block(
+        &storage,
+        &wallet,
+        vec![transfer(&storage, &wallet, Some(1), None)],
+    );
+    storage.add_block(&block).unwrap();
+}
+
+fn transfer(
+    storage: &Storage,
+    wallet: &Wallet,
+    valid_from: Option<u64>,
+    valid_until: Option<u64>,
+) -> Tx {
+    let data = TxData::new(
+        wallet,
+        chain_id(storage),
+        Wallet::new().address_str(),
+        String::from("10"),
+        String::from("0"),
+        2,
+    )
+    .and_then(|data| data.valid_between(wallet, valid_from, valid_until))
+    .unwrap();
+    Tx::from_tx(data, storage.find_latest_event_hash(), 1)
+}
@@ -0,0 +1,176 @@
+use account::account::Account;
+use common::biginteger::BigInt;
+use std::collections::BTreeMap;
+use storage::stake_storage::{StakeStorage, Stakes};
+use storage::storage::Storage;
+
+#[test]
+fn select_validator_small_stakes() {
+    let stakes = stakes(&[
+        ("GENESIS", "0"),
+        ("alice", "10"),
+        ("bob", "20"),
+        ("carol", "30"),
+    ]);
+    let vectors = [
+        (
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "alice",
+        ),
+        (
+            "000000000000000000000000000000000000000000000000000000000000000a",
+            "bob",
+        ),
+        (
+            "000000000000000000000000000000000000000000000000000000000000001d",
+            "bob",
+        ),
+        (
+            "000000000000000000000000000000000000000000000000000000000000001e",
+            "carol",
+        ),
+        (
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "bob",
+        ),
+        (
+            "0d68231eaac7124945f00a477bd6a7ecc9154e2388302f81c1c78fda8258fc5f",
+            "carol",
+        ),
+    ];
+    for (hash, expected) in vectors {
+        assert_eq!(
+            Storage::select_validator(hex(hash), &stakes),
+            Some(String::from(expected)),
+            "hash: {}",
+            hash
+        );
+    }
+}
+
+#[test]
+fn select_validator_stakes_above_u64() {
+    let stakes = stakes(&[
+        ("alice", "1208925819614629174706176"),
+        ("bob", "2417851639229258349412352"),
+        ("carol", "18446744073709551617"),
+    ]);
+    let vectors = [
+        (
+            "b8c6f33f1780d30977c5e964f62e7959102a3694f1c28ae0834ab12f98a3dcb0",
+            "bob",
+        ),
+        (
+            "89a1a98e709fa672374b463bbd8d5946ff4f530c5e65be07bf17ef8473ec96e9",
+            "alice",
+        ),
+        (
+            "00000000000000000000000000000000000000000002ffffffffffffffffffff",
+            "bob",
+        ),
+        (
+            "0000000000000000000000000000000000000000000300000000000000000000",
+            "carol",
+        ),
+    ];
+    for (hash, expected) in vectors {
+        assert_eq!(
+            Storage::select_validator(hex(hash), &stakes),
+            Some(String::from(expected)),
+            "hash: {}",
+            hash
+        );
+    }
+}
+
+#[test]
+fn select_validator_without_stake() {
+    let stakes = stakes(&[("GENESIS", "0"), ("alice", "0")]);
+    assert_eq!(Storage::select_validator([0xff; 32], &stakes), None);
+    assert_eq!(
+        Storage::select_validator([0xff; 32], &BTreeMap::new()),
+        None
+    );
+}
+
+#[test]
+fn eligible_proportionally_to_stake() {
+    let stakes = stakes(&[("alice", "1"), ("bob", "3")]);
+    let mut quarter = [0u8; 32];
+    quarter[0] = 0x40;
+    let mut below_quarter = [0xff; 32];
+    below_quarter[0] = 0x3f;
+    assert!(Storage::eligible("alice", below_quarter, &stakes));
+    assert!(!Storage::eligible("alice", quarter, &stakes));
+    assert!(Storage::eligible("bob", [0xbf; 32], &stakes));
+    assert!(!Storage::eligible("bob", [0xc0; 32], &stakes));
+    assert!(!Storage::eligible("carol", [0; 32], &stakes));
+}
+
+#[test]
+fn delegations_count_toward_weight() {
+    let mut accounts = BTreeMap::new();
+    for (wallet, stake) in [("alice", "1"), ("bob", "1"), ("carol", "0")] {
+        let mut account = Account::new(wallet.to_string());
+        account.stake = BigInt::from_str(stake).unwrap();
+        accounts.insert(wallet.to_string(), account);
+    }
+    let bob = accounts.get_mut("bob").unwrap();
+    bob.delegators
+        .insert(String::from("dave"), BigInt::from_str("2").unwrap());
+    let carol = accounts.get_mut("carol").unwrap();
+    carol
+        .delegators
+        .insert(String::from("dave"), BigInt::from_str("5").unwrap());
+    let stakes = StakeStorage::weights(accounts.into_values());
+
+    assert!(Storage::eligible("bob", [0xbf; 32], &stakes));
+    assert!(!Storage::eligible("bob", [0xc0; 32], &stakes));
+    assert!(!Storage::eligible("carol", [0; 32], &stakes));
+    assert_eq!(
+        Storage::select_validator(hex(&format!("{:064x}", 3)), &stakes),
+        Some(String::from("bob"))
+    );
+}
+
+#[test]
+fn backup_ranking_covers_staked_validators() {
+    let stakes = stakes(&[
+        ("GENESIS", "0"),
+        ("alice", "10"),
+        ("bob", "20"),
+        ("carol", "30"),
+    ]);
+    let ranking = Storage::backup_ranking("parent", &stakes);
+    let mut sorted = ranking.clone();
+    sorted.sort();
+    assert_eq!(sorted, vec!["alice", "bob", "carol"]);
+    assert_eq!(Storage::backup_ranking("parent", &stakes), ranking);
+
+    assert_eq!(Storage::backup_proposer("parent", 7, 8, &stakes), None);
+    for rank in 0..6 {
+        assert_eq!(
+            Storage::backup_proposer("parent", 7, 9 + rank, &stakes),
+            Some(ranking[rank as usize % 3].clone())
+        );
+    }
+    assert_eq!(
+        Storage::backup_proposer("parent", 7, 9, &BTreeMap::new()),
+        None
+    );
+}
+
+fn stakes(values: &[(&str, &str)]) -> Stakes {
+    values
+        .iter()
+        .map(|(wallet, stake)| (wallet.to_string(), BigInt::from_str(stake).unwrap()))
+        .collect()
+}
+
+fn hex(value: &str) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&value[i * 2..i * 2 + 2], 16).unwrap();
+    }
+    bytes
+}
@@ -0,0 +1,84 @@
+use common::codec::encode;
+use crypto::crypto::verify_signature;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SignedHeader {
+    #[serde(default)]
+    pub chain_id: String,
+    pub idx: u64,
+    pub slot: u64,
+    pub timestamp: u64,
+    pub validator: String,
+    pub parent_hash: String,
+    pub merkle_root: String,
+    pub vrf_proof: String,
+    pub signature: String,
+}
+
+impl SignedHeader {
+    pub fn hash(&self) -> [u8; 32] {
+        let data = encode(&(
+            &self.chain_id,
+            self.idx,
+            self.slot,
+            self.timestamp,
+            &self.validator,
+            &self.parent_hash,
+            &self.merkle_root,
+            &self.vrf_proof,
+        ))
+        .unwrap();
+        sha2::Sha256::digest(data).into()
+    }
+
+    pub fn valid(&self) -> bool {
+        match bs58::decode(&self.validator).into_vec() {
+            Ok(public_key) => {
+                if let Ok(public_key) = public_key.try_into() {
+                    verify_signature(public_key, &self.signature, &self.hash())
+                } else {
+                    false
+                }
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Evidence {
+    pub first: SignedHeader,
+    pub second: SignedHeader,
+}
+
+impl Evidence {
+    pub fn new(first: SignedHeader, second: SignedHeader) -> Self {
+        Self { first, second }
+    }
+
+    pub fn validator(&self) -> String {
+        self.first.validator.clone()
+    }
+
+    pub fn idx(&self) -> u64 {
+        self.first.idx
+    }
+
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = sha2::Sha256::new();
+        hasher.update(self.first.hash());
+        hasher.update(self.second.hash());
+        hasher.finalize().into()
+    }
+
+    pub fn valid(&self) -> bool {
+        self.first.idx == self.second.idx
+            && self.first.slot == self.second.slot
+            && self.first.validator == self.second.validator
+            && self.first.hash() != self.second.hash()
+            && self.first.valid()
+            && self.second.valid()
+    }
+}
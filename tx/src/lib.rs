@@ -1,3 +1,6 @@
+pub mod evidence;
+pub mod multisig;
+pub mod payload;
 pub mod tx;
 pub mod tx_storage;
 pub mod tx_data;
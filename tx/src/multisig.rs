@@ -0,0 +1,68 @@
+use common::codec::encode;
+use crypto::crypto::verify_signature;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::collections::BTreeMap;
+
+pub const MAX_MULTISIG_KEYS: usize = 16;
+
+/// M-of-N spending policy. Its address is the hash of the policy, so it can
+/// never collide with a single key address, which is a 33 byte public key.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Multisig {
+    pub threshold: u8,
+    pub keys: Vec<String>,
+}
+
+impl Multisig {
+    pub fn new(threshold: u8, mut keys: Vec<String>) -> Result<Self, std::io::Error> {
+        keys.sort();
+        keys.dedup();
+        let multisig = Self { threshold, keys };
+        if !multisig.valid() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Multisig needs 1 to {} distinct keys and a threshold between 1 and the number of keys",
+                    MAX_MULTISIG_KEYS
+                ),
+            ));
+        }
+        Ok(multisig)
+    }
+
+    pub fn address(&self) -> String {
+        let data = encode(&(self.threshold, &self.keys)).unwrap();
+        let hash: [u8; 32] = sha2::Sha256::digest(data).into();
+        bs58::encode(hash).into_string()
+    }
+
+    /// Keys must be sorted and distinct so every policy has a single address.
+    pub fn valid(&self) -> bool {
+        let threshold = self.threshold as usize;
+        threshold > 0
+            && threshold <= self.keys.len()
+            && self.keys.len() <= MAX_MULTISIG_KEYS
+            && self.keys.windows(2).all(|pair| pair[0] < pair[1])
+    }
+
+    /// Returns true if every signature is a valid one by a policy key and there
+    /// are at least `threshold` of them.
+    pub fn verify(&self, hash: &[u8; 32], signatures: &BTreeMap<String, String>) -> bool {
+        self.valid()
+            && signatures.len() >= self.threshold as usize
+            && signatures.iter().all(|(key, signature)| {
+                self.keys.binary_search(key).is_ok() && verify_key(key, signature, hash)
+            })
+    }
+}
+
+fn verify_key(key: &str, signature: &String, hash: &[u8; 32]) -> bool {
+    match bs58::decode(key).into_vec() {
+        Ok(public_key) => match public_key.try_into() {
+            Ok(public_key) => verify_signature(public_key, signature, hash),
+            Err(_) => false,
+        },
+        Err(_) => false,
+    }
+}
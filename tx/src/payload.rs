@@ -0,0 +1,211 @@
+use crate::evidence::Evidence;
+use common::amount::Amount;
+use serde::{Deserialize, Serialize};
+
+pub const MAX_BATCH_OUTPUTS: usize = 256;
+
+/// Variants are encoded by their index, so new ones must only be appended.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Payload {
+    Transfer {
+        to: String,
+        amount: Amount,
+    },
+    Stake {
+        amount: Amount,
+    },
+    Unstake {
+        amount: Amount,
+    },
+    Genesis {
+        to: String,
+        amount: Amount,
+    },
+    Coinbase {
+        to: String,
+        amount: Amount,
+    },
+    Evidence {
+        evidence: Box<Evidence>,
+    },
+    Delegate {
+        validator: String,
+        amount: Amount,
+    },
+    Undelegate {
+        validator: String,
+        amount: Amount,
+    },
+    Commission {
+        rate: Amount,
+    },
+    Batch {
+        outputs: Vec<Output>,
+    },
+    HtlcCreate {
+        to: String,
+        amount: Amount,
+        hashlock: String,
+        timeout: u64,
+    },
+    HtlcClaim {
+        sender: String,
+        id: String,
+        preimage: String,
+    },
+    HtlcRefund {
+        id: String,
+    },
+    Vesting {
+        to: String,
+        amount: Amount,
+        start: u64,
+        cliff: u64,
+        end: u64,
+    },
+    AssetCreate {
+        symbol: String,
+        decimals: u8,
+        max_supply: Amount,
+        mint_authority: String,
+    },
+    AssetMint {
+        asset: String,
+        to: String,
+        amount: Amount,
+    },
+    AssetTransfer {
+        asset: String,
+        to: String,
+        amount: Amount,
+    },
+    AssetBurn {
+        asset: String,
+        amount: Amount,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Output {
+    pub to: String,
+    pub amount: Amount,
+}
+
+impl Payload {
+    pub fn to(&self) -> Option<String> {
+        match self {
+            Payload::Transfer { to, .. }
+            | Payload::Genesis { to, .. }
+            | Payload::Coinbase { to, .. }
+            | Payload::HtlcCreate { to, .. }
+            | Payload::Vesting { to, .. }
+            | Payload::AssetMint { to, .. }
+            | Payload::AssetTransfer { to, .. } => Some(to.clone()),
+            Payload::HtlcClaim { sender, .. } => Some(sender.clone()),
+            Payload::Delegate { validator, .. } | Payload::Undelegate { validator, .. } => {
+                Some(validator.clone())
+            }
+            Payload::Evidence { evidence } => Some(evidence.validator()),
+            Payload::Stake { .. }
+            | Payload::Unstake { .. }
+            | Payload::Commission { .. }
+            | Payload::Batch { .. }
+            | Payload::HtlcRefund { .. }
+            | Payload::AssetCreate { .. }
+            | Payload::AssetBurn { .. } => None,
+        }
+    }
+
+    /// Returns every wallet receiving tokens or affected by the payload, once.
+    pub fn recipients(&self) -> Vec<String> {
+        match self {
+            Payload::Batch { outputs } => {
+                let mut recipients: Vec<String> = Vec::new();
+                for output in outputs {
+                    if !recipients.contains(&output.to) {
+                        recipients.push(output.to.clone());
+                    }
+                }
+                recipients
+            }
+            _ => self.to().into_iter().collect(),
+        }
+    }
+
+    /// Returns every wallet whose account the payload reads or writes besides
+    /// the sender, including the issuer holding an asset's definition.
+    pub fn touched_accounts(&self) -> Vec<String> {
+        let mut accounts = self.recipients();
+        let issuer = match self {
+            Payload::AssetMint { asset, .. }
+            | Payload::AssetTransfer { asset, .. }
+            | Payload::AssetBurn { asset, .. } => asset.split_once(':').map(|(issuer, _)| issuer),
+            _ => None,
+        };
+        if let Some(issuer) = issuer
+            && !accounts.iter().any(|account| account == issuer)
+        {
+            accounts.push(issuer.to_string());
+        }
+        accounts
+    }
+
+    /// Returns the amount of native coin moved; asset payloads move none.
+    pub fn amount(&self) -> Amount {
+        match self {
+            Payload::Transfer { amount, .. }
+            | Payload::Stake { amount }
+            | Payload::Unstake { amount }
+            | Payload::Genesis { amount, .. }
+            | Payload::Coinbase { amount, .. }
+            | Payload::Delegate { amount, .. }
+            | Payload::Undelegate { amount, .. }
+            | Payload::HtlcCreate { amount, .. }
+            | Payload::Vesting { amount, .. } => *amount,
+            Payload::Evidence { .. }
+            | Payload::Commission { .. }
+            | Payload::HtlcClaim { .. }
+            | Payload::HtlcRefund { .. }
+            | Payload::AssetCreate { .. }
+            | Payload::AssetMint { .. }
+            | Payload::AssetTransfer { .. }
+            | Payload::AssetBurn { .. } => Amount::zero(),
+            Payload::Batch { outputs } => {
+                Self::total(outputs).unwrap_or(Amount::from_units(u128::MAX))
+            }
+        }
+    }
+
+    pub fn valid(&self) -> bool {
+        match self {
+            Payload::Batch { outputs } => {
+                !outputs.is_empty()
+                    && outputs.len() <= MAX_BATCH_OUTPUTS
+                    && Self::total(outputs).is_some()
+            }
+            _ => true,
+        }
+    }
+
+    fn total(outputs: &[Output]) -> Option<Amount> {
+        outputs.iter().try_fold(Amount::zero(), |total, output| {
+            total.checked_add(output.amount)
+        })
+    }
+
+    /// Returns the fee required at the given rate per token moved; payloads
+    /// moving no tokens pay the rate itself.
+    pub fn required_fee(&self, rate: Amount) -> Option<Amount> {
+        let amount = self.amount();
+        if amount == Amount::zero() {
+            return Some(rate);
+        }
+        rate.checked_mul(amount)
+    }
+
+    /// Returns true for payloads minting new tokens, which only blocks can carry.
+    pub fn is_mint(&self) -> bool {
+        matches!(self, Payload::Genesis { .. } | Payload::Coinbase { .. })
+    }
+}
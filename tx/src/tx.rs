@@ -1,5 +1,7 @@
-use crate::tx_data::TxData;
-use common::bigdecimal::BigDecimal;
+use crate::payload::Payload;
+use crate::tx_data::{GENESIS_WALLET, TxData};
+use common::amount::Amount;
+use common::codec::encode;
 use serde::{Deserialize, Serialize};
 use sha2::Digest;
 
@@ -31,12 +33,20 @@ impl Tx {
         self.data.nonce
     }
 
-    pub fn to(&self) -> String {
-        self.data.to.to_string()
+    pub fn to(&self) -> Option<String> {
+        self.data.to()
     }
 
-    pub fn amount(&self) -> BigDecimal {
-        self.data.amount.clone()
+    pub fn recipients(&self) -> Vec<String> {
+        self.data.recipients()
+    }
+
+    pub fn amount(&self) -> Amount {
+        self.data.amount()
+    }
+
+    pub fn payload(&self) -> &Payload {
+        &self.data.payload
     }
 
     pub fn prev_hash(&self) -> String {
@@ -44,11 +54,8 @@ impl Tx {
     }
 
     pub fn hash(&self) -> [u8; 32] {
-        let mut hasher = sha2::Sha256::new();
-        hasher.update(self.prev_hash.as_bytes());
-        hasher.update(self.block.to_be_bytes());
-        hasher.update(self.data.hash());
-        hasher.finalize().into()
+        let data = encode(&(&self.prev_hash, self.block, self.data.hash())).unwrap();
+        sha2::Sha256::digest(data).into()
     }
 
     pub fn hash_str(&self) -> String {
@@ -56,22 +63,28 @@ impl Tx {
         bs58::encode(&hash).into_string()
     }
 
+    /// Genesis transactions are unsigned and only trusted by `init_genesis`.
     pub fn valid(&self) -> bool {
-        if self.block == 0 {
-            return true;
+        if self.is_coinbase() {
+            return self.hash == self.hash_str();
         }
         self.hash == self.hash_str() && self.data.valid()
     }
 
-    pub fn fee(&self) -> BigDecimal {
-        self.data.fee()
+    /// Returns true for txs only a genesis file can carry.
+    pub fn is_genesis(&self) -> bool {
+        matches!(self.data.payload, Payload::Genesis { .. }) || self.data.from == GENESIS_WALLET
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        matches!(self.data.payload, Payload::Coinbase { .. })
     }
 
-    pub fn fee_amount(&self) -> BigDecimal {
-        self.data.fee_amount()
+    pub fn fee(&self) -> Amount {
+        self.data.fee()
     }
 
-    pub fn amount_with_fee(&self) -> BigDecimal {
-        self.amount() + self.fee()
+    pub fn amount_with_fee(&self) -> Option<Amount> {
+        self.amount().checked_add(self.fee())
     }
 }
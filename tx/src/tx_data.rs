@@ -1,60 +1,240 @@
-use common::bigdecimal::BigDecimal;
+use crate::evidence::Evidence;
+use crate::multisig::Multisig;
+use crate::payload::Payload;
+use common::amount::Amount;
+use common::codec::encode;
 use crypto::crypto::verify_signature;
 use serde::{Deserialize, Serialize};
 use sha2::Digest;
+use std::collections::BTreeMap;
 use wallet::wallet::Wallet;
 
+pub const TX_VERSION: u8 = 1;
+pub const GENESIS_WALLET: &str = "GENESIS";
+pub const COINBASE_WALLET: &str = "COINBASE";
+pub const MAX_MEMO_SIZE: usize = 256;
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct TxData {
+    pub version: u8,
+    #[serde(default)]
+    pub chain_id: String,
     pub from: String,
-    pub to: String,
-    pub amount: BigDecimal,
-    pub fee: BigDecimal,
+    pub payload: Payload,
+    pub fee: Amount,
     pub nonce: u64,
+    #[serde(default)]
+    pub valid_from_block: Option<u64>,
+    #[serde(default)]
+    pub valid_until_block: Option<u64>,
+    #[serde(default)]
+    pub memo: Option<String>,
+    #[serde(default)]
+    pub multisig: Option<Multisig>,
     pub signature: String,
+    #[serde(default)]
+    pub signatures: BTreeMap<String, String>,
 }
 
 impl TxData {
     pub fn new(
         wallet: &Wallet,
+        chain_id: String,
         to: String,
         amount: String,
         fee: String,
         nonce: u64,
     ) -> Result<Self, std::io::Error> {
-        let amount = BigDecimal::from_str(amount.as_str())?;
-        let fee = BigDecimal::from_str(fee.as_str())?;
-        let mut tx = Self {
-            from: wallet.address_str(),
-            to,
-            amount,
+        let amount = Amount::from_str(amount.as_str())?;
+        Self::with_payload(
+            wallet,
+            chain_id,
+            Payload::Transfer { to, amount },
             fee,
             nonce,
+        )
+    }
+
+    pub fn with_payload(
+        wallet: &Wallet,
+        chain_id: String,
+        payload: Payload,
+        fee: String,
+        nonce: u64,
+    ) -> Result<Self, std::io::Error> {
+        let mut tx = Self::unsigned(chain_id, wallet.address_str(), payload, fee, nonce)?;
+        tx.sign(wallet)?;
+        Ok(tx)
+    }
+
+    /// Creates a transaction spending from the multisig address, carrying the
+    /// partial signature of `wallet`, which must be one of the policy keys.
+    pub fn with_multisig(
+        wallet: &Wallet,
+        chain_id: String,
+        multisig: Multisig,
+        payload: Payload,
+        fee: String,
+        nonce: u64,
+    ) -> Result<Self, std::io::Error> {
+        let mut tx = Self::unsigned(chain_id, multisig.address(), payload, fee, nonce)?;
+        tx.multisig = Some(multisig);
+        tx.sign(wallet)?;
+        Ok(tx)
+    }
+
+    fn unsigned(
+        chain_id: String,
+        from: String,
+        payload: Payload,
+        fee: String,
+        nonce: u64,
+    ) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            version: TX_VERSION,
+            chain_id,
+            from,
+            payload,
+            fee: Amount::from_str(fee.as_str())?,
+            nonce,
+            valid_from_block: None,
+            valid_until_block: None,
+            memo: None,
+            multisig: None,
             signature: "".to_string(),
+            signatures: BTreeMap::new(),
+        })
+    }
+
+    /// Signs the transaction, dropping signatures made over its previous
+    /// content. Multisig transactions get a partial signature of `wallet`.
+    fn sign(&mut self, wallet: &Wallet) -> Result<(), std::io::Error> {
+        if self.multisig.is_some() {
+            self.signatures.clear();
+            return self.sign_partial(wallet);
+        }
+        self.signature = wallet.sign(&self.hash())?;
+        Ok(())
+    }
+
+    pub fn sign_partial(&mut self, wallet: &Wallet) -> Result<(), std::io::Error> {
+        let Some(multisig) = &self.multisig else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Not a multisig transaction",
+            ));
         };
-        let signature = wallet.sign(&tx.hash())?;
-        tx.signature = signature;
-        Ok(tx)
+        let key = wallet.address_str();
+        if !multisig.keys.contains(&key) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is not a key of the multisig", key),
+            ));
+        }
+        let signature = wallet.sign(&self.hash())?;
+        self.signatures.insert(key, signature);
+        Ok(())
+    }
+
+    /// Merges the partial signatures of another copy of the same transaction.
+    pub fn combine(mut self, other: TxData) -> Result<Self, std::io::Error> {
+        if self.hash() != other.hash() || self.multisig != other.multisig {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Cannot combine signatures of different transactions",
+            ));
+        }
+        self.signatures.extend(other.signatures);
+        Ok(self)
+    }
+
+    /// Limits the blocks the transaction can be included in, both bounds
+    /// inclusive, and signs it again.
+    pub fn valid_between(
+        mut self,
+        wallet: &Wallet,
+        valid_from_block: Option<u64>,
+        valid_until_block: Option<u64>,
+    ) -> Result<Self, std::io::Error> {
+        self.valid_from_block = valid_from_block;
+        self.valid_until_block = valid_until_block;
+        self.sign(wallet)?;
+        Ok(self)
+    }
+
+    /// Attaches a memo, e.g. a deposit id, and signs the transaction again.
+    pub fn with_memo(mut self, wallet: &Wallet, memo: String) -> Result<Self, std::io::Error> {
+        if memo.len() > MAX_MEMO_SIZE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Memo exceeds {} bytes", MAX_MEMO_SIZE),
+            ));
+        }
+        self.memo = Some(memo);
+        self.sign(wallet)?;
+        Ok(self)
+    }
+
+    pub fn new_evidence(
+        wallet: &Wallet,
+        chain_id: String,
+        evidence: Evidence,
+        fee: String,
+        nonce: u64,
+    ) -> Result<Self, std::io::Error> {
+        Self::with_payload(
+            wallet,
+            chain_id,
+            Payload::Evidence {
+                evidence: Box::new(evidence),
+            },
+            fee,
+            nonce,
+        )
+    }
+
+    pub fn coinbase(chain_id: String, validator: String, reward: Amount, block: u64) -> Self {
+        Self {
+            version: TX_VERSION,
+            chain_id,
+            from: String::from(COINBASE_WALLET),
+            payload: Payload::Coinbase {
+                to: validator,
+                amount: reward,
+            },
+            fee: Amount::zero(),
+            nonce: block,
+            valid_from_block: None,
+            valid_until_block: None,
+            memo: None,
+            multisig: None,
+            signature: String::from(COINBASE_WALLET),
+            signatures: BTreeMap::new(),
+        }
     }
 
     pub fn from(&self) -> String {
         self.from.clone()
     }
 
-    pub fn to(&self) -> String {
-        self.from.clone()
+    pub fn to(&self) -> Option<String> {
+        self.payload.to()
+    }
+
+    pub fn recipients(&self) -> Vec<String> {
+        self.payload.recipients()
     }
 
-    pub fn amount(&self) -> BigDecimal {
-        self.amount.clone()
+    pub fn amount(&self) -> Amount {
+        self.payload.amount()
     }
 
     pub fn nonce(&self) -> u64 {
         self.nonce
     }
 
-    pub fn fee(&self) -> BigDecimal {
-        self.fee.clone()
+    pub fn fee(&self) -> Amount {
+        self.fee
     }
 
     pub fn signature(&self) -> String {
@@ -62,16 +242,40 @@ impl TxData {
     }
 
     pub fn hash(&self) -> [u8; 32] {
-        let mut hasher = sha2::Sha256::new();
-        hasher.update(self.from.as_bytes());
-        hasher.update(self.to.as_bytes());
-        hasher.update(self.amount.to_string().as_bytes());
-        hasher.update(self.fee.to_string().as_bytes());
-        hasher.update(self.nonce.to_be_bytes());
-        hasher.finalize().into()
+        let data = encode(&(
+            self.version,
+            &self.chain_id,
+            &self.from,
+            &self.payload,
+            &self.fee,
+            self.nonce,
+            self.valid_from_block,
+            self.valid_until_block,
+            &self.memo,
+        ))
+        .unwrap();
+        sha2::Sha256::digest(data).into()
+    }
+
+    pub fn hash_str(&self) -> String {
+        bs58::encode(self.hash()).into_string()
     }
 
     pub fn valid(&self) -> bool {
+        if self.version != TX_VERSION
+            || self.payload.is_mint()
+            || !self.payload.valid()
+            || self.memo_size() > MAX_MEMO_SIZE
+        {
+            return false;
+        }
+        if let Some(multisig) = &self.multisig {
+            return multisig.address() == self.from
+                && multisig.verify(&self.hash(), &self.signatures);
+        }
+        if !self.signatures.is_empty() {
+            return false;
+        }
         match bs58::decode(&self.from).into_vec() {
             Ok(public_key) => {
                 if let Ok(public_key) = public_key.try_into() {
@@ -84,10 +288,23 @@ impl TxData {
         }
     }
 
-    pub fn fee_amount(&self) -> BigDecimal {
-        if self.fee == BigDecimal::zero() {
-            return BigDecimal::zero();
-        }
-        self.amount.clone() / self.fee.clone()
+    pub fn valid_at(&self, block: u64) -> bool {
+        self.valid_from_block.is_none_or(|from| from <= block)
+            && self.valid_until_block.is_none_or(|until| block <= until)
+    }
+
+    pub fn memo_size(&self) -> usize {
+        self.memo.as_ref().map_or(0, String::len)
+    }
+
+    /// Returns the fee required at the given rate, with each memo byte
+    /// charged like one more token moved.
+    pub fn required_fee(&self, rate: Amount) -> Option<Amount> {
+        let memo_fee = Self::memo_fee(self.memo_size(), rate)?;
+        self.payload.required_fee(rate)?.checked_add(memo_fee)
+    }
+
+    pub fn memo_fee(memo_size: usize, rate: Amount) -> Option<Amount> {
+        rate.mul_div(memo_size as u128, 1)
     }
 }
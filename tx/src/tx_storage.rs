@@ -1,4 +1,6 @@
 use crate::tx::Tx;
+use common::codec::{decode, encode};
+use db::batch::Batch;
 use rocksdb::{DBWithThreadMode, MultiThreaded};
 use std::sync::Arc;
 
@@ -15,55 +17,119 @@ impl TxStorage {
     }
 
     pub fn find_latest_hash(&self) -> Result<Option<String>, std::io::Error> {
-        if let Some(value) = self
-            .db
-            .get(LATEST_HASH_KEY)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
-        {
+        self.find_staged_latest_hash(&Batch::new(&self.db))
+    }
+
+    pub fn find_staged_latest_hash(&self, batch: &Batch) -> Result<Option<String>, std::io::Error> {
+        if let Some(value) = batch.get(LATEST_HASH_KEY)? {
             Ok(Some(String::from_utf8(value).unwrap()))
         } else {
             Ok(None)
         }
     }
 
-    fn save_latest_hash(&self, hash: String) -> Result<(), std::io::Error> {
-        self.db
-            .put(LATEST_HASH_KEY, hash.as_bytes())
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-        Ok(())
+    fn save_latest_hash(&self, batch: &mut Batch, hash: String) {
+        batch.put(LATEST_HASH_KEY, hash.as_bytes());
     }
 
     pub fn save(&self, txs: &Vec<Tx>, block: u64) -> Result<(), std::io::Error> {
+        let mut batch = Batch::new(&self.db);
+        self.stage(&mut batch, txs, block)?;
+        batch.commit()
+    }
+
+    pub fn stage(
+        &self,
+        batch: &mut Batch,
+        txs: &Vec<Tx>,
+        block: u64,
+    ) -> Result<(), std::io::Error> {
         for tx in txs {
-            self.save_without_idx(tx)?;
-            self.add_to_txs_index(tx.from(), tx.hash_str())?;
-            self.add_to_txs_index(tx.to(), tx.hash_str())?;
+            self.save_without_idx(batch, tx)?;
+            self.add_to_txs_index(batch, tx.from(), tx.hash_str())?;
+            for to in tx.recipients() {
+                self.add_to_txs_index(batch, to, tx.hash_str())?;
+            }
         }
-        self.save_block_idx(txs, block)?;
+        self.save_block_idx(batch, txs, block)?;
         if let Some(latest) = txs.last() {
-            self.save_latest_hash(latest.hash_str())?;
+            self.save_latest_hash(batch, latest.hash_str());
+        }
+        Ok(())
+    }
+
+    pub fn stage_remove(
+        &self,
+        batch: &mut Batch,
+        txs: &[Tx],
+        block: u64,
+        latest_hash: Option<String>,
+    ) -> Result<(), std::io::Error> {
+        for tx in txs.iter().rev() {
+            for to in tx.recipients().into_iter().rev() {
+                self.remove_from_txs_index(batch, to, tx.hash_str())?;
+            }
+            self.remove_from_txs_index(batch, tx.from(), tx.hash_str())?;
+            batch.delete(self.build_key(&tx.hash_str()));
+        }
+        batch.delete(self.build_key(&block.to_string()));
+        match latest_hash {
+            Some(hash) => self.save_latest_hash(batch, hash),
+            None => batch.delete(LATEST_HASH_KEY),
         }
         Ok(())
     }
 
-    fn add_to_txs_index(&self, wallet: String, tx_hash: String) -> Result<(), std::io::Error> {
-        let mut txs = self.find_wallet_txs_hashes(wallet.clone())?;
+    fn add_to_txs_index(
+        &self,
+        batch: &mut Batch,
+        wallet: String,
+        tx_hash: String,
+    ) -> Result<(), std::io::Error> {
+        let key = self.build_key(&wallet);
+        let mut txs: Vec<String> = match batch.get(&key)? {
+            Some(txs) => decode(&txs)?,
+            None => Vec::new(),
+        };
         txs.push(tx_hash);
-        let data = serde_json::to_vec(&txs)?;
+        let data = encode(&txs)?;
+        batch.put(key, data);
+        Ok(())
+    }
+
+    fn remove_from_txs_index(
+        &self,
+        batch: &mut Batch,
+        wallet: String,
+        tx_hash: String,
+    ) -> Result<(), std::io::Error> {
         let key = self.build_key(&wallet);
-        self.db
-            .put(key, data)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let mut txs: Vec<String> = match batch.get(&key)? {
+            Some(txs) => decode(&txs)?,
+            None => return Ok(()),
+        };
+        if let Some(position) = txs.iter().rposition(|hash| *hash == tx_hash) {
+            txs.remove(position);
+        }
+        if txs.is_empty() {
+            batch.delete(key);
+        } else {
+            let data = encode(&txs)?;
+            batch.put(key, data);
+        }
         Ok(())
     }
 
-    fn save_block_idx(&self, txs: &Vec<Tx>, block: u64) -> Result<(), std::io::Error> {
+    fn save_block_idx(
+        &self,
+        batch: &mut Batch,
+        txs: &[Tx],
+        block: u64,
+    ) -> Result<(), std::io::Error> {
         let hashes = txs.iter().map(|tx| tx.hash_str()).collect::<Vec<String>>();
-        let data = serde_json::to_vec(&hashes)?;
+        let data = encode(&hashes)?;
         let key = self.build_key(&block.to_string());
-        self.db
-            .put(key, &data)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        batch.put(key, &data);
         Ok(())
     }
 
@@ -74,8 +140,7 @@ impl TxStorage {
             .get(key)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
         {
-            Ok(serde_json::from_slice(&hashes)
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?)
+            decode(&hashes)
         } else {
             Ok(Vec::new())
         }
@@ -88,7 +153,7 @@ impl TxStorage {
             .get(key)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
         {
-            let tx: Tx = serde_json::from_slice(&data)?;
+            let tx: Tx = decode(&data)?;
             Ok(Some(tx))
         } else {
             Ok(None)
@@ -113,7 +178,7 @@ impl TxStorage {
             .get(key)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
         {
-            Some(txs) => Ok(serde_json::from_slice(&txs)?),
+            Some(txs) => Ok(decode(&txs)?),
             None => Ok(Vec::new()),
         }
     }
@@ -133,11 +198,9 @@ impl TxStorage {
         format!("tx.{}", value)
     }
 
-    fn save_without_idx(&self, tx: &Tx) -> Result<(), std::io::Error> {
-        let json = serde_json::to_vec(&tx)?;
-        self.db
-            .put(self.build_key(&tx.hash_str()), json)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    fn save_without_idx(&self, batch: &mut Batch, tx: &Tx) -> Result<(), std::io::Error> {
+        let data = encode(&tx)?;
+        batch.put(self.build_key(&tx.hash_str()), data);
         Ok(())
     }
 }
@@ -0,0 +1,97 @@
+use common::amount::Amount;
+use tx::multisig::{MAX_MULTISIG_KEYS, Multisig};
+use tx::payload::Payload;
+use tx::tx_data::TxData;
+use wallet::wallet::Wallet;
+
+#[test]
+fn address_derived_from_policy() {
+    let keys: Vec<Wallet> = (0..3).map(|_| Wallet::new()).collect();
+    let addresses: Vec<String> = keys.iter().map(Wallet::address_str).collect();
+    let multisig = Multisig::new(2, addresses.clone()).unwrap();
+
+    let reversed = Multisig::new(2, addresses.iter().rev().cloned().collect()).unwrap();
+    assert_eq!(multisig.address(), reversed.address());
+    let threshold = Multisig::new(3, addresses.clone()).unwrap();
+    assert_ne!(multisig.address(), threshold.address());
+    assert!(!addresses.contains(&multisig.address()));
+}
+
+#[test]
+fn reject_invalid_policy() {
+    let addresses: Vec<String> = (0..3).map(|_| Wallet::new().address_str()).collect();
+    assert!(Multisig::new(0, addresses.clone()).is_err());
+    assert!(Multisig::new(4, addresses.clone()).is_err());
+    let duplicated = vec![addresses[0].clone(), addresses[0].clone()];
+    assert!(Multisig::new(2, duplicated).is_err());
+    let too_many = (0..=MAX_MULTISIG_KEYS)
+        .map(|_| Wallet::new().address_str())
+        .collect();
+    assert!(Multisig::new(1, too_many).is_err());
+}
+
+#[test]
+fn spend_needs_threshold_signatures() {
+    let keys: Vec<Wallet> = (0..3).map(|_| Wallet::new()).collect();
+    let multisig = Multisig::new(2, keys.iter().map(Wallet::address_str).collect()).unwrap();
+
+    let tx = transfer(&keys[0], &multisig);
+    assert_eq!(tx.from, multisig.address());
+    assert_eq!(tx.signatures.len(), 1);
+    assert!(!tx.valid());
+
+    let mut other = tx.clone();
+    other.signatures.clear();
+    other.sign_partial(&keys[2]).unwrap();
+    let combined = tx.combine(other).unwrap();
+    assert_eq!(combined.signatures.len(), 2);
+    assert!(combined.valid());
+
+    let mut tampered = combined.clone();
+    tampered.nonce = 2;
+    assert!(!tampered.valid());
+}
+
+#[test]
+fn reject_foreign_signatures() {
+    let keys: Vec<Wallet> = (0..3).map(|_| Wallet::new()).collect();
+    let multisig = Multisig::new(1, keys.iter().map(Wallet::address_str).collect()).unwrap();
+    let outsider = Wallet::new();
+
+    let mut tx = transfer(&keys[0], &multisig);
+    assert!(tx.valid());
+    assert!(tx.sign_partial(&outsider).is_err());
+    tx.signatures
+        .insert(outsider.address_str(), outsider.sign(&tx.hash()).unwrap());
+    assert!(!tx.valid());
+
+    let other = Multisig::new(1, vec![outsider.address_str()]).unwrap();
+    let mut tx = transfer(&keys[0], &multisig);
+    tx.multisig = Some(other);
+    assert!(!tx.valid());
+}
+
+#[test]
+fn reject_combining_different_txs() {
+    let keys: Vec<Wallet> = (0..2).map(|_| Wallet::new()).collect();
+    let multisig = Multisig::new(2, keys.iter().map(Wallet::address_str).collect()).unwrap();
+    let tx = transfer(&keys[0], &multisig);
+    let other = transfer(&keys[1], &multisig);
+    assert!(tx.combine(other).is_err());
+}
+
+fn transfer(wallet: &Wallet, multisig: &Multisig) -> TxData {
+    let payload = Payload::Transfer {
+        to: Wallet::new().address_str(),
+        amount: Amount::from_str("10").unwrap(),
+    };
+    TxData::with_multisig(
+        wallet,
+        String::default(),
+        multisig.clone(),
+        payload,
+        String::from("1"),
+        1,
+    )
+    .unwrap()
+}
@@ -1,4 +1,7 @@
+use common::amount::Amount;
+use db::batch::Batch;
 use db::open;
+use tx::payload::{Output, Payload};
 use tx::tx::Tx;
 use tx::tx_data::TxData;
 use tx::tx_storage::TxStorage;
@@ -14,9 +17,43 @@ fn test_tx_storage_save_find_by_hash() -> Result<(), std::io::Error> {
     let tx_storage = TxStorage::new(&db);
     let from = Wallet::new();
     let to = Wallet::new();
-    let tx_data = TxData::new(&from, to.address_str(), String::from("10"), String::from("10"), 1)?;
+    let tx_data = TxData::new(&from, String::default(), to.address_str(), String::from("10"), String::from("10"), 1)?;
     let tx = Tx::from_tx(tx_data, String::default(), 0);
     tx_storage.save(&vec![tx.clone()], 0)?;
     assert_eq!(tx_storage.find_latest_hash()?.unwrap(), tx.hash_str());
     Ok(())
 }
+
+#[test]
+fn batch_indexed_for_each_recipient() -> Result<(), std::io::Error> {
+    let temp_dir = tempfile::tempdir()?;
+    let db = open(temp_dir.path())?;
+    let tx_storage = TxStorage::new(&db);
+    let from = Wallet::new();
+    let outputs = ["alice", "bob", "alice"]
+        .iter()
+        .map(|to| Output {
+            to: to.to_string(),
+            amount: Amount::from_str("1").unwrap(),
+        })
+        .collect();
+    let tx_data = TxData::with_payload(
+        &from,
+        String::default(),
+        Payload::Batch { outputs },
+        String::from("0"),
+        1,
+    )?;
+    let tx = Tx::from_tx(tx_data, String::default(), 1);
+    tx_storage.save(&vec![tx.clone()], 1)?;
+    for wallet in [from.address_str(), String::from("alice"), String::from("bob")] {
+        assert_eq!(tx_storage.find_wallet_txs(wallet)?, vec![tx.clone()]);
+    }
+
+    let mut batch = Batch::new(&db);
+    tx_storage.stage_remove(&mut batch, &[tx], 1, None)?;
+    batch.commit()?;
+    assert!(tx_storage.find_wallet_txs(String::from("alice"))?.is_empty());
+    assert!(tx_storage.find_wallet_txs(String::from("bob"))?.is_empty());
+    Ok(())
+}
@@ -1,15 +1,144 @@
+use common::amount::Amount;
+use common::codec::{decode, encode};
+use tx::payload::{MAX_BATCH_OUTPUTS, Output, Payload};
 use tx::tx::Tx;
-use tx::tx_data::TxData;
+use tx::tx_data::{MAX_MEMO_SIZE, TxData};
 use wallet::wallet::Wallet;
 
 #[test]
 fn new_tx() {
     let from = Wallet::new();
     let to = Wallet::new();
-    let tx_data = TxData::new(&from, to.address_str(), String::from("0.0001"), String::from("0"), 1).unwrap();
+    let tx_data = TxData::new(&from, String::default(), to.address_str(), String::from("0.0001"), String::from("0"), 1).unwrap();
     let tx_1 = Tx::from_tx(tx_data.clone(), String::default(), 0);
     assert!(tx_1.valid());
     let tx_2 = Tx::from_tx(tx_data, String::default(), 0);
     assert!(tx_2.valid());
     assert_eq!(tx_1.hash, tx_2.hash);
 }
+
+#[test]
+fn signature_covers_payload_type() {
+    let from = Wallet::new();
+    let amount = Amount::from_str("10").unwrap();
+    let mut tx_data = TxData::with_payload(&from, String::default(), Payload::Stake { amount }, String::from("0"), 1).unwrap();
+    assert!(tx_data.valid());
+    tx_data.payload = Payload::Unstake { amount };
+    assert!(!tx_data.valid());
+}
+
+#[test]
+fn reject_unknown_version() {
+    let from = Wallet::new();
+    let to = Wallet::new();
+    let mut tx_data = TxData::new(&from, String::default(), to.address_str(), String::from("1"), String::from("0"), 1).unwrap();
+    tx_data.version += 1;
+    assert!(!tx_data.valid());
+}
+
+#[test]
+fn signature_covers_chain_id() {
+    let from = Wallet::new();
+    let to = Wallet::new();
+    let mut tx_data = TxData::new(&from, String::from("xchg-testnet"), to.address_str(), String::from("1"), String::from("0"), 1).unwrap();
+    assert!(tx_data.valid());
+    tx_data.chain_id = String::from("xchg-mainnet");
+    assert!(!tx_data.valid());
+}
+
+#[test]
+fn validity_window() {
+    let from = Wallet::new();
+    let to = Wallet::new();
+    let tx_data = TxData::new(&from, String::default(), to.address_str(), String::from("1"), String::from("0"), 1).unwrap();
+    assert!(tx_data.valid_at(0));
+    assert!(tx_data.valid_at(u64::MAX));
+
+    let mut tx_data = tx_data.valid_between(&from, Some(5), Some(10)).unwrap();
+    assert!(tx_data.valid());
+    assert!(!tx_data.valid_at(4));
+    assert!(tx_data.valid_at(5));
+    assert!(tx_data.valid_at(10));
+    assert!(!tx_data.valid_at(11));
+
+    tx_data.valid_until_block = Some(20);
+    assert!(!tx_data.valid());
+}
+
+#[test]
+fn memo_is_signed_and_limited() {
+    let from = Wallet::new();
+    let to = Wallet::new();
+    let tx_data = TxData::new(&from, String::default(), to.address_str(), String::from("1"), String::from("0"), 1).unwrap();
+    let mut tx_data = tx_data.with_memo(&from, String::from("deposit-42")).unwrap();
+    assert!(tx_data.valid());
+    let restored: TxData = decode(&encode(&tx_data).unwrap()).unwrap();
+    assert_eq!(restored.memo.as_deref(), Some("deposit-42"));
+
+    tx_data.memo = Some(String::from("deposit-43"));
+    assert!(!tx_data.valid());
+
+    let oversized = "x".repeat(MAX_MEMO_SIZE + 1);
+    assert!(tx_data.clone().with_memo(&from, oversized.clone()).is_err());
+    tx_data.memo = Some(oversized);
+    assert!(!tx_data.valid());
+}
+
+#[test]
+fn fee_scales_with_memo_size() {
+    let from = Wallet::new();
+    let rate = Amount::from_str("0.01").unwrap();
+    let tx_data = TxData::new(&from, String::default(), Wallet::new().address_str(), String::from("10"), String::from("0"), 1).unwrap();
+    assert_eq!(tx_data.required_fee(rate), Amount::from_str("0.1").ok());
+    let tx_data = tx_data.with_memo(&from, String::from("abcd")).unwrap();
+    assert_eq!(tx_data.required_fee(rate), Amount::from_str("0.14").ok());
+}
+
+#[test]
+fn batch_limits() {
+    let from = Wallet::new();
+    let output = |amount: &str| Output { to: Wallet::new().address_str(), amount: Amount::from_str(amount).unwrap() };
+    let batch = |outputs: Vec<Output>| TxData::with_payload(&from, String::default(), Payload::Batch { outputs }, String::from("0"), 1).unwrap();
+
+    let tx_data = batch(vec![output("1"), output("2.5")]);
+    assert!(tx_data.valid());
+    assert_eq!(tx_data.amount(), Amount::from_str("3.5").unwrap());
+    assert_eq!(tx_data.recipients().len(), 2);
+
+    assert!(!batch(vec![]).valid());
+    assert!(!batch((0..=MAX_BATCH_OUTPUTS).map(|_| output("1")).collect()).valid());
+    let max = Amount::from_units(u128::MAX).to_string();
+    assert!(!batch(vec![output(&max), output("1")]).valid());
+}
+
+#[test]
+fn payload_encoding_vector() {
+    let payload = Payload::Stake { amount: Amount::from_str("10.0").unwrap() };
+    let data = encode(&payload).unwrap();
+    assert_eq!(data, vec![0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2, b'1', b'0']);
+    assert_eq!(decode::<Payload>(&data).unwrap(), payload);
+}
+
+#[test]
+fn encoding_round_trips() {
+    let from = Wallet::new();
+    let to = Wallet::new();
+    let tx_data = TxData::new(&from, String::from("xchg-testnet"), to.address_str(), String::from("0.5"), String::from("0.01"), 1).unwrap();
+    let restored: TxData = decode(&encode(&tx_data).unwrap()).unwrap();
+    assert_eq!(restored, tx_data);
+    assert!(restored.valid());
+
+    let tx = Tx::from_tx(tx_data, String::from("prev"), 3);
+    let restored: Tx = decode(&encode(&tx).unwrap()).unwrap();
+    assert_eq!(restored, tx);
+    assert!(restored.valid());
+}
+
+#[test]
+fn equal_decimals_hash_equal() {
+    let from = Wallet::new();
+    let to = Wallet::new();
+    let tx = |amount: &str| TxData::new(&from, String::default(), to.address_str(), String::from(amount), String::from("0"), 1).unwrap();
+    assert_eq!(tx("10").hash(), tx("10.00").hash());
+    assert_ne!(tx("10").hash(), tx("1").hash());
+}
@@ -1,4 +1,5 @@
 use crypto::crypto::{decrypt_data, derive_key, encrypt_data, restore_key};
+use crypto::vrf;
 use libp2p::identity::secp256k1;
 use std::fs;
 use std::fs::{File, OpenOptions};
@@ -86,6 +87,11 @@ impl Wallet {
         Ok(bs58::encode(self.keypair.secret().sign(data)).into_string())
     }
 
+    pub fn vrf_prove(&self, alpha: &[u8]) -> Result<(String, [u8; 32]), std::io::Error> {
+        let (proof, output) = vrf::prove(&self.secret(), alpha)?;
+        Ok((bs58::encode(proof).into_string(), output))
+    }
+
     pub fn verify(&self, data: &[u8; 32], signature: String) -> bool {
         let signature = bs58::encode(signature).into_vec();
         self.keypair.public().verify(data, &signature)